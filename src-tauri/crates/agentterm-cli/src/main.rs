@@ -0,0 +1,230 @@
+use std::env;
+use std::io::Read;
+use std::path::PathBuf;
+
+use agentterm_shared::control::{tool_value, ControlClient};
+use agentterm_shared::socket_path::{control_socket_path, control_token_path};
+use serde_json::{json, Value};
+
+const USAGE: &str = "usage: agent-term [--socket <path>] <command> [args]
+
+commands:
+  list [--json]
+  create [--tool <tool>] [--title <title>] [--path <dir>] [--section <id>]
+         [--command <cmd>] [--start]
+  start <id> [--rows <n>] [--cols <n>]
+  stop <id>
+  write <id> [--enter] <text...>     use - as text to read stdin
+  output <id> [--lines <n>]      plain-text tail of the session's output
+  status <id> [--json]";
+
+#[tokio::main]
+async fn main() {
+    if env::args().any(|a| a == "--version" || a == "-V") {
+        println!("{}", env!("CARGO_PKG_VERSION"));
+        return;
+    }
+
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let socket = take_value(&mut args, "--socket")
+        .map(PathBuf::from)
+        .unwrap_or_else(control_socket_path);
+    if args.is_empty() || args.iter().any(|a| a == "--help" || a == "-h") {
+        println!("{}", USAGE);
+        return;
+    }
+
+    let mut client = match ControlClient::connect(&socket).await {
+        Ok(client) => client,
+        Err(err) => fail(format!(
+            "cannot connect to {}: {} (is Agent Term running?)",
+            socket.display(),
+            err
+        )),
+    };
+    let token = control_token_path();
+    if let Err(err) = client.authenticate_user(&token) {
+        fail(format!("cannot read {}: {}", token.display(), err));
+    }
+    let command = args.remove(0);
+    if let Err(err) = run(&mut client, &command, args).await {
+        fail(err);
+    }
+}
+
+fn fail(message: impl AsRef<str>) -> ! {
+    eprintln!("agent-term: {}", message.as_ref());
+    std::process::exit(1);
+}
+
+async fn run(client: &mut ControlClient, command: &str, mut args: Vec<String>) -> Result<(), String> {
+    match command {
+        "list" => {
+            let sessions = client.call("list_sessions", json!({})).await?;
+            if take_flag(&mut args, "--json") {
+                println!("{}", sessions);
+                return Ok(());
+            }
+            for session in sessions.as_array().into_iter().flatten() {
+                let id = str_field(session, "id");
+                let status = client
+                    .call("get_session_status", json!({ "id": id }))
+                    .await?;
+                println!(
+                    "{}\t{}\t{}\t{}",
+                    id,
+                    str_field(&status, "status"),
+                    tool_name(&session["tool"]),
+                    str_field(session, "title")
+                );
+            }
+            Ok(())
+        }
+        "create" => {
+            let start = take_flag(&mut args, "--start");
+            let path = match take_value(&mut args, "--path") {
+                Some(path) => PathBuf::from(path),
+                None => env::current_dir().map_err(|e| e.to_string())?,
+            };
+            // The app resolves paths itself, from its own working directory.
+            let path = std::fs::canonicalize(&path)
+                .map_err(|e| format!("invalid path {}: {}", path.display(), e))?;
+            let mut params = json!({ "projectPath": path.display().to_string() });
+            if let Some(tool) = take_value(&mut args, "--tool") {
+                params["tool"] = tool_value(&tool);
+            }
+            for (flag, key) in [
+                ("--title", "title"),
+                ("--section", "sectionId"),
+                ("--command", "command"),
+            ] {
+                if let Some(value) = take_value(&mut args, flag) {
+                    params[key] = json!(value);
+                }
+            }
+            no_extra_args(&args)?;
+            let session = client.call("create_session", params).await?;
+            let id = str_field(&session, "id");
+            if start {
+                client.call("start_session", json!({ "id": id })).await?;
+            }
+            println!("{}", id);
+            Ok(())
+        }
+        "start" => {
+            let rows = take_number(&mut args, "--rows")?;
+            let cols = take_number(&mut args, "--cols")?;
+            let id = take_id(&mut args)?;
+            client
+                .call("start_session", json!({ "id": id, "rows": rows, "cols": cols }))
+                .await?;
+            Ok(())
+        }
+        "stop" => {
+            let id = take_id(&mut args)?;
+            client.call("stop_session", json!({ "id": id })).await?;
+            Ok(())
+        }
+        "write" => {
+            let enter = take_flag(&mut args, "--enter");
+            let id = take_id(&mut args)?;
+            let mut data = if args == ["-"] {
+                let mut input = String::new();
+                std::io::stdin()
+                    .read_to_string(&mut input)
+                    .map_err(|e| e.to_string())?;
+                input
+            } else {
+                args.join(" ")
+            };
+            if enter {
+                data.push('\r');
+            }
+            client
+                .call("write_session_input", json!({ "id": id, "data": data }))
+                .await?;
+            Ok(())
+        }
+        "output" => {
+            let lines = take_number(&mut args, "--lines")?;
+            let id = take_id(&mut args)?;
+            let output = client
+                .call("read_output", json!({ "id": id, "lines": lines }))
+                .await?;
+            for line in output.as_array().into_iter().flatten() {
+                println!("{}", line.as_str().unwrap_or_default());
+            }
+            Ok(())
+        }
+        "status" => {
+            let json_output = take_flag(&mut args, "--json");
+            let id = take_id(&mut args)?;
+            let status = client
+                .call("get_session_status", json!({ "id": id }))
+                .await?;
+            if json_output {
+                println!("{}", status);
+            } else {
+                println!("{}", str_field(&status, "status"));
+            }
+            Ok(())
+        }
+        other => Err(format!("unknown command: {}\n\n{}", other, USAGE)),
+    }
+}
+
+/// Remove `flag` and its value from `args`.
+fn take_value(args: &mut Vec<String>, flag: &str) -> Option<String> {
+    let index = args.iter().position(|a| a == flag)?;
+    args.remove(index);
+    if index < args.len() {
+        Some(args.remove(index))
+    } else {
+        fail(format!("{} needs a value", flag))
+    }
+}
+
+fn take_flag(args: &mut Vec<String>, flag: &str) -> bool {
+    match args.iter().position(|a| a == flag) {
+        Some(index) => {
+            args.remove(index);
+            true
+        }
+        None => false,
+    }
+}
+
+fn take_number(args: &mut Vec<String>, flag: &str) -> Result<Option<u64>, String> {
+    take_value(args, flag)
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|_| format!("{} expects a number, got {}", flag, value))
+        })
+        .transpose()
+}
+
+fn take_id(args: &mut Vec<String>) -> Result<String, String> {
+    if args.is_empty() {
+        return Err("missing session id".to_string());
+    }
+    Ok(args.remove(0))
+}
+
+fn no_extra_args(args: &[String]) -> Result<(), String> {
+    match args.first() {
+        Some(arg) => Err(format!("unexpected argument: {}", arg)),
+        None => Ok(()),
+    }
+}
+
+fn str_field<'a>(value: &'a Value, key: &str) -> &'a str {
+    value[key].as_str().unwrap_or_default()
+}
+
+fn tool_name(tool: &Value) -> String {
+    match tool {
+        Value::String(name) => name.to_lowercase(),
+        _ => tool["custom"].as_str().unwrap_or("custom").to_string(),
+    }
+}
@@ -3,10 +3,21 @@ use std::io;
 use std::path::PathBuf;
 
 use agentterm_shared::diagnostics;
+use agentterm_shared::pool::ClientInfo;
 use agentterm_shared::socket_path::socket_path_for;
 use agentterm_shared::transport;
-use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
-use tokio::time::{sleep, Duration};
+use tokio::io::{AsyncBufReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::mpsc;
+use tokio::time::{sleep, Duration, Instant};
+
+use session::ClientSession;
+
+mod session;
+
+const RECONNECT_BASE_DELAY: Duration = Duration::from_millis(100);
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(2);
+/// Long enough to ride out a crash-loop backoff in the pool supervisor.
+const RECONNECT_GIVE_UP: Duration = Duration::from_secs(120);
 
 #[tokio::main]
 async fn main() {
@@ -32,7 +43,7 @@ async fn main() {
         endpoint.display()
     ));
 
-    let stream = match connect_with_retry(&endpoint).await {
+    let mut stream = match connect_with_retry(&endpoint).await {
         Ok(stream) => stream,
         Err(err) => {
             diagnostics::log(format!(
@@ -45,22 +56,174 @@ async fn main() {
 
     diagnostics::log(format!("mcp_proxy_connected name={}", name));
 
-    let (reader, writer) = tokio::io::split(stream);
-
-    diagnostics::log(format!("mcp_proxy_pump_start name={} dir=stdin->socket", name));
+    let (client_tx, mut client_rx) = mpsc::channel::<String>(256);
     let stdin_name = name.clone();
-    let stdin_task = tokio::spawn(async move {
-        let stdin = tokio::io::stdin();
-        pump(stdin, writer, "stdin->socket", &stdin_name, false).await;
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(tokio::io::stdin()).lines();
+        loop {
+            match lines.next_line().await {
+                Ok(Some(line)) => {
+                    if client_tx.send(line).await.is_err() {
+                        break;
+                    }
+                }
+                Ok(None) => break,
+                Err(err) => {
+                    diagnostics::log(format!(
+                        "mcp_proxy_read_failed name={} dir=stdin error={}",
+                        stdin_name, err
+                    ));
+                    break;
+                }
+            }
+        }
+        diagnostics::log(format!("mcp_proxy_eof name={} dir=stdin", stdin_name));
     });
 
-    diagnostics::log(format!("mcp_proxy_pump_start name={} dir=socket->stdout", name));
-    let stdout = tokio::io::stdout();
-    pump(reader, stdout, "socket->stdout", &name, true).await;
-    let _ = stdin_task.await;
+    // The pool drops every connection when it restarts a server. Reconnect
+    // and replay the client's state so the agent never notices.
+    let mut session = ClientSession::default();
+    let mut stdout = tokio::io::stdout();
+    // Tell the pool which session and project this connection serves, on
+    // every connection.
+    let client_info = ClientInfo::from_env();
+    let hello: Vec<String> = if client_info.is_empty() {
+        Vec::new()
+    } else {
+        vec![client_info.to_message().to_string()]
+    };
+    let mut replay = hello.clone();
+    loop {
+        let outcome = relay(stream, &mut client_rx, &mut stdout, &mut session, replay, &name).await;
+        if outcome == Outcome::ClientClosed {
+            break;
+        }
+        diagnostics::log(format!("mcp_proxy_reconnecting name={}", name));
+        stream = match reconnect(&endpoint).await {
+            Ok(stream) => stream,
+            Err(err) => {
+                diagnostics::log(format!(
+                    "mcp_proxy_reconnect_failed name={} error={}",
+                    name, err
+                ));
+                std::process::exit(1);
+            }
+        };
+        let restored = session.reconnect();
+        diagnostics::log(format!(
+            "mcp_proxy_reconnected name={} replayed={} failed={}",
+            name,
+            restored.to_server.len(),
+            restored.to_client.len()
+        ));
+        for line in &restored.to_client {
+            if write_line(&mut stdout, line).await.is_err() {
+                std::process::exit(0);
+            }
+        }
+        replay = hello.iter().cloned().chain(restored.to_server).collect();
+    }
     diagnostics::log(format!("mcp_proxy_exit name={}", name));
 }
 
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    ClientClosed,
+    ServerClosed,
+}
+
+/// Pass lines both ways until one side goes away. `replay` is written to the
+/// server before anything else.
+async fn relay<W: AsyncWrite + Unpin>(
+    stream: transport::LocalStream,
+    client_rx: &mut mpsc::Receiver<String>,
+    stdout: &mut W,
+    session: &mut ClientSession,
+    replay: Vec<String>,
+    name: &str,
+) -> Outcome {
+    let (read_half, mut write_half) = tokio::io::split(stream);
+    for line in &replay {
+        if write_line(&mut write_half, line).await.is_err() {
+            return Outcome::ServerClosed;
+        }
+    }
+
+    // Reading a line is not cancel-safe, so it happens in its own task and
+    // only whole lines cross into the select below.
+    let (server_tx, mut server_rx) = mpsc::channel::<String>(256);
+    let reader = tokio::spawn(read_server(read_half, server_tx, name.to_string()));
+    let outcome = loop {
+        tokio::select! {
+            line = client_rx.recv() => {
+                let Some(line) = line else {
+                    break Outcome::ClientClosed;
+                };
+                session.client_line(&line);
+                if let Err(err) = write_line(&mut write_half, &line).await {
+                    diagnostics::log(format!(
+                        "mcp_proxy_write_failed name={} dir=stdin->socket error={}",
+                        name, err
+                    ));
+                    break Outcome::ServerClosed;
+                }
+            }
+            line = server_rx.recv() => {
+                let Some(line) = line else {
+                    break Outcome::ServerClosed;
+                };
+                if session.server_line(&line) {
+                    if let Err(err) = write_line(stdout, &line).await {
+                        diagnostics::log(format!(
+                            "mcp_proxy_write_failed name={} dir=socket->stdout error={}",
+                            name, err
+                        ));
+                        break Outcome::ClientClosed;
+                    }
+                }
+            }
+        }
+    };
+    reader.abort();
+    outcome
+}
+
+/// Send each non-empty line from the server until the socket closes.
+async fn read_server(
+    read_half: tokio::io::ReadHalf<transport::LocalStream>,
+    server_tx: mpsc::Sender<String>,
+    name: String,
+) {
+    let mut lines = BufReader::new(read_half).lines();
+    loop {
+        match lines.next_line().await {
+            Ok(Some(line)) => {
+                let line = line.trim_end_matches('\r');
+                if !line.is_empty() && server_tx.send(line.to_string()).await.is_err() {
+                    break;
+                }
+            }
+            Ok(None) => {
+                diagnostics::log(format!("mcp_proxy_eof name={} dir=socket", name));
+                break;
+            }
+            Err(err) => {
+                diagnostics::log(format!(
+                    "mcp_proxy_read_failed name={} dir=socket error={}",
+                    name, err
+                ));
+                break;
+            }
+        }
+    }
+}
+
+async fn write_line<W: AsyncWrite + Unpin>(writer: &mut W, line: &str) -> io::Result<()> {
+    writer.write_all(line.as_bytes()).await?;
+    writer.write_all(b"\n").await?;
+    writer.flush().await
+}
+
 struct ProxyArgs {
     name: String,
     endpoint: Option<String>,
@@ -105,62 +268,18 @@ async fn connect_with_retry(path: &PathBuf) -> io::Result<transport::LocalStream
     }))
 }
 
-async fn pump<R: AsyncRead + Unpin, W: AsyncWrite + Unpin>(
-    mut reader: R,
-    mut writer: W,
-    direction: &str,
-    name: &str,
-    flush_after_write: bool,
-) {
-    let mut buf = [0u8; 8192];
-    let mut total: u64 = 0;
+/// Keep trying the endpoint with backoff while the pool restarts the server.
+async fn reconnect(path: &PathBuf) -> io::Result<transport::LocalStream> {
+    let started = Instant::now();
+    let mut delay = RECONNECT_BASE_DELAY;
     loop {
-        match reader.read(&mut buf).await {
-            Ok(0) => {
-                diagnostics::log(format!(
-                    "mcp_proxy_eof name={} dir={} total_bytes={}",
-                    name, direction, total
-                ));
-                break;
-            }
-            Ok(n) => {
-                total += n as u64;
-                diagnostics::log(format!(
-                    "mcp_proxy_read name={} dir={} bytes={}",
-                    name, direction, n
-                ));
-                if let Err(err) = writer.write_all(&buf[..n]).await {
-                    diagnostics::log(format!(
-                        "mcp_proxy_write_failed name={} dir={} error={}",
-                        name, direction, err
-                    ));
-                    break;
-                }
-                if flush_after_write {
-                    if let Err(err) = writer.flush().await {
-                        diagnostics::log(format!(
-                            "mcp_proxy_flush_failed name={} dir={} error={}",
-                            name, direction, err
-                        ));
-                        break;
-                    }
-                }
-                diagnostics::log(format!(
-                    "mcp_proxy_write name={} dir={} bytes={}",
-                    name, direction, n
-                ));
-            }
-            Err(err) => {
-                diagnostics::log(format!(
-                    "mcp_proxy_read_failed name={} dir={} error={}",
-                    name, direction, err
-                ));
-                break;
+        match transport::connect(path).await {
+            Ok(stream) => return Ok(stream),
+            Err(err) if started.elapsed() >= RECONNECT_GIVE_UP => return Err(err),
+            Err(_) => {
+                sleep(delay).await;
+                delay = (delay * 2).min(RECONNECT_MAX_DELAY);
             }
         }
     }
-    diagnostics::log(format!(
-        "mcp_proxy_pump_done name={} dir={} total_bytes={}",
-        name, direction, total
-    ));
 }
@@ -0,0 +1,234 @@
+//! What the proxy remembers about its MCP client, so a new connection to the
+//! pool can pick up where a dropped one left off.
+
+use std::collections::{BTreeMap, HashSet};
+
+use serde_json::{json, Value};
+
+/// Messages to send after reconnecting.
+#[derive(Debug, Default)]
+pub struct Replay {
+    pub to_server: Vec<String>,
+    pub to_client: Vec<String>,
+}
+
+struct InFlight {
+    key: String,
+    method: String,
+    line: String,
+}
+
+#[derive(Default)]
+pub struct ClientSession {
+    initialize: Option<Value>,
+    initialized: Option<String>,
+    in_flight: Vec<InFlight>,
+    /// `resources/subscribe` params by URI.
+    subscriptions: BTreeMap<String, Value>,
+    /// Ids of requests the proxy replayed itself; their responses are dropped.
+    replayed: HashSet<String>,
+    next_replay_id: u64,
+}
+
+/// Requests that are safe to send twice, so they can be retried on a new
+/// connection. Anything else (such as `tools/call`) may already have had its
+/// effect and is answered with an error instead.
+fn is_retriable(method: &str) -> bool {
+    matches!(
+        method,
+        "initialize"
+            | "ping"
+            | "resources/read"
+            | "resources/subscribe"
+            | "prompts/get"
+            | "completion/complete"
+            | "logging/setLevel"
+    ) || method.ends_with("/list")
+}
+
+fn id_key(id: &Value) -> String {
+    id.to_string()
+}
+
+impl ClientSession {
+    /// Note a line the client is sending to the server.
+    pub fn client_line(&mut self, line: &str) {
+        let Ok(message) = serde_json::from_str::<Value>(line) else {
+            return;
+        };
+        let Some(method) = message.get("method").and_then(Value::as_str) else {
+            return;
+        };
+        match method {
+            "initialize" => self.initialize = Some(message.clone()),
+            "notifications/initialized" => self.initialized = Some(line.to_string()),
+            "notifications/cancelled" => {
+                if let Some(request_id) = message.pointer("/params/requestId") {
+                    let key = id_key(request_id);
+                    self.in_flight.retain(|request| request.key != key);
+                }
+            }
+            "resources/subscribe" | "resources/unsubscribe" => {
+                if let Some(uri) = message.pointer("/params/uri").and_then(Value::as_str) {
+                    if method == "resources/subscribe" {
+                        self.subscriptions
+                            .insert(uri.to_string(), message["params"].clone());
+                    } else {
+                        self.subscriptions.remove(uri);
+                    }
+                }
+            }
+            _ => {}
+        }
+        if let Some(id) = message.get("id").filter(|id| !id.is_null()) {
+            self.in_flight.push(InFlight {
+                key: id_key(id),
+                method: method.to_string(),
+                line: line.to_string(),
+            });
+        }
+    }
+
+    /// Note a line from the server. Returns whether it should reach the client.
+    pub fn server_line(&mut self, line: &str) -> bool {
+        let Ok(message) = serde_json::from_str::<Value>(line) else {
+            return true;
+        };
+        if message.get("method").is_some() {
+            return true;
+        }
+        let Some(id) = message.get("id").filter(|id| !id.is_null()) else {
+            return true;
+        };
+        let key = id_key(id);
+        if self.replayed.remove(&key) {
+            return false;
+        }
+        self.in_flight.retain(|request| request.key != key);
+        true
+    }
+
+    /// Rebuild the client's state on a fresh connection: redo the handshake
+    /// and subscriptions, retry what can be retried and fail the rest.
+    pub fn reconnect(&mut self) -> Replay {
+        let mut replay = Replay::default();
+        self.replayed.clear();
+
+        let (retry, failed): (Vec<InFlight>, Vec<InFlight>) = self
+            .in_flight
+            .drain(..)
+            .partition(|request| is_retriable(&request.method));
+
+        let initialize_pending = retry.iter().any(|request| request.method == "initialize");
+        if !initialize_pending {
+            if let Some(initialize) = self.initialize.clone() {
+                replay.to_server.push(self.replayed_request(initialize));
+            }
+            replay.to_server.extend(self.initialized.clone());
+        }
+        let subscriptions: Vec<Value> = self.subscriptions.values().cloned().collect();
+        for params in subscriptions {
+            let request = json!({
+                "jsonrpc": "2.0",
+                "id": Value::Null,
+                "method": "resources/subscribe",
+                "params": params
+            });
+            replay.to_server.push(self.replayed_request(request));
+        }
+        // A pending initialize goes first, the rest keep their order.
+        let (initialize, others): (Vec<InFlight>, Vec<InFlight>) = retry
+            .into_iter()
+            .partition(|request| request.method == "initialize");
+        for request in initialize.into_iter().chain(others) {
+            replay.to_server.push(request.line.clone());
+            self.in_flight.push(request);
+        }
+
+        for request in failed {
+            let id: Value = serde_json::from_str(&request.key).unwrap_or(Value::Null);
+            let error = json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": {
+                    "code": -32603,
+                    "message": format!(
+                        "connection to the MCP server was lost during {}; it was not retried",
+                        request.method
+                    )
+                }
+            });
+            replay.to_client.push(error.to_string());
+        }
+        replay
+    }
+
+    fn replayed_request(&mut self, mut request: Value) -> String {
+        self.next_replay_id += 1;
+        let id = Value::from(format!("agentterm-proxy-replay-{}", self.next_replay_id));
+        self.replayed.insert(id_key(&id));
+        request["id"] = id;
+        request.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn handshake(session: &mut ClientSession) {
+        session.client_line(r#"{"jsonrpc":"2.0","id":0,"method":"initialize","params":{"capabilities":{}}}"#);
+        assert!(session.server_line(r#"{"jsonrpc":"2.0","id":0,"result":{"capabilities":{}}}"#));
+        session.client_line(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#);
+    }
+
+    #[test]
+    fn test_reconnect_replays_handshake_and_swallows_its_response() {
+        let mut session = ClientSession::default();
+        handshake(&mut session);
+        session.client_line(r#"{"jsonrpc":"2.0","id":1,"method":"resources/subscribe","params":{"uri":"file:///a"}}"#);
+        assert!(session.server_line(r#"{"jsonrpc":"2.0","id":1,"result":{}}"#));
+
+        let replay = session.reconnect();
+        assert_eq!(replay.to_server.len(), 3);
+        assert!(replay.to_server[0].contains("\"initialize\""));
+        assert!(replay.to_server[1].contains("notifications/initialized"));
+        assert!(replay.to_server[2].contains("file:///a"));
+        assert!(replay.to_client.is_empty());
+
+        let initialize: Value = serde_json::from_str(&replay.to_server[0]).unwrap();
+        let response = json!({ "jsonrpc": "2.0", "id": initialize["id"], "result": {} });
+        assert!(!session.server_line(&response.to_string()));
+    }
+
+    #[test]
+    fn test_reconnect_retries_reads_and_fails_tool_calls() {
+        let mut session = ClientSession::default();
+        handshake(&mut session);
+        session.client_line(r#"{"jsonrpc":"2.0","id":2,"method":"tools/list"}"#);
+        session.client_line(r#"{"jsonrpc":"2.0","id":3,"method":"tools/call","params":{"name":"deploy"}}"#);
+
+        let replay = session.reconnect();
+        assert!(replay.to_server.last().unwrap().contains("tools/list"));
+        assert!(!replay.to_server.iter().any(|line| line.contains("tools/call")));
+        assert_eq!(replay.to_client.len(), 1);
+        let error: Value = serde_json::from_str(&replay.to_client[0]).unwrap();
+        assert_eq!(error["id"], json!(3));
+        assert!(error["error"]["message"].as_str().unwrap().contains("tools/call"));
+
+        // The retried request is still in flight and its answer goes through.
+        assert!(session.server_line(r#"{"jsonrpc":"2.0","id":2,"result":{"tools":[]}}"#));
+        assert!(session.reconnect().to_client.is_empty());
+    }
+
+    #[test]
+    fn test_unanswered_initialize_is_retried_as_is() {
+        let mut session = ClientSession::default();
+        session.client_line(r#"{"jsonrpc":"2.0","id":0,"method":"initialize","params":{}}"#);
+        let replay = session.reconnect();
+        assert_eq!(replay.to_server.len(), 1);
+        let initialize: Value = serde_json::from_str(&replay.to_server[0]).unwrap();
+        assert_eq!(initialize["id"], json!(0));
+        assert!(session.server_line(r#"{"jsonrpc":"2.0","id":0,"result":{}}"#));
+    }
+}
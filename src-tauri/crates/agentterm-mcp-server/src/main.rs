@@ -0,0 +1,324 @@
+use std::env;
+use std::path::PathBuf;
+
+use agentterm_shared::control::{tool_value, ControlClient, CALLER_TOKEN_ENV};
+use agentterm_shared::diagnostics;
+use agentterm_shared::socket_path::control_socket_path;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::time::{sleep, Duration, Instant};
+
+/// Set by the app in every session it starts; identifies the calling agent.
+const SESSION_ID_ENV: &str = "AGENT_TERM_SESSION_ID";
+const PROTOCOL_VERSION: &str = "2024-11-05";
+const DEFAULT_OUTPUT_LINES: u64 = 50;
+const DEFAULT_WAIT_SECONDS: u64 = 300;
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+#[tokio::main]
+async fn main() {
+    if env::args().any(|a| a == "--version" || a == "-V") {
+        println!("{}", env!("CARGO_PKG_VERSION"));
+        std::process::exit(0);
+    }
+
+    let mut endpoint = None;
+    let mut iter = env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--endpoint" => endpoint = iter.next(),
+            "--debug" => env::set_var("AGENT_TERM_DIAG", "1"),
+            _ => {}
+        }
+    }
+
+    let server = Server {
+        caller: env::var(SESSION_ID_ENV).ok().filter(|id| !id.is_empty()),
+        token: env::var(CALLER_TOKEN_ENV).ok().filter(|token| !token.is_empty()),
+        endpoint: endpoint.map(PathBuf::from).unwrap_or_else(control_socket_path),
+    };
+    diagnostics::log(format!(
+        "agent_mcp_start caller={:?} endpoint={}",
+        server.caller,
+        server.endpoint.display()
+    ));
+
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut stdout = tokio::io::stdout();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let Some(response) = server.handle(&line).await else {
+            continue;
+        };
+        let mut out = response.to_string();
+        out.push('\n');
+        if stdout.write_all(out.as_bytes()).await.is_err() || stdout.flush().await.is_err() {
+            break;
+        }
+    }
+    diagnostics::log("agent_mcp_exit");
+}
+
+struct Server {
+    caller: Option<String>,
+    token: Option<String>,
+    endpoint: PathBuf,
+}
+
+impl Server {
+    /// Handle one MCP message; notifications get no response.
+    async fn handle(&self, line: &str) -> Option<Value> {
+        let message: Value = match serde_json::from_str(line) {
+            Ok(message) => message,
+            Err(err) => return Some(error_response(Value::Null, -32700, err.to_string())),
+        };
+        let id = message.get("id").cloned()?;
+        let method = message["method"].as_str().unwrap_or_default();
+        let params = message.get("params").cloned().unwrap_or_else(|| json!({}));
+
+        let result = match method {
+            "initialize" => json!({
+                "protocolVersion": params["protocolVersion"].as_str().unwrap_or(PROTOCOL_VERSION),
+                "capabilities": { "tools": {} },
+                "serverInfo": { "name": "agent-term", "version": env!("CARGO_PKG_VERSION") },
+            }),
+            "ping" => json!({}),
+            "tools/list" => json!({ "tools": tool_definitions() }),
+            "tools/call" => self.call_tool(&params).await,
+            other => return Some(error_response(id, -32601, format!("unknown method: {}", other))),
+        };
+        Some(json!({ "jsonrpc": "2.0", "id": id, "result": result }))
+    }
+
+    async fn call_tool(&self, params: &Value) -> Value {
+        let name = params["name"].as_str().unwrap_or_default();
+        let args = params.get("arguments").cloned().unwrap_or_else(|| json!({}));
+        diagnostics::log(format!("agent_mcp_tool name={}", name));
+        match self.run_tool(name, &args).await {
+            Ok(text) => json!({ "content": [{ "type": "text", "text": text }] }),
+            Err(err) => json!({ "content": [{ "type": "text", "text": err }], "isError": true }),
+        }
+    }
+
+    async fn run_tool(&self, name: &str, args: &Value) -> Result<String, String> {
+        let caller = self.caller.as_deref().ok_or_else(|| {
+            format!("agent-term tools only work inside an Agent Term session ({} is not set)", SESSION_ID_ENV)
+        })?;
+        let mut client = ControlClient::connect(&self.endpoint)
+            .await
+            .map_err(|e| format!("cannot reach Agent Term at {}: {}", self.endpoint.display(), e))?;
+        let mut agent = Agent {
+            client: &mut client,
+            caller,
+            token: self.token.as_deref(),
+        };
+
+        match name {
+            "list_sessions" => agent.list_sessions().await,
+            "create_session" => agent.create_session(args).await,
+            "send_input" => {
+                let id = required_str(args, "sessionId")?;
+                let mut data = required_str(args, "text")?.to_string();
+                if args["submit"].as_bool().unwrap_or(true) {
+                    data.push('\r');
+                }
+                agent
+                    .request("write_session_input", json!({ "id": id, "data": data }))
+                    .await?;
+                Ok(format!("Sent input to {}", id))
+            }
+            "read_output" => {
+                let id = required_str(args, "sessionId")?;
+                let lines = args["lines"].as_u64().unwrap_or(DEFAULT_OUTPUT_LINES);
+                let output = agent
+                    .request("read_output", json!({ "id": id, "lines": lines }))
+                    .await?;
+                let text: Vec<&str> = output
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(Value::as_str)
+                    .collect();
+                Ok(text.join("\n"))
+            }
+            "wait_for_status" => agent.wait_for_status(args).await,
+            other => Err(format!("unknown tool: {}", other)),
+        }
+    }
+}
+
+/// Control socket calls made on behalf of the calling session.
+struct Agent<'a> {
+    client: &'a mut ControlClient,
+    caller: &'a str,
+    token: Option<&'a str>,
+}
+
+impl Agent<'_> {
+    async fn request(&mut self, method: &str, mut params: Value) -> Result<Value, String> {
+        params["callerId"] = json!(self.caller);
+        if let Some(token) = self.token {
+            params["callerToken"] = json!(token);
+        }
+        self.client.call(method, params).await
+    }
+
+    async fn list_sessions(&mut self) -> Result<String, String> {
+        let sessions = self.request("list_sessions", json!({})).await?;
+        let mut listed = Vec::new();
+        for session in sessions.as_array().into_iter().flatten() {
+            let id = session["id"].as_str().unwrap_or_default();
+            let status = self
+                .request("get_session_status", json!({ "id": id }))
+                .await?;
+            listed.push(json!({
+                "id": id,
+                "title": session["title"],
+                "tool": session["tool"],
+                "projectPath": session["projectPath"],
+                "status": status["status"],
+                "running": status["running"],
+                "createdByYou": session["createdBy"].as_str() == Some(self.caller),
+                "isYou": id == self.caller,
+            }));
+        }
+        serde_json::to_string_pretty(&listed).map_err(|e| e.to_string())
+    }
+
+    async fn create_session(&mut self, args: &Value) -> Result<String, String> {
+        let mut params = json!({});
+        if let Some(tool) = args["tool"].as_str() {
+            params["tool"] = tool_value(tool);
+        }
+        for key in ["title", "projectPath", "command"] {
+            if let Some(value) = args[key].as_str() {
+                params[key] = json!(value);
+            }
+        }
+        let session = self.request("create_session", params).await?;
+        let id = session["id"].as_str().unwrap_or_default().to_string();
+
+        let start = args["start"].as_bool().unwrap_or(true);
+        if start {
+            self.request("start_session", json!({ "id": id })).await?;
+        }
+        // Queued so it is typed once the new tool shows its prompt.
+        if let Some(prompt) = args["prompt"].as_str().filter(|p| !p.trim().is_empty()) {
+            self.request("enqueue_prompt", json!({ "sessionId": id, "text": prompt }))
+                .await?;
+        }
+        Ok(json!({ "id": id, "title": session["title"], "started": start }).to_string())
+    }
+
+    async fn wait_for_status(&mut self, args: &Value) -> Result<String, String> {
+        let id = required_str(args, "sessionId")?;
+        if id == self.caller {
+            return Err("a session cannot wait for itself".to_string());
+        }
+        let wanted = args["status"].as_str().unwrap_or("waiting");
+        let timeout = Duration::from_secs(args["timeoutSeconds"].as_u64().unwrap_or(DEFAULT_WAIT_SECONDS));
+        let deadline = Instant::now() + timeout;
+        loop {
+            let status = self
+                .request("get_session_status", json!({ "id": id }))
+                .await?;
+            let current = status["status"].as_str().unwrap_or_default();
+            if current == wanted {
+                return Ok(format!("Session {} is {}", id, current));
+            }
+            if !status["running"].as_bool().unwrap_or(false) {
+                return Err(format!("Session {} is not running (status {})", id, current));
+            }
+            if Instant::now() >= deadline {
+                return Err(format!(
+                    "Timed out after {}s; session {} is {}",
+                    timeout.as_secs(),
+                    id,
+                    current
+                ));
+            }
+            sleep(POLL_INTERVAL).await;
+        }
+    }
+}
+
+fn required_str<'a>(args: &'a Value, key: &str) -> Result<&'a str, String> {
+    args[key]
+        .as_str()
+        .filter(|value| !value.is_empty())
+        .ok_or_else(|| format!("missing argument: {}", key))
+}
+
+fn error_response(id: Value, code: i64, message: String) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+fn tool_definitions() -> Value {
+    let session_id = json!({ "type": "string", "description": "Session id from list_sessions" });
+    json!([
+        {
+            "name": "list_sessions",
+            "description": "List the Agent Term sessions you may access, with their status. \"waiting\" means the session's agent is idle at its prompt.",
+            "inputSchema": { "type": "object", "properties": {} },
+        },
+        {
+            "name": "create_session",
+            "description": "Create a helper session next to yours, start it, and optionally queue a first prompt for it.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "tool": { "type": "string", "description": "shell, claude, gemini, codex, opencode or a custom command name. Defaults to shell." },
+                    "title": { "type": "string" },
+                    "projectPath": { "type": "string", "description": "Working directory. Defaults to yours." },
+                    "command": { "type": "string", "description": "Command to run instead of the tool's default." },
+                    "prompt": { "type": "string", "description": "Typed into the session once its tool is ready." },
+                    "start": { "type": "boolean", "description": "Start the session right away. Defaults to true." },
+                },
+            },
+        },
+        {
+            "name": "send_input",
+            "description": "Type text into another session's terminal.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "sessionId": session_id,
+                    "text": { "type": "string" },
+                    "submit": { "type": "boolean", "description": "Press Enter after the text. Defaults to true." },
+                },
+                "required": ["sessionId", "text"],
+            },
+        },
+        {
+            "name": "read_output",
+            "description": "Read the last lines a session printed, as plain text with escape sequences removed. This is the output history, not the current state of the screen.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "sessionId": session_id,
+                    "lines": { "type": "integer", "description": "Defaults to 50." },
+                },
+                "required": ["sessionId"],
+            },
+        },
+        {
+            "name": "wait_for_status",
+            "description": "Wait until a session reaches a status, e.g. \"waiting\" once its agent has finished a task.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "sessionId": session_id,
+                    "status": { "type": "string", "enum": ["running", "waiting", "idle", "error"], "description": "Defaults to waiting." },
+                    "timeoutSeconds": { "type": "integer", "description": "Defaults to 300." },
+                },
+                "required": ["sessionId"],
+            },
+        },
+    ])
+}
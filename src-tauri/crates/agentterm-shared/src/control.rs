@@ -0,0 +1,91 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines, ReadHalf, WriteHalf};
+
+use crate::transport::{self, LocalStream};
+
+/// Set by the app in every session it starts. Sent as `callerToken` so the
+/// control API can tell which session a request comes from.
+pub const CALLER_TOKEN_ENV: &str = "AGENT_TERM_CALLER_TOKEN";
+
+/// Param carrying the token from `control_token_path`, which the app only
+/// accepts as the user's.
+pub const USER_TOKEN_PARAM: &str = "userToken";
+
+/// Client for the app's JSON-RPC control socket. Requests are sent one at a
+/// time and each waits for its response line.
+pub struct ControlClient {
+    lines: Lines<BufReader<ReadHalf<LocalStream>>>,
+    writer: WriteHalf<LocalStream>,
+    next_id: u64,
+    user_token: Option<String>,
+}
+
+impl ControlClient {
+    pub async fn connect(path: &PathBuf) -> io::Result<Self> {
+        let stream = transport::connect(path).await?;
+        let (reader, writer) = tokio::io::split(stream);
+        Ok(Self {
+            lines: BufReader::new(reader).lines(),
+            writer,
+            next_id: 1,
+            user_token: None,
+        })
+    }
+
+    /// Send the user's token from `path` with every request, so they are not
+    /// treated as coming from an unknown caller.
+    pub fn authenticate_user(&mut self, path: &Path) -> io::Result<()> {
+        let token = std::fs::read_to_string(path)?;
+        self.user_token = Some(token.trim().to_string());
+        Ok(())
+    }
+
+    /// Call `method`, returning its result or the error message from the app.
+    pub async fn call(&mut self, method: &str, mut params: Value) -> Result<Value, String> {
+        if let (Some(token), Some(params)) = (&self.user_token, params.as_object_mut()) {
+            params.insert(USER_TOKEN_PARAM.to_string(), json!(token));
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        let mut request = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params,
+        })
+        .to_string();
+        request.push('\n');
+        self.writer
+            .write_all(request.as_bytes())
+            .await
+            .map_err(|e| format!("failed to send request: {}", e))?;
+
+        let line = self
+            .lines
+            .next_line()
+            .await
+            .map_err(|e| format!("failed to read response: {}", e))?
+            .ok_or_else(|| "connection closed by Agent Term".to_string())?;
+        let mut response: Value =
+            serde_json::from_str(&line).map_err(|e| format!("invalid response: {}", e))?;
+        if let Some(error) = response.get("error") {
+            return Err(error["message"].as_str().unwrap_or("unknown error").to_string());
+        }
+        Ok(response["result"].take())
+    }
+}
+
+/// A session tool as the app serializes it; unknown names are custom tools.
+pub fn tool_value(tool: &str) -> Value {
+    match tool.to_lowercase().as_str() {
+        "shell" => json!("shell"),
+        "claude" => json!("claude"),
+        "gemini" => json!("gemini"),
+        "codex" => json!("codex"),
+        "opencode" => json!("openCode"),
+        _ => json!({ "custom": tool }),
+    }
+}
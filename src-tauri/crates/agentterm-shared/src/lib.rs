@@ -1,3 +1,5 @@
+pub mod control;
 pub mod diagnostics;
+pub mod pool;
 pub mod socket_path;
 pub mod transport;
@@ -0,0 +1,65 @@
+use serde_json::{json, Map, Value};
+
+/// Notification the MCP proxy sends first on every pool connection, naming
+/// the session and project it runs in. The pool consumes it; the server
+/// never sees it.
+pub const CLIENT_INFO_METHOD: &str = "agentterm/client";
+
+/// Set in the environment of every session's tool, and inherited by the MCP
+/// servers and proxies it launches.
+pub const SESSION_ID_ENV: &str = "AGENT_TERM_SESSION_ID";
+pub const PROJECT_PATH_ENV: &str = "AGENT_TERM_PROJECT_PATH";
+
+/// Who is on the other end of a pool connection.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClientInfo {
+    pub session_id: Option<String>,
+    pub project_path: Option<String>,
+}
+
+impl ClientInfo {
+    /// Read from the environment a session's tool runs in.
+    pub fn from_env() -> Self {
+        let var = |name| std::env::var(name).ok().filter(|value: &String| !value.is_empty());
+        Self {
+            session_id: var(SESSION_ID_ENV),
+            project_path: var(PROJECT_PATH_ENV),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.session_id.is_none() && self.project_path.is_none()
+    }
+
+    pub fn to_message(&self) -> Value {
+        let mut params = Map::new();
+        if let Some(session_id) = &self.session_id {
+            params.insert("sessionId".to_string(), Value::from(session_id.as_str()));
+        }
+        if let Some(project_path) = &self.project_path {
+            params.insert("projectPath".to_string(), Value::from(project_path.as_str()));
+        }
+        json!({
+            "jsonrpc": "2.0",
+            "method": CLIENT_INFO_METHOD,
+            "params": params
+        })
+    }
+
+    /// Parse a client-info notification; `None` for any other message.
+    pub fn from_message(message: &Value) -> Option<Self> {
+        if message.get("method").and_then(Value::as_str) != Some(CLIENT_INFO_METHOD) {
+            return None;
+        }
+        let param = |name: &str| {
+            message
+                .pointer(&format!("/params/{}", name))
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        };
+        Some(Self {
+            session_id: param("sessionId"),
+            project_path: param("projectPath"),
+        })
+    }
+}
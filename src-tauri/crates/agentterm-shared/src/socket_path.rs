@@ -9,14 +9,36 @@ pub fn get_agent_term_dir() -> io::Result<PathBuf> {
     Ok(home.join(".agent-term"))
 }
 
+/// Get the run directory for sockets
+pub fn get_agent_term_run_dir() -> io::Result<PathBuf> {
+    Ok(get_agent_term_dir()?.join("run"))
+}
+
 /// Get the MCP run directory for sockets
 pub fn get_agent_term_mcp_run_dir() -> io::Result<PathBuf> {
-    Ok(get_agent_term_dir()?.join("run").join("mcp"))
+    Ok(get_agent_term_run_dir()?.join("mcp"))
+}
+
+/// Socket the app's control API listens on
+pub fn control_socket_path() -> PathBuf {
+    if cfg!(windows) {
+        return PathBuf::from("\\\\.\\pipe\\agentterm-control");
+    }
+    let base = get_agent_term_run_dir().unwrap_or_else(|_| PathBuf::from("/tmp"));
+    base.join("control.sock")
+}
+
+/// Token file that marks control requests as coming from the user. It sits
+/// next to the control socket, in a directory sandboxes hide and containers
+/// never mount.
+pub fn control_token_path() -> PathBuf {
+    let base = get_agent_term_run_dir().unwrap_or_else(|_| PathBuf::from("/tmp"));
+    base.join("control.token")
 }
 
 /// Compute the socket path for a given MCP name
 pub fn socket_path_for(name: &str) -> PathBuf {
-    let safe_name = sanitize_socket_name(name);
+    let safe_name = sanitize_name(name);
     if cfg!(windows) {
         return PathBuf::from(format!("\\\\.\\pipe\\agentterm-mcp-{}", safe_name));
     }
@@ -24,7 +46,9 @@ pub fn socket_path_for(name: &str) -> PathBuf {
     base.join(format!("agentterm-mcp-{}.sock", safe_name))
 }
 
-fn sanitize_socket_name(name: &str) -> String {
+/// An MCP name made safe to use in a file name: anything but ASCII letters,
+/// digits, `-` and `_` becomes `_`.
+pub fn sanitize_name(name: &str) -> String {
     let mut out = String::with_capacity(name.len());
     for ch in name.chars() {
         if ch.is_ascii_alphanumeric() || ch == '-' || ch == '_' {
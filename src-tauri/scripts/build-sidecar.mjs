@@ -5,35 +5,38 @@ import { fileURLToPath } from "node:url";
 
 const scriptDir = dirname(fileURLToPath(import.meta.url));
 const repoRoot = resolve(scriptDir, "..", "..");
-const manifestPath = resolve(repoRoot, "src-tauri", "crates", "agentterm-mcp-proxy", "Cargo.toml");
 const targetDir = process.env.CARGO_TARGET_DIR
   ? resolve(process.env.CARGO_TARGET_DIR)
   : resolve(repoRoot, "src-tauri", "target");
 const binDir = resolve(repoRoot, "src-tauri", "bin");
-const binBase = "agentterm-mcp-proxy";
-const binName = process.platform === "win32" ? `${binBase}.exe` : binBase;
-const builtPath = resolve(targetDir, "release", binName);
 const targetTriple = resolveTargetTriple();
 const targetSuffix = targetTriple ? `-${targetTriple}` : "";
-const destName = process.platform === "win32"
-  ? `${binBase}${targetSuffix}.exe`
-  : `${binBase}${targetSuffix}`;
-const destPath = resolve(binDir, destName);
+const sidecars = ["agentterm-mcp-proxy", "agentterm-mcp-server"];
 
-execFileSync(
-  "cargo",
-  ["build", "--release", "--manifest-path", manifestPath],
-  { stdio: "inherit" }
-);
+for (const binBase of sidecars) {
+  const manifestPath = resolve(repoRoot, "src-tauri", "crates", binBase, "Cargo.toml");
+  const binName = process.platform === "win32" ? `${binBase}.exe` : binBase;
+  const builtPath = resolve(targetDir, "release", binName);
+  const destName = process.platform === "win32"
+    ? `${binBase}${targetSuffix}.exe`
+    : `${binBase}${targetSuffix}`;
+  const destPath = resolve(binDir, destName);
 
-if (!existsSync(builtPath)) {
-  throw new Error(`sidecar build missing: ${builtPath}`);
-}
+  execFileSync(
+    "cargo",
+    ["build", "--release", "--manifest-path", manifestPath],
+    { stdio: "inherit" }
+  );
+
+  if (!existsSync(builtPath)) {
+    throw new Error(`sidecar build missing: ${builtPath}`);
+  }
 
-mkdirSync(binDir, { recursive: true });
-copyFileSync(builtPath, destPath);
+  mkdirSync(binDir, { recursive: true });
+  copyFileSync(builtPath, destPath);
 
-console.log(`sidecar copied to ${destPath}`);
+  console.log(`sidecar copied to ${destPath}`);
+}
 
 function resolveTargetTriple() {
   const envTarget = process.env.TAURI_ENV_TARGET_TRIPLE || process.env.TARGET;
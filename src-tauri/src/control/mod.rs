@@ -0,0 +1,557 @@
+//! Local control API.
+//!
+//! Serves line-delimited JSON-RPC 2.0 on `~/.agent-term/run/control.sock` so the
+//! `agent-term` CLI and other local tools can drive sessions without the webview.
+//! Method names and params mirror the Tauri commands of the same name. Requests
+//! from inside a session, recognised by the client's process tree or the
+//! `callerToken` the session was started with, are checked against the agent
+//! permissions in `permissions`. Only requests carrying the user token from
+//! `control_token_path` are unrestricted; any other caller is refused.
+
+mod permissions;
+
+use std::sync::{Arc, OnceLock};
+
+use agentterm_shared::control::USER_TOKEN_PARAM;
+use agentterm_shared::socket_path::{control_socket_path, control_token_path};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+use crate::diagnostics;
+use crate::mcp::pool::transport::{self, LocalListener, LocalStream};
+use crate::mcp::McpManager;
+use crate::session::{
+    default_tool_command, NewSessionInput, SessionManager, SessionRecord, SessionTool,
+};
+use permissions::Agent;
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// Errors returned by `SessionManager` itself.
+const SESSION_ERROR: i64 = -32000;
+/// The calling agent is not allowed to make this request.
+const PERMISSION_DENIED: i64 = -32001;
+
+pub(crate) const DEFAULT_SECTION_ID: &str = "default-section";
+const DEFAULT_OUTPUT_LINES: usize = 50;
+
+/// Written to `control_token_path` at startup; presenting it marks a request
+/// as the user's.
+static USER_TOKEN: OnceLock<String> = OnceLock::new();
+
+#[derive(Debug)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    fn denied(message: String) -> Self {
+        Self::new(PERMISSION_DENIED, message)
+    }
+}
+
+impl From<String> for RpcError {
+    fn from(message: String) -> Self {
+        Self::new(SESSION_ERROR, message)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IdParams {
+    id: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StartParams {
+    id: String,
+    rows: Option<u16>,
+    cols: Option<u16>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WriteParams {
+    id: String,
+    data: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PromptParams {
+    session_id: String,
+    text: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReadOutputParams {
+    id: String,
+    lines: Option<usize>,
+}
+
+/// `create_session` params; unlike `NewSessionInput` everything is optional.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CreateParams {
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    project_path: String,
+    #[serde(default)]
+    section_id: Option<String>,
+    /// Defaults to the section's default tool, then to a shell.
+    #[serde(default)]
+    tool: Option<SessionTool>,
+    #[serde(default)]
+    command: Option<String>,
+    #[serde(default)]
+    icon: Option<String>,
+}
+
+/// Start listening on the control socket. Failure to bind is logged and leaves
+/// the app running without the control API.
+pub fn spawn_control_server(app: AppHandle) {
+    let path = control_socket_path();
+    #[cfg(unix)]
+    if let Some(dir) = path.parent() {
+        use std::os::unix::fs::PermissionsExt;
+        // Bind inside a private directory so the socket is never reachable by
+        // other users, even before its own mode is narrowed below.
+        let private = std::fs::create_dir_all(dir)
+            .and_then(|_| std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700)));
+        if let Err(err) = private {
+            diagnostics::log(format!(
+                "control_bind_failed path={} error={}",
+                dir.display(),
+                err
+            ));
+            return;
+        }
+    }
+    let listener = match transport::bind(&path) {
+        Ok(listener) => Arc::new(listener),
+        Err(err) => {
+            diagnostics::log(format!(
+                "control_bind_failed path={} error={}",
+                path.display(),
+                err
+            ));
+            return;
+        }
+    };
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        // Anyone who can connect can type into sessions.
+        let _ = std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600));
+    }
+    if let Err(err) = write_user_token() {
+        diagnostics::log(format!(
+            "control_token_write_failed path={} error={}",
+            control_token_path().display(),
+            err
+        ));
+    }
+    diagnostics::log(format!("control_listening path={}", path.display()));
+
+    tauri::async_runtime::spawn(accept_loop(app, listener));
+}
+
+/// Write a fresh user token readable only by the user.
+fn write_user_token() -> std::io::Result<()> {
+    use std::io::Write;
+    let token = USER_TOKEN.get_or_init(|| uuid::Uuid::new_v4().to_string());
+    let path = control_token_path();
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        // A token file left from an older run keeps its mode; narrow it first.
+        if path.exists() {
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+        }
+    }
+    options.open(&path)?.write_all(token.as_bytes())
+}
+
+/// Remove the socket file so clients fail fast instead of connecting to nothing.
+pub fn remove_control_socket() {
+    #[cfg(unix)]
+    {
+        let _ = std::fs::remove_file(control_socket_path());
+    }
+    let _ = std::fs::remove_file(control_token_path());
+}
+
+async fn accept_loop(app: AppHandle, listener: Arc<LocalListener>) {
+    loop {
+        match listener.accept().await {
+            Ok(stream) => {
+                tauri::async_runtime::spawn(handle_client(app.clone(), stream));
+            }
+            Err(err) => {
+                diagnostics::log(format!("control_accept_error error={}", err));
+                tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            }
+        }
+    }
+}
+
+async fn handle_client(app: AppHandle, stream: LocalStream) {
+    let peer = peer_pid(&stream);
+    let (read_half, mut write_half) = tokio::io::split(stream);
+    let mut lines = BufReader::new(read_half).lines();
+    loop {
+        let line = match lines.next_line().await {
+            Ok(Some(line)) => line,
+            Ok(None) => break,
+            Err(err) => {
+                diagnostics::log(format!("control_read_error error={}", err));
+                break;
+            }
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let app = app.clone();
+        // Session calls block on PTY and process work.
+        let response = tauri::async_runtime::spawn_blocking(move || handle_line(&app, &line, peer))
+            .await
+            .unwrap_or_else(|err| error_response(Value::Null, RpcError::new(SESSION_ERROR, err.to_string())));
+        let mut out = response.to_string();
+        out.push('\n');
+        if write_half.write_all(out.as_bytes()).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// Process id of the connected client, where the platform reports it.
+#[cfg(unix)]
+fn peer_pid(stream: &LocalStream) -> Option<u32> {
+    let pid = stream.peer_cred().ok()?.pid()?;
+    u32::try_from(pid).ok()
+}
+
+#[cfg(not(unix))]
+fn peer_pid(_stream: &LocalStream) -> Option<u32> {
+    None
+}
+
+fn handle_line(app: &AppHandle, line: &str, peer: Option<u32>) -> Value {
+    let request: Value = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(err) => return error_response(Value::Null, RpcError::new(PARSE_ERROR, err.to_string())),
+    };
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+    let Some(method) = request.get("method").and_then(Value::as_str) else {
+        return error_response(id, RpcError::new(INVALID_REQUEST, "missing method"));
+    };
+    let params = request.get("params").cloned().unwrap_or_else(|| json!({}));
+    diagnostics::log(format!("control_request method={}", method));
+
+    match dispatch(app, method, params, peer) {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(err) => error_response(id, err),
+    }
+}
+
+fn error_response(id: Value, err: RpcError) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": err.code, "message": err.message },
+    })
+}
+
+fn parse<T: serde::de::DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    serde_json::from_value(params).map_err(|err| RpcError::new(INVALID_PARAMS, err.to_string()))
+}
+
+fn to_value<T: serde::Serialize>(value: T) -> Result<Value, RpcError> {
+    serde_json::to_value(value).map_err(|err| RpcError::new(SESSION_ERROR, err.to_string()))
+}
+
+fn dispatch(
+    app: &AppHandle,
+    method: &str,
+    params: Value,
+    peer: Option<u32>,
+) -> Result<Value, RpcError> {
+    let manager = app.state::<SessionManager>();
+    let agent = caller(app, &params, peer)?;
+    // Looks up a target session and applies the agent's scope to it.
+    let target = |id: &str| -> Result<SessionRecord, RpcError> {
+        let record = manager.get_session(id)?;
+        if let Some(agent) = &agent {
+            agent.check_access(&record).map_err(RpcError::denied)?;
+        }
+        Ok(record)
+    };
+
+    match method {
+        "list_sessions" => {
+            let sessions: Vec<SessionRecord> = manager
+                .list_sessions()
+                .into_iter()
+                .filter(|session| agent.as_ref().is_none_or(|agent| agent.can_access(session)))
+                .collect();
+            to_value(sessions)
+        }
+        "create_session" => {
+            let mut params: CreateParams = parse(params)?;
+            let record = match &agent {
+                Some(agent) => {
+                    agent.check_create().map_err(RpcError::denied)?;
+                    // Helpers land next to the agent that asked for them.
+                    if params.project_path.is_empty() {
+                        params.project_path = agent.caller.project_path.clone();
+                    }
+                    params
+                        .section_id
+                        .get_or_insert_with(|| agent.caller.section_id.clone());
+                    manager.create_agent_session(new_session_input(app, params), &agent.caller.id)?
+                }
+                None => manager.create_session(new_session_input(app, params))?,
+            };
+            manager.attach_section_mcps(&app.state::<McpManager>(), &record);
+            let _ = app.emit("session-created", record.clone());
+            to_value(record)
+        }
+        "start_session" => {
+            let params: StartParams = parse(params)?;
+            target(&params.id)?;
+            manager.start_session(app, &params.id, params.rows, params.cols)?;
+            Ok(Value::Null)
+        }
+        "stop_session" => {
+            let params: IdParams = parse(params)?;
+            target(&params.id)?;
+            manager.stop_session(&params.id)?;
+            Ok(Value::Null)
+        }
+        "write_session_input" => {
+            let params: WriteParams = parse(params)?;
+            let record = target(&params.id)?;
+            if let Some(agent) = &agent {
+                agent.check_input(&record).map_err(RpcError::denied)?;
+            }
+            manager.write_session_input(&params.id, params.data.as_bytes())?;
+            Ok(Value::Null)
+        }
+        "enqueue_prompt" => {
+            let params: PromptParams = parse(params)?;
+            let record = target(&params.session_id)?;
+            if let Some(agent) = &agent {
+                agent.check_input(&record).map_err(RpcError::denied)?;
+            }
+            to_value(manager.enqueue_prompt(&params.session_id, params.text)?)
+        }
+        "read_output" => {
+            let params: ReadOutputParams = parse(params)?;
+            target(&params.id)?;
+            let lines = params.lines.unwrap_or(DEFAULT_OUTPUT_LINES);
+            to_value(manager.read_output(&params.id, lines)?)
+        }
+        "get_session_status" => {
+            let params: IdParams = parse(params)?;
+            let record = target(&params.id)?;
+            let live = manager.live_status(&params.id);
+            Ok(json!({
+                "id": record.id,
+                "running": live.is_some(),
+                "status": live.unwrap_or(record.status),
+            }))
+        }
+        other => Err(RpcError::new(
+            METHOD_NOT_FOUND,
+            format!("unknown method: {}", other),
+        )),
+    }
+}
+
+/// The agent making the request, if it comes from inside a session. Requests
+/// from no session are only served, unrestricted, when they carry the user token.
+fn caller(app: &AppHandle, params: &Value, peer: Option<u32>) -> Result<Option<Agent>, RpcError> {
+    let manager = app.state::<SessionManager>();
+    let from_tree = peer.and_then(|pid| manager.session_for_pid(pid));
+    let from_token = match params.get("callerToken").and_then(Value::as_str) {
+        Some(token) => Some(
+            manager
+                .session_for_caller_token(token)
+                .ok_or_else(|| RpcError::denied("unknown caller token".to_string()))?,
+        ),
+        None => None,
+    };
+    let claimed = params.get("callerId").and_then(Value::as_str);
+    let Some(caller_id) = resolve_caller(from_tree, from_token, claimed).map_err(RpcError::denied)?
+    else {
+        let presented = params.get(USER_TOKEN_PARAM).and_then(Value::as_str);
+        return check_user_token(presented, USER_TOKEN.get().map(String::as_str))
+            .map(|()| None)
+            .map_err(RpcError::denied);
+    };
+    let settings = app
+        .state::<McpManager>()
+        .load_config_blocking()
+        .unwrap_or_default()
+        .agent_api;
+    if !settings.enabled {
+        return Err(RpcError::denied("the agent API is disabled (agent_api.enabled)".to_string()));
+    }
+    let caller = manager
+        .get_session(&caller_id)
+        .map_err(|_| RpcError::denied(format!("unknown calling session: {}", caller_id)))?;
+    Ok(Some(Agent { caller, settings }))
+}
+
+/// Settle which session a request comes from. The process tree and the caller
+/// token are verified; a self-reported `callerId` is only accepted when it
+/// agrees with them.
+fn resolve_caller(
+    from_tree: Option<String>,
+    from_token: Option<String>,
+    claimed: Option<&str>,
+) -> Result<Option<String>, String> {
+    let verified = match (from_tree, from_token) {
+        (Some(tree), Some(token)) if tree != token => {
+            return Err(format!("caller token belongs to session {}, not {}", token, tree));
+        }
+        (Some(id), _) | (None, Some(id)) => Some(id),
+        (None, None) => None,
+    };
+    match (verified, claimed) {
+        (Some(id), Some(claimed)) if id != claimed => Err(format!(
+            "request claims to come from session {} but comes from {}",
+            claimed, id
+        )),
+        (None, Some(claimed)) => Err(format!(
+            "could not verify that the request comes from session {}",
+            claimed
+        )),
+        (verified, _) => Ok(verified),
+    }
+}
+
+/// A caller no session vouches for must present the user token. Without one the
+/// request may come from an agent that left its session's process tree.
+fn check_user_token(presented: Option<&str>, expected: Option<&str>) -> Result<(), String> {
+    match (presented, expected) {
+        (Some(presented), Some(expected)) if presented == expected => Ok(()),
+        (Some(_), _) => Err("invalid user token".to_string()),
+        (None, _) => Err(format!(
+            "unverified caller: requests from outside a session need the user token in {}",
+            control_token_path().display()
+        )),
+    }
+}
+
+/// Fill in what the sidebar would: a title from the tool, the default section
+/// and the tool's command, with the configured shell for shell sessions.
+fn new_session_input(app: &AppHandle, params: CreateParams) -> NewSessionInput {
+    let section_id = params
+        .section_id
+        .unwrap_or_else(|| DEFAULT_SECTION_ID.to_string());
+    let manager = app.state::<SessionManager>();
+    let tool = params
+        .tool
+        .or(manager.section_defaults(&section_id).tool)
+        .unwrap_or(SessionTool::Shell);
+    let command = params
+        .command
+        .or_else(|| manager.default_command(&section_id, &tool))
+        .unwrap_or_else(|| default_tool_command(app, &tool));
+    let title = params.title.unwrap_or_else(|| default_title(&tool));
+    NewSessionInput {
+        title,
+        project_path: params.project_path,
+        section_id,
+        tool,
+        command,
+        icon: params.icon,
+    }
+}
+
+fn default_title(tool: &SessionTool) -> String {
+    match tool {
+        SessionTool::Shell => "Terminal".to_string(),
+        SessionTool::Claude => "Claude Code".to_string(),
+        SessionTool::Gemini => "Gemini".to_string(),
+        SessionTool::Codex => "Codex".to_string(),
+        SessionTool::OpenCode => "OpenCode".to_string(),
+        SessionTool::Custom(name) => name.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_params_leave_tool_to_section_defaults() {
+        let params: CreateParams = parse(json!({ "projectPath": "/tmp" })).unwrap();
+        assert!(params.tool.is_none());
+        assert!(params.title.is_none());
+        assert!(params.section_id.is_none());
+
+        let params: CreateParams = parse(json!({ "tool": "claude", "title": "Reviewer" })).unwrap();
+        assert_eq!(params.tool, Some(SessionTool::Claude));
+        assert_eq!(params.title.as_deref(), Some("Reviewer"));
+    }
+
+    #[test]
+    fn test_callers_are_identified_without_trusting_caller_id() {
+        let lead = || Some("lead".to_string());
+        assert_eq!(resolve_caller(None, None, None), Ok(None));
+        assert_eq!(resolve_caller(lead(), None, None), Ok(lead()));
+        assert_eq!(resolve_caller(None, lead(), Some("lead")), Ok(lead()));
+        assert_eq!(resolve_caller(lead(), lead(), Some("lead")), Ok(lead()));
+
+        // An unverifiable or mismatched claim is refused rather than trusted.
+        assert!(resolve_caller(None, None, Some("lead")).is_err());
+        assert!(resolve_caller(lead(), None, Some("helper")).is_err());
+        assert!(resolve_caller(lead(), Some("helper".to_string()), None).is_err());
+    }
+
+    #[test]
+    fn test_unverified_callers_need_the_user_token() {
+        assert_eq!(check_user_token(Some("secret"), Some("secret")), Ok(()));
+        assert!(check_user_token(None, Some("secret")).is_err());
+        assert!(check_user_token(Some("guess"), Some("secret")).is_err());
+        // No token was written, so nobody is the user.
+        assert!(check_user_token(Some("secret"), None).is_err());
+    }
+
+    #[test]
+    fn test_invalid_params_use_json_rpc_code() {
+        let err = parse::<StartParams>(json!({ "rows": 10 })).unwrap_err();
+        assert_eq!(err.code, INVALID_PARAMS);
+    }
+
+    #[test]
+    fn test_error_response_shape() {
+        let response = error_response(json!(7), RpcError::from("Session not found".to_string()));
+        assert_eq!(response["id"], 7);
+        assert_eq!(response["error"]["code"], SESSION_ERROR);
+        assert_eq!(response["error"]["message"], "Session not found");
+    }
+}
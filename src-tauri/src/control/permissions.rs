@@ -0,0 +1,124 @@
+//! Permission checks for requests made by agents through the agent-term MCP server.
+//!
+//! Requests from inside a session come from the agent running there and are
+//! limited by `[agent_api]` in config.toml. Requests from outside every session
+//! that carry the user token come from the user (the CLI or their own scripts)
+//! and are not restricted.
+
+use crate::mcp::config::{AgentApiSettings, AgentScope};
+use crate::session::SessionRecord;
+
+/// The calling session and the policy that applies to it.
+pub struct Agent {
+    pub caller: SessionRecord,
+    pub settings: AgentApiSettings,
+}
+
+impl Agent {
+    /// Whether the agent may see, read and stop `target`. An agent can always
+    /// reach itself and the sessions it created.
+    pub fn can_access(&self, target: &SessionRecord) -> bool {
+        if target.id == self.caller.id || target.created_by.as_deref() == Some(&self.caller.id) {
+            return true;
+        }
+        match self.settings.scope {
+            AgentScope::Own => false,
+            AgentScope::Section => target.section_id == self.caller.section_id,
+            AgentScope::All => true,
+        }
+    }
+
+    pub fn check_access(&self, target: &SessionRecord) -> Result<(), String> {
+        if self.can_access(target) {
+            Ok(())
+        } else {
+            Err(format!(
+                "session {} is outside this agent's scope ({:?})",
+                target.id, self.settings.scope
+            ))
+        }
+    }
+
+    pub fn check_input(&self, target: &SessionRecord) -> Result<(), String> {
+        if !self.settings.allow_input && target.id != self.caller.id {
+            return Err("agents may not send input to other sessions (agent_api.allow_input)".to_string());
+        }
+        self.check_access(target)
+    }
+
+    pub fn check_create(&self) -> Result<(), String> {
+        if self.settings.allow_create {
+            Ok(())
+        } else {
+            Err("agents may not create sessions (agent_api.allow_create)".to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::{SessionStatus, SessionTool};
+
+    fn record(id: &str, section_id: &str, created_by: Option<&str>) -> SessionRecord {
+        SessionRecord {
+            id: id.to_string(),
+            title: id.to_string(),
+            project_path: String::new(),
+            section_id: section_id.to_string(),
+            tool: SessionTool::Claude,
+            command: "claude".to_string(),
+            icon: None,
+            status: SessionStatus::Idle,
+            created_at: String::new(),
+            last_accessed_at: None,
+            claude_session_id: None,
+            gemini_session_id: None,
+            loaded_mcp_names: Vec::new(),
+            is_open: true,
+            tab_order: None,
+            sandbox: None,
+            container: None,
+            remote: None,
+            created_by: created_by.map(str::to_string),
+            env: Default::default(),
+        }
+    }
+
+    fn agent(scope: AgentScope) -> Agent {
+        Agent {
+            caller: record("lead", "api", None),
+            settings: AgentApiSettings {
+                scope,
+                ..AgentApiSettings::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_scope_limits_visible_sessions() {
+        let helper = record("helper", "web", Some("lead"));
+        let sibling = record("sibling", "api", None);
+        let stranger = record("stranger", "web", None);
+
+        let own = agent(AgentScope::Own);
+        assert!(own.can_access(&helper));
+        assert!(!own.can_access(&sibling));
+
+        let section = agent(AgentScope::Section);
+        assert!(section.can_access(&sibling));
+        assert!(!section.can_access(&stranger));
+
+        assert!(agent(AgentScope::All).can_access(&stranger));
+    }
+
+    #[test]
+    fn test_input_and_create_flags() {
+        let mut lead = agent(AgentScope::All);
+        lead.settings.allow_input = false;
+        lead.settings.allow_create = false;
+        assert!(lead.check_input(&record("other", "api", None)).is_err());
+        assert!(lead.check_input(&record("lead", "api", None)).is_ok());
+        assert!(lead.check_create().is_err());
+    }
+}
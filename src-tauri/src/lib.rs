@@ -8,8 +8,10 @@ use window_vibrancy::{apply_vibrancy, NSVisualEffectMaterial};
 #[cfg(target_os = "windows")]
 use window_vibrancy::{apply_acrylic, apply_mica};
 
+mod control;
 pub mod diagnostics;
 pub mod mcp;
+mod orchestrator;
 mod search;
 mod session;
 mod tools;
@@ -128,6 +130,9 @@ pub fn run() {
 
     let update_manager = update::build_update_manager();
 
+    let orchestrator_manager = orchestrator::build_orchestrator_manager()
+        .expect("failed to build orchestrator manager");
+
     let app = tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
@@ -135,6 +140,7 @@ pub fn run() {
         .manage(mcp_manager)
         .manage(search_manager)
         .manage(update_manager)
+        .manage(orchestrator_manager)
         .invoke_handler(tauri::generate_handler![
             get_home_dir,
             get_default_shell,
@@ -145,6 +151,9 @@ pub fn run() {
             session::rename_session,
             session::set_session_command,
             session::set_session_icon,
+            session::set_session_sandbox,
+            session::set_session_container,
+            session::set_session_remote,
             session::delete_session,
             session::move_session,
             session::set_active_session,
@@ -153,13 +162,36 @@ pub fn run() {
             session::set_section_path,
             session::set_section_icon,
             session::delete_section,
+            session::move_section,
+            session::reorder_sections,
+            session::reorder_sessions,
+            session::set_section_defaults,
+            session::get_section_defaults,
             session::start_session,
             session::stop_session,
             session::restart_session,
             session::write_session_input,
+            session::broadcast_session_input,
             session::resize_session,
             session::acknowledge_session,
             session::set_tool_session_id,
+            session::enqueue_prompt,
+            session::list_prompt_queue,
+            session::cancel_queued_prompt,
+            session::reorder_prompt_queue,
+            session::list_checkpoints,
+            session::create_checkpoint,
+            session::restore_checkpoint,
+            session::list_schedules,
+            session::create_schedule,
+            session::set_schedule_enabled,
+            session::delete_schedule,
+            session::run_schedule_now,
+            session::get_session_usage,
+            session::kill_session_process,
+            session::export_session_bundle,
+            session::preview_session_bundle,
+            session::import_session_bundle,
             mcp::mcp_list,
             mcp::mcp_get_settings,
             mcp::mcp_set_settings,
@@ -167,9 +199,20 @@ pub fn run() {
             mcp::mcp_attach,
             mcp::mcp_detach,
             mcp::mcp_pool_status,
+            mcp::mcp_server_logs,
+            mcp::mcp_traffic_query,
+            mcp::mcp_traffic_export,
+            mcp::mcp_traffic_clear,
             mcp::mcp_restart_server,
             mcp::mcp_stop_server,
             mcp::mcp_start_server,
+            orchestrator::orchestrator_list_runs,
+            orchestrator::orchestrator_get_run,
+            orchestrator::orchestrator_load_graph,
+            orchestrator::orchestrator_start_run,
+            orchestrator::orchestrator_cancel_run,
+            orchestrator::orchestrator_retry_run,
+            orchestrator::orchestrator_delete_run,
             search::search_index_status,
             search::search_reindex,
             search::search_query,
@@ -220,12 +263,21 @@ pub fn run() {
                 // Blur effects achieved via CSS backdrop-filter (compositor-dependent)
             }
 
+            session::spawn_scheduler(app.handle().clone());
+            session::spawn_idle_reaper(app.handle().clone());
+            session::spawn_process_monitor(app.handle().clone());
+            control::spawn_control_server(app.handle().clone());
+            orchestrator::spawn_orchestrator(app.handle().clone());
+            mcp::spawn_log_events(app.handle().clone());
+            mcp::pool_manager::install_peer_resolver(app.handle().clone());
+
             if let Err(err) = mcp::proxy::ensure_proxy_installed(&app.handle()) {
                 let msg = err.to_string().replace('.', "");
                 diagnostics::log(format!("proxy_install_error error={}", msg));
             }
 
             let mcp_manager = app.state::<mcp::McpManager>().inner().clone();
+            let app_handle = app.handle().clone();
             tauri::async_runtime::spawn(async move {
                 if let Ok(config) = mcp_manager.load_config().await {
                     if config.mcp_pool.enabled {
@@ -235,6 +287,19 @@ pub fn run() {
                         }
                     }
                 }
+                if let Err(err) =
+                    mcp::agent_server::ensure_agent_server_registered(&app_handle, &mcp_manager).await
+                {
+                    let msg = err.to_string().replace('.', "");
+                    diagnostics::log(format!("agent_server_register_failed error={}", msg));
+                }
+                // Auto-started sessions may load pooled MCPs, so start them last.
+                let _ = tauri::async_runtime::spawn_blocking(move || {
+                    app_handle
+                        .state::<session::SessionManager>()
+                        .auto_start_sessions(&app_handle);
+                })
+                .await;
             });
 
             Ok(())
@@ -252,6 +317,8 @@ pub fn run() {
                 }
             }
 
+            control::remove_control_socket();
+
             let mcp_manager = app_handle.state::<mcp::McpManager>();
             // Use block_on for cleanup since we're in a sync context during shutdown
             if let Ok(config) = tauri::async_runtime::block_on(mcp_manager.load_config()) {
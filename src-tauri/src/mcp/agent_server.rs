@@ -0,0 +1,80 @@
+//! Registration of the built-in agent-term MCP server.
+//!
+//! The server binary ships next to the proxy. It talks to the app's control
+//! socket on behalf of the session it runs in, so it is never pooled.
+
+use tauri::AppHandle;
+
+use crate::diagnostics;
+
+use super::config::MCPDef;
+use super::error::McpResult;
+use super::manager::{McpManager, McpScope};
+use super::proxy::{install_bundled_binary, resolve_bundled_binary};
+
+/// Name of the server in config.toml and the managed MCP config.
+pub const AGENT_SERVER_NAME: &str = "agent-term";
+
+const AGENT_SERVER_BINARY: &str = "agentterm-mcp-server";
+
+/// Version of the bundled server binary (from the agentterm-mcp-server crate).
+const BUNDLED_AGENT_SERVER_VERSION: &str = "0.1.0";
+
+/// Install the server, add it to config.toml if missing and attach it globally,
+/// or detach it when `[agent_api] enabled = false`.
+pub async fn ensure_agent_server_registered(app: &AppHandle, manager: &McpManager) -> McpResult<()> {
+    let mut config = manager.load_config().await?;
+    let attached = manager
+        .get_attached_mcps(McpScope::Global, None)
+        .await?
+        .iter()
+        .any(|name| name == AGENT_SERVER_NAME);
+
+    if !config.agent_api.enabled {
+        if attached {
+            manager
+                .detach_mcp(McpScope::Global, None, AGENT_SERVER_NAME)
+                .await?;
+            diagnostics::log("agent_server_detached reason=disabled");
+        }
+        return Ok(());
+    }
+
+    // A user-edited entry is left alone.
+    if !config.mcps.contains_key(AGENT_SERVER_NAME) {
+        let install_path =
+            install_bundled_binary(app, AGENT_SERVER_BINARY, BUNDLED_AGENT_SERVER_VERSION)?;
+        let command = if install_path.exists() {
+            install_path
+        } else if let Some(bundled) = resolve_bundled_binary(app, AGENT_SERVER_BINARY) {
+            bundled
+        } else {
+            diagnostics::log("agent_server_binary_not_found");
+            return Ok(());
+        };
+        config.mcps.insert(
+            AGENT_SERVER_NAME.to_string(),
+            MCPDef {
+                command: command.display().to_string(),
+                description: "Lets agents list, create, read and wait for Agent Term sessions"
+                    .to_string(),
+                ..MCPDef::default()
+            },
+        );
+        manager.write_config(&config).await?;
+        diagnostics::log(format!(
+            "agent_server_configured command={}",
+            command.display()
+        ));
+    } else {
+        install_bundled_binary(app, AGENT_SERVER_BINARY, BUNDLED_AGENT_SERVER_VERSION)?;
+    }
+
+    if !attached {
+        manager
+            .attach_mcp(McpScope::Global, None, AGENT_SERVER_NAME)
+            .await?;
+        diagnostics::log("agent_server_attached scope=global");
+    }
+    Ok(())
+}
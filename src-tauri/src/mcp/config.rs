@@ -43,6 +43,18 @@ pub struct UserConfig {
     /// Update settings
     #[serde(default)]
     pub updates: UpdateSettings,
+
+    /// Idle session reaping settings
+    #[serde(default)]
+    pub idle: IdleSettings,
+
+    /// Session process monitoring settings
+    #[serde(default)]
+    pub monitor: MonitorSettings,
+
+    /// Built-in agent-term MCP server settings
+    #[serde(default)]
+    pub agent_api: AgentApiSettings,
 }
 
 impl Default for UserConfig {
@@ -57,6 +69,9 @@ impl Default for UserConfig {
             global_search: GlobalSearchSettings::default(),
             mcp_pool: MCPPoolSettings::default(),
             updates: UpdateSettings::default(),
+            idle: IdleSettings::default(),
+            monitor: MonitorSettings::default(),
+            agent_api: AgentApiSettings::default(),
         }
     }
 }
@@ -94,12 +109,88 @@ pub struct ToolDef {
     /// Whether this tool is enabled
     #[serde(default = "default_tool_enabled")]
     pub enabled: bool,
+
+    /// Resource limits for sessions running this tool (Unix only)
+    #[serde(default)]
+    pub limits: ResourceLimits,
+
+    /// Sandbox for sessions running this tool
+    #[serde(default)]
+    pub sandbox: SandboxSettings,
 }
 
 fn default_tool_enabled() -> bool {
     true
 }
 
+/// Per-session resource limits, applied before the tool starts
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct ResourceLimits {
+    /// Maximum memory in MB, enforced with a systemd scope; sessions fail to
+    /// start where systemd-run is unavailable
+    #[serde(default)]
+    pub memory_mb: Option<u64>,
+
+    /// Maximum CPU time in seconds
+    #[serde(default)]
+    pub cpu_seconds: Option<u64>,
+
+    /// Maximum number of open files
+    #[serde(default)]
+    pub open_files: Option<u64>,
+}
+
+impl ResourceLimits {
+    pub fn is_empty(&self) -> bool {
+        self.memory_mb.is_none() && self.cpu_seconds.is_none() && self.open_files.is_none()
+    }
+}
+
+/// Sandbox settings for a tool
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SandboxSettings {
+    /// Run sessions of this tool inside the sandbox
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// "bubblewrap" (Linux) or "wrapper"
+    #[serde(default = "default_sandbox_mode")]
+    pub mode: String,
+
+    /// Wrapper command and leading arguments for "wrapper" mode; `{project}` is
+    /// replaced with the session's project path
+    #[serde(default)]
+    pub wrapper: Vec<String>,
+
+    /// Writable paths in addition to the project directory
+    #[serde(default)]
+    pub writable_paths: Vec<String>,
+
+    /// Allow network access
+    #[serde(default = "default_sandbox_network")]
+    pub network: bool,
+}
+
+impl Default for SandboxSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            mode: default_sandbox_mode(),
+            wrapper: Vec::new(),
+            writable_paths: Vec::new(),
+            network: default_sandbox_network(),
+        }
+    }
+}
+
+fn default_sandbox_mode() -> String {
+    "bubblewrap".to_string()
+}
+
+fn default_sandbox_network() -> bool {
+    true
+}
+
 /// Shell-specific settings
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ShellSettings {
@@ -147,6 +238,18 @@ pub struct MCPDef {
     /// Transport type: "stdio", "http", or "sse"
     #[serde(default)]
     pub transport: String,
+
+    /// Tools agents may call, in every project (enforced when pooled)
+    #[serde(default)]
+    pub policy: ToolPolicy,
+
+    /// Further restrictions for sessions in a project, keyed by project path.
+    /// When any are set, clients that do not name their project may call no tools.
+    /// A client running in an agent-term session gets that session's project;
+    /// any other client's project is only what it reports, so this is advisory
+    /// for processes started outside agent-term
+    #[serde(default)]
+    pub project_policies: HashMap<String, ToolPolicy>,
 }
 
 impl Default for MCPDef {
@@ -158,10 +261,25 @@ impl Default for MCPDef {
             description: String::new(),
             url: String::new(),
             transport: String::new(),
+            policy: ToolPolicy::default(),
+            project_policies: HashMap::new(),
         }
     }
 }
 
+/// Which tools of an MCP server agents may call. Names may use `*` as a
+/// wildcard, as in `delete_*`.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ToolPolicy {
+    /// Tools that may be called (empty = all)
+    #[serde(default)]
+    pub allow: Vec<String>,
+
+    /// Tools that may never be called, even if allowed
+    #[serde(default)]
+    pub deny: Vec<String>,
+}
+
 /// Claude Code integration settings
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ClaudeSettings {
@@ -169,7 +287,7 @@ pub struct ClaudeSettings {
     #[serde(default = "default_claude_config_dir")]
     pub config_dir: String,
 
-    /// Enable dangerous mode
+    /// Skip Claude's permission prompts; only allowed for sandboxed or container sessions
     #[serde(default)]
     pub dangerous_mode: bool,
 }
@@ -297,10 +415,14 @@ pub struct MCPPoolSettings {
     #[serde(default = "default_port_end")]
     pub port_end: i32,
 
-    /// Start on demand
+    /// Spawn pooled servers when their first client connects
     #[serde(default)]
     pub start_on_demand: bool,
 
+    /// Stop pooled servers with no clients for this many minutes (0 = never)
+    #[serde(default)]
+    pub idle_timeout_minutes: u64,
+
     /// Shutdown on exit
     #[serde(default = "default_shutdown_on_exit")]
     pub shutdown_on_exit: bool,
@@ -324,6 +446,34 @@ pub struct MCPPoolSettings {
     /// Exclude MCPs from pool
     #[serde(default)]
     pub exclude_mcps: Vec<String>,
+
+    /// Restart pooled servers that crash
+    #[serde(default = "default_auto_restart")]
+    pub auto_restart: bool,
+
+    /// Crashes in a row before a pooled server is given up on
+    #[serde(default = "default_max_restarts")]
+    pub max_restarts: u32,
+
+    /// Ping pooled servers to catch ones that stopped responding
+    #[serde(default = "default_health_checks")]
+    pub health_checks: bool,
+
+    /// Restart pooled servers that fail their health checks, even with auto_restart off
+    #[serde(default)]
+    pub restart_unhealthy: bool,
+
+    /// Record requests and responses passing through the pool
+    #[serde(default)]
+    pub record_traffic: bool,
+
+    /// Recorded exchanges kept, across all pooled servers
+    #[serde(default = "default_record_max_entries")]
+    pub record_max_entries: usize,
+
+    /// Fields whose values are never recorded (case-insensitive)
+    #[serde(default = "default_record_redact")]
+    pub record_redact: Vec<String>,
 }
 
 impl Default for MCPPoolSettings {
@@ -334,12 +484,20 @@ impl Default for MCPPoolSettings {
             port_start: default_port_start(),
             port_end: default_port_end(),
             start_on_demand: false,
+            idle_timeout_minutes: 0,
             shutdown_on_exit: default_shutdown_on_exit(),
             pool_mcps: Vec::new(),
             fallback_to_stdio: default_fallback_stdio(),
             show_pool_status: default_show_pool_status(),
             pool_all: false,
             exclude_mcps: Vec::new(),
+            auto_restart: default_auto_restart(),
+            max_restarts: default_max_restarts(),
+            health_checks: default_health_checks(),
+            restart_unhealthy: false,
+            record_traffic: false,
+            record_max_entries: default_record_max_entries(),
+            record_redact: default_record_redact(),
         }
     }
 }
@@ -368,6 +526,29 @@ fn default_show_pool_status() -> bool {
     true
 }
 
+fn default_auto_restart() -> bool {
+    true
+}
+
+fn default_max_restarts() -> u32 {
+    5
+}
+
+fn default_health_checks() -> bool {
+    true
+}
+
+fn default_record_max_entries() -> usize {
+    2000
+}
+
+fn default_record_redact() -> Vec<String> {
+    ["password", "token", "secret", "apiKey", "api_key", "authorization"]
+        .into_iter()
+        .map(String::from)
+        .collect()
+}
+
 /// Update settings
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct UpdateSettings {
@@ -416,6 +597,174 @@ fn default_notify_in_cli() -> bool {
     true
 }
 
+/// Idle session reaping settings
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct IdleSettings {
+    /// Stop idle sessions in the background
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Minutes between idle checks
+    #[serde(default = "default_idle_check_interval_minutes")]
+    pub check_interval_minutes: u64,
+
+    /// Idle policies; the most specific match for a session wins
+    #[serde(default)]
+    pub policies: Vec<IdlePolicy>,
+}
+
+impl Default for IdleSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            check_interval_minutes: default_idle_check_interval_minutes(),
+            policies: Vec::new(),
+        }
+    }
+}
+
+fn default_idle_check_interval_minutes() -> u64 {
+    5
+}
+
+/// Stop sessions that have been quiet for a while
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct IdlePolicy {
+    /// Tool this policy applies to ("claude", "shell", a custom tool name; empty = any)
+    #[serde(default)]
+    pub tool: String,
+
+    /// Section id this policy applies to (empty = any)
+    #[serde(default)]
+    pub section: String,
+
+    /// Hours without input, or without the session starting new work, before
+    /// it is stopped. Output alone (a redrawn prompt, say) doesn't count.
+    pub idle_hours: f64,
+}
+
+impl IdlePolicy {
+    /// The idle limit, or `None` for hours that are not a usable duration.
+    pub fn limit(&self) -> Option<std::time::Duration> {
+        if self.idle_hours.is_nan() || self.idle_hours <= 0.0 {
+            return None;
+        }
+        std::time::Duration::try_from_secs_f64(self.idle_hours * 3600.0).ok()
+    }
+
+    fn matches(&self, tool: &str, section: &str) -> bool {
+        (self.tool.is_empty() || self.tool.eq_ignore_ascii_case(tool))
+            && (self.section.is_empty() || self.section == section)
+    }
+
+    /// Section matches outrank tool matches, which outrank catch-all policies.
+    fn specificity(&self) -> u8 {
+        (!self.section.is_empty() as u8) * 2 + (!self.tool.is_empty() as u8)
+    }
+}
+
+impl IdleSettings {
+    /// The policy that applies to a session with the given tool and section, if any.
+    pub fn policy_for(&self, tool: &str, section: &str) -> Option<&IdlePolicy> {
+        self.policies
+            .iter()
+            .filter(|policy| policy.limit().is_some() && policy.matches(tool, section))
+            .max_by_key(|policy| policy.specificity())
+    }
+}
+
+/// Session process monitoring settings
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MonitorSettings {
+    /// Sample session process trees in the background
+    #[serde(default = "default_monitor_enabled")]
+    pub enabled: bool,
+
+    /// Seconds between samples
+    #[serde(default = "default_monitor_interval_seconds")]
+    pub interval_seconds: u64,
+
+    /// Alert when a session's total CPU exceeds this percentage (0 = off)
+    #[serde(default)]
+    pub cpu_percent: f64,
+
+    /// Alert when a session's resident memory exceeds this many MB (0 = off)
+    #[serde(default)]
+    pub memory_mb: u64,
+
+    /// Alert when a session runs more than this many processes (0 = off)
+    #[serde(default)]
+    pub max_processes: usize,
+}
+
+impl Default for MonitorSettings {
+    fn default() -> Self {
+        Self {
+            enabled: default_monitor_enabled(),
+            interval_seconds: default_monitor_interval_seconds(),
+            cpu_percent: 0.0,
+            memory_mb: 0,
+            max_processes: 0,
+        }
+    }
+}
+
+fn default_monitor_enabled() -> bool {
+    false
+}
+
+fn default_monitor_interval_seconds() -> u64 {
+    5
+}
+
+/// Built-in agent-term MCP server settings
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AgentApiSettings {
+    /// Register the agent-term MCP server so agents can drive other sessions
+    #[serde(default = "default_agent_api_enabled")]
+    pub enabled: bool,
+
+    /// Which sessions an agent may see, read and type into
+    #[serde(default)]
+    pub scope: AgentScope,
+
+    /// Allow agents to create and start helper sessions
+    #[serde(default)]
+    pub allow_create: bool,
+
+    /// Allow agents to send input to other sessions
+    #[serde(default)]
+    pub allow_input: bool,
+}
+
+impl Default for AgentApiSettings {
+    fn default() -> Self {
+        Self {
+            enabled: default_agent_api_enabled(),
+            scope: AgentScope::default(),
+            allow_create: false,
+            allow_input: false,
+        }
+    }
+}
+
+fn default_agent_api_enabled() -> bool {
+    true
+}
+
+/// Sessions visible to an agent besides itself.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentScope {
+    /// Only sessions the agent created
+    Own,
+    /// Sessions it created plus those in its section
+    #[default]
+    Section,
+    /// Every session
+    All,
+}
+
 /// MCP server configuration for Claude's .mcp.json format
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct MCPServerConfig {
@@ -585,4 +934,48 @@ mod tests {
         assert!(settings.auto_start);
         assert!(settings.fallback_to_stdio);
     }
+
+    #[test]
+    fn test_idle_policy_most_specific_match_wins() {
+        let settings: IdleSettings = toml::from_str(
+            r#"
+            enabled = true
+
+            [[policies]]
+            idle_hours = 48.0
+
+            [[policies]]
+            tool = "shell"
+            idle_hours = 8.0
+
+            [[policies]]
+            tool = "claude"
+            section = "scratch"
+            idle_hours = 2.0
+            "#,
+        )
+        .unwrap();
+        assert_eq!(settings.check_interval_minutes, 5);
+        assert_eq!(settings.policy_for("shell", "default").unwrap().idle_hours, 8.0);
+        assert_eq!(settings.policy_for("claude", "default").unwrap().idle_hours, 48.0);
+        assert_eq!(settings.policy_for("claude", "scratch").unwrap().idle_hours, 2.0);
+        assert!(IdleSettings::default().policy_for("claude", "default").is_none());
+    }
+
+    #[test]
+    fn test_idle_policy_ignores_unusable_hours() {
+        let settings: IdleSettings = toml::from_str(
+            r#"
+            [[policies]]
+            idle_hours = inf
+
+            [[policies]]
+            tool = "shell"
+            idle_hours = 1e300
+            "#,
+        )
+        .unwrap();
+        assert!(settings.policy_for("claude", "default").is_none());
+        assert!(settings.policy_for("shell", "default").is_none());
+    }
 }
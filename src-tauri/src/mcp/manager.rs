@@ -82,6 +82,30 @@ impl McpManager {
         Ok(config)
     }
 
+    /// Blocking variant of `load_config` for code running outside the async runtime,
+    /// such as session start-up and background threads
+    pub fn load_config_blocking(&self) -> McpResult<UserConfig> {
+        {
+            let cache = self.config_cache.lock();
+            if let Some(ref config) = *cache {
+                return Ok(config.clone());
+            }
+        }
+
+        let config_path = get_config_path()?;
+        let config = if config_path.exists() {
+            let contents = std::fs::read_to_string(&config_path)
+                .map_err(|e| McpError::ConfigReadError(format!("{}: {}", config_path.display(), e)))?;
+            toml::from_str(&contents)
+                .map_err(|e| McpError::ConfigParseError(format!("{}: {}", config_path.display(), e)))?
+        } else {
+            UserConfig::default()
+        };
+
+        *self.config_cache.lock() = Some(config.clone());
+        Ok(config)
+    }
+
     /// Write user configuration to ~/.agent-term/config.toml
     pub async fn write_config(&self, config: &UserConfig) -> McpResult<()> {
         let config_path = get_config_path()?;
@@ -4,6 +4,7 @@
 //! Supports three scopes: Global (Agent Term managed config), Project (project .mcp.json),
 //! and Local (project .mcp.json).
 
+pub(crate) mod agent_server;
 pub(crate) mod config;
 mod error;
 mod manager;
@@ -16,9 +17,11 @@ pub use config::get_claude_config_dir;
 pub use error::McpResult;
 pub use manager::{McpManager, McpScope};
 
+use pool::logs::McpServerLogLine;
+use pool::recorder::{TrafficQuery, TrafficRecord};
 use pool::types::PoolStatusResponse;
 use serde::Serialize;
-use tauri::State;
+use tauri::{AppHandle, Emitter, State};
 use std::collections::HashMap;
 
 /// MCP info returned to frontend
@@ -40,12 +43,47 @@ pub struct McpPoolSettingsDto {
     pub port_start: i32,
     pub port_end: i32,
     pub start_on_demand: bool,
+    pub idle_timeout_minutes: u64,
     pub shutdown_on_exit: bool,
     pub pool_mcps: Vec<String>,
     pub fallback_to_stdio: bool,
     pub show_pool_status: bool,
     pub pool_all: bool,
     pub exclude_mcps: Vec<String>,
+    #[serde(default = "default_auto_restart")]
+    pub auto_restart: bool,
+    #[serde(default = "default_max_restarts")]
+    pub max_restarts: u32,
+    #[serde(default = "default_health_checks")]
+    pub health_checks: bool,
+    #[serde(default)]
+    pub restart_unhealthy: bool,
+    #[serde(default)]
+    pub record_traffic: bool,
+    #[serde(default = "default_record_max_entries")]
+    pub record_max_entries: usize,
+    #[serde(default = "default_record_redact")]
+    pub record_redact: Vec<String>,
+}
+
+fn default_auto_restart() -> bool {
+    config::MCPPoolSettings::default().auto_restart
+}
+
+fn default_max_restarts() -> u32 {
+    config::MCPPoolSettings::default().max_restarts
+}
+
+fn default_health_checks() -> bool {
+    config::MCPPoolSettings::default().health_checks
+}
+
+fn default_record_max_entries() -> usize {
+    config::MCPPoolSettings::default().record_max_entries
+}
+
+fn default_record_redact() -> Vec<String> {
+    config::MCPPoolSettings::default().record_redact
 }
 
 impl From<config::MCPPoolSettings> for McpPoolSettingsDto {
@@ -56,12 +94,20 @@ impl From<config::MCPPoolSettings> for McpPoolSettingsDto {
             port_start: settings.port_start,
             port_end: settings.port_end,
             start_on_demand: settings.start_on_demand,
+            idle_timeout_minutes: settings.idle_timeout_minutes,
             shutdown_on_exit: settings.shutdown_on_exit,
             pool_mcps: settings.pool_mcps,
             fallback_to_stdio: settings.fallback_to_stdio,
             show_pool_status: settings.show_pool_status,
             pool_all: settings.pool_all,
             exclude_mcps: settings.exclude_mcps,
+            auto_restart: settings.auto_restart,
+            max_restarts: settings.max_restarts,
+            health_checks: settings.health_checks,
+            restart_unhealthy: settings.restart_unhealthy,
+            record_traffic: settings.record_traffic,
+            record_max_entries: settings.record_max_entries,
+            record_redact: settings.record_redact,
         }
     }
 }
@@ -74,12 +120,20 @@ impl From<McpPoolSettingsDto> for config::MCPPoolSettings {
             port_start: settings.port_start,
             port_end: settings.port_end,
             start_on_demand: settings.start_on_demand,
+            idle_timeout_minutes: settings.idle_timeout_minutes,
             shutdown_on_exit: settings.shutdown_on_exit,
             pool_mcps: settings.pool_mcps,
             fallback_to_stdio: settings.fallback_to_stdio,
             show_pool_status: settings.show_pool_status,
             pool_all: settings.pool_all,
             exclude_mcps: settings.exclude_mcps,
+            auto_restart: settings.auto_restart,
+            max_restarts: settings.max_restarts,
+            health_checks: settings.health_checks,
+            restart_unhealthy: settings.restart_unhealthy,
+            record_traffic: settings.record_traffic,
+            record_max_entries: settings.record_max_entries,
+            record_redact: settings.record_redact,
         }
     }
 }
@@ -223,9 +277,64 @@ pub async fn mcp_pool_status() -> Result<PoolStatusResponse, String> {
     Ok(pool_manager::get_pool_status())
 }
 
+/// Lines pooled MCP servers show by default when no tail is given
+const DEFAULT_LOG_TAIL: usize = 200;
+
+/// Get the recent stderr of a pooled MCP server
+#[tauri::command(rename_all = "camelCase")]
+pub async fn mcp_server_logs(
+    name: String,
+    tail: Option<usize>,
+) -> Result<Vec<McpServerLogLine>, String> {
+    pool_manager::pool_server_logs(&name, tail.unwrap_or(DEFAULT_LOG_TAIL))
+        .map_err(|e| e.to_string())
+}
+
+/// Query the traffic recorded through the MCP pool
+#[tauri::command(rename_all = "camelCase")]
+pub async fn mcp_traffic_query(query: TrafficQuery) -> Result<Vec<TrafficRecord>, String> {
+    Ok(pool_manager::pool_traffic(&query))
+}
+
+/// Export recorded MCP pool traffic to a JSONL file, returning how many records were written
+#[tauri::command(rename_all = "camelCase")]
+pub async fn mcp_traffic_export(path: String, query: TrafficQuery) -> Result<usize, String> {
+    pool_manager::export_pool_traffic(&query, &config::expand_tilde(&path))
+        .map_err(|e| e.to_string())
+}
+
+/// Drop all recorded MCP pool traffic
+#[tauri::command(rename_all = "camelCase")]
+pub async fn mcp_traffic_clear() -> Result<(), String> {
+    pool_manager::clear_pool_traffic();
+    Ok(())
+}
+
+/// Forward stderr from pooled MCP servers to the frontend as
+/// `mcp-server-log` events, for live tailing.
+pub fn spawn_log_events(app: AppHandle) {
+    let mut lines = pool::logs::subscribe();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match lines.recv().await {
+                Ok(line) => {
+                    let _ = app.emit("mcp-server-log", line);
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
 /// Restart a specific MCP server in the pool
 #[tauri::command(rename_all = "camelCase")]
-pub async fn mcp_restart_server(name: String) -> Result<bool, String> {
+pub async fn mcp_restart_server(
+    state: State<'_, McpManager>,
+    name: String,
+) -> Result<bool, String> {
+    let config = state.load_config().await.map_err(|e| e.to_string())?;
+    pool_manager::update_pool_policies(&config);
     pool_manager::restart_pool_server(&name).await.map_err(|e| e.to_string())
 }
 
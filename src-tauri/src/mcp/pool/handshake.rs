@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+
+use serde_json::{json, Value};
+
+/// What the proxy does with a client message after checking it against the handshake.
+#[derive(Debug, PartialEq)]
+pub(super) enum Intercept {
+    Forward,
+    /// Forward it; it is the `initialize` the server will actually see.
+    ForwardInitialize,
+    /// Answer the client directly instead of forwarding.
+    Reply(Value),
+    Drop,
+}
+
+/// The MCP handshake of a pooled server, done once on behalf of every client.
+///
+/// The first client's `initialize` goes to the server and its result is
+/// cached. Later clients are answered from the cache (or queued until it
+/// arrives) and their `notifications/initialized` are swallowed, since most
+/// servers reject a second handshake. A later client asking for a different
+/// protocol version than the one negotiated is refused, since the shared
+/// server cannot speak two versions at once.
+#[derive(Default)]
+pub(super) struct Handshake {
+    result: Option<Value>,
+    started: bool,
+    /// Proxy id the real `initialize` was forwarded under.
+    request_id: Option<u64>,
+    /// Clients queued behind the real `initialize`: id, request id and the
+    /// protocol version they asked for.
+    waiting: Vec<(String, Value, Option<String>)>,
+    initialized_sent: bool,
+    /// What each client announced in its own `initialize`; the server only
+    /// ever sees the first client's.
+    client_capabilities: HashMap<String, Value>,
+}
+
+impl Handshake {
+    pub fn intercept(&mut self, client_id: &str, message: &Value) -> Intercept {
+        match message.get("method").and_then(Value::as_str) {
+            Some("initialize") => {
+                let capabilities = message
+                    .pointer("/params/capabilities")
+                    .cloned()
+                    .unwrap_or_else(|| json!({}));
+                self.client_capabilities
+                    .insert(client_id.to_string(), capabilities);
+                let Some(id) = message.get("id").filter(|id| !id.is_null()) else {
+                    return Intercept::Forward;
+                };
+                let requested = message
+                    .pointer("/params/protocolVersion")
+                    .and_then(Value::as_str)
+                    .map(str::to_string);
+                if let Some(result) = &self.result {
+                    return Intercept::Reply(cached_reply(id, result, requested.as_deref()));
+                }
+                if self.started {
+                    self.waiting.push((client_id.to_string(), id.clone(), requested));
+                    return Intercept::Drop;
+                }
+                self.started = true;
+                Intercept::ForwardInitialize
+            }
+            Some("notifications/initialized") => {
+                if self.initialized_sent {
+                    Intercept::Drop
+                } else {
+                    self.initialized_sent = true;
+                    Intercept::Forward
+                }
+            }
+            _ => Intercept::Forward,
+        }
+    }
+
+    pub fn forwarded(&mut self, proxy_id: u64) {
+        self.request_id = Some(proxy_id);
+    }
+
+    /// Handle a server response. If it answers the real `initialize`, caches
+    /// a successful result and returns the replies owed to queued clients.
+    pub fn on_response(&mut self, proxy_id: u64, response: &Value) -> Vec<(String, Value)> {
+        if self.request_id != Some(proxy_id) {
+            return Vec::new();
+        }
+        self.request_id = None;
+        match response.get("result") {
+            Some(result) => {
+                self.result = Some(result.clone());
+                self.waiting
+                    .drain(..)
+                    .map(|(client_id, id, requested)| {
+                        (client_id, cached_reply(&id, result, requested.as_deref()))
+                    })
+                    .collect()
+            }
+            None => {
+                // Let the next client's initialize try again.
+                self.started = false;
+                let error = response
+                    .get("error")
+                    .cloned()
+                    .unwrap_or_else(|| json!({ "code": -32603, "message": "initialize failed" }));
+                self.waiting
+                    .drain(..)
+                    .map(|(client_id, id, _)| {
+                        (client_id, json!({ "jsonrpc": "2.0", "id": id, "error": error }))
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// Whether the client announced `capability` in its own `initialize`.
+    pub fn supports(&self, client_id: &str, capability: &str) -> bool {
+        self.client_capabilities
+            .get(client_id)
+            .is_some_and(|capabilities| capabilities.get(capability).is_some())
+    }
+
+    /// Clients that have sent an `initialize`.
+    pub fn clients(&self) -> Vec<&str> {
+        self.client_capabilities.keys().map(String::as_str).collect()
+    }
+
+    pub fn client_disconnected(&mut self, client_id: &str) {
+        self.client_capabilities.remove(client_id);
+        self.waiting.retain(|(waiting, _, _)| waiting != client_id);
+    }
+}
+
+/// The cached `initialize` result for a later client, or an error if it asked
+/// for a protocol version other than the negotiated one.
+fn cached_reply(id: &Value, result: &Value, requested: Option<&str>) -> Value {
+    let negotiated = result.get("protocolVersion").and_then(Value::as_str);
+    match (requested, negotiated) {
+        (Some(requested), Some(negotiated)) if requested != negotiated => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": {
+                "code": -32602,
+                "message": "Unsupported protocol version",
+                "data": { "supported": [negotiated], "requested": requested }
+            }
+        }),
+        _ => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn initialize(id: u64, capabilities: Value) -> Value {
+        initialize_version(id, capabilities, "2025-06-18")
+    }
+
+    fn initialize_version(id: u64, capabilities: Value, version: &str) -> Value {
+        json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "initialize",
+            "params": { "protocolVersion": version, "capabilities": capabilities }
+        })
+    }
+
+    #[test]
+    fn test_only_first_initialize_reaches_server() {
+        let mut handshake = Handshake::default();
+        assert_eq!(
+            handshake.intercept("a", &initialize(1, json!({ "roots": {} }))),
+            Intercept::ForwardInitialize
+        );
+        handshake.forwarded(10);
+        assert_eq!(handshake.intercept("b", &initialize(1, json!({}))), Intercept::Drop);
+
+        let result = json!({ "protocolVersion": "2025-06-18", "capabilities": { "tools": {} } });
+        let replies = handshake.on_response(10, &json!({ "jsonrpc": "2.0", "id": 10, "result": result }));
+        assert_eq!(replies.len(), 1);
+        assert_eq!(replies[0].0, "b");
+        assert_eq!(replies[0].1["id"], json!(1));
+        assert_eq!(replies[0].1["result"], result);
+
+        match handshake.intercept("c", &initialize(3, json!({ "sampling": {} }))) {
+            Intercept::Reply(reply) => {
+                assert_eq!(reply["id"], json!(3));
+                assert_eq!(reply["result"], result);
+            }
+            other => panic!("expected a cached reply, got {:?}", other),
+        }
+        assert_eq!(handshake.client_capabilities.get("a"), Some(&json!({ "roots": {} })));
+        assert_eq!(handshake.client_capabilities.get("c"), Some(&json!({ "sampling": {} })));
+    }
+
+    #[test]
+    fn test_other_protocol_versions_are_refused() {
+        let mut handshake = Handshake::default();
+        handshake.intercept("a", &initialize(1, json!({})));
+        handshake.forwarded(7);
+        handshake.intercept("b", &initialize_version(2, json!({}), "2024-11-05"));
+
+        let result = json!({ "protocolVersion": "2025-06-18", "capabilities": {} });
+        let replies = handshake.on_response(7, &json!({ "jsonrpc": "2.0", "id": 7, "result": result }));
+        assert_eq!(replies[0].1["error"]["code"], json!(-32602));
+        assert_eq!(replies[0].1["error"]["data"]["supported"], json!(["2025-06-18"]));
+
+        match handshake.intercept("c", &initialize_version(3, json!({}), "2024-11-05")) {
+            Intercept::Reply(reply) => {
+                assert_eq!(reply["error"]["data"]["requested"], json!("2024-11-05"));
+            }
+            other => panic!("expected an error reply, got {:?}", other),
+        }
+        match handshake.intercept("d", &initialize(4, json!({}))) {
+            Intercept::Reply(reply) => assert_eq!(reply["result"], result),
+            other => panic!("expected a cached reply, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_duplicate_initialized_notifications_are_dropped() {
+        let mut handshake = Handshake::default();
+        let initialized = json!({ "jsonrpc": "2.0", "method": "notifications/initialized" });
+        assert_eq!(handshake.intercept("a", &initialized), Intercept::Forward);
+        assert_eq!(handshake.intercept("b", &initialized), Intercept::Drop);
+    }
+
+    #[test]
+    fn test_failed_initialize_is_not_cached() {
+        let mut handshake = Handshake::default();
+        handshake.intercept("a", &initialize(1, json!({})));
+        handshake.forwarded(4);
+        handshake.intercept("b", &initialize(2, json!({})));
+        let error = json!({ "code": -32602, "message": "unsupported protocol" });
+        let replies = handshake.on_response(4, &json!({ "jsonrpc": "2.0", "id": 4, "error": error }));
+        assert_eq!(replies[0].1["error"], error);
+        assert_eq!(
+            handshake.intercept("c", &initialize(1, json!({}))),
+            Intercept::ForwardInitialize
+        );
+    }
+}
@@ -0,0 +1,192 @@
+use std::time::{Duration, Instant};
+
+use super::types::HealthStatus;
+
+/// How often pooled servers are pinged and when they count as unhealthy.
+#[derive(Debug, Clone)]
+pub struct HealthCheckPolicy {
+    pub enabled: bool,
+    pub interval: Duration,
+    /// How long a ping may go unanswered before it counts as a failure.
+    pub timeout: Duration,
+    /// Failed pings in a row before the server is marked unhealthy.
+    pub unhealthy_after: u32,
+    /// Kill and restart an unhealthy server, even with auto-restart off.
+    pub restart_unhealthy: bool,
+}
+
+impl Default for HealthCheckPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval: Duration::from_secs(30),
+            timeout: Duration::from_secs(10),
+            unhealthy_after: 3,
+            restart_unhealthy: false,
+        }
+    }
+}
+
+/// Ping results for one run of a server.
+///
+/// A socket that still accepts connections says nothing about a server that
+/// is stuck, so the proxy sends its own `ping` and waits for the answer.
+/// Any response counts, even an error: the server is reading its input.
+#[derive(Debug, Default)]
+pub(super) struct Health {
+    status: HealthStatus,
+    failures: u32,
+    last_latency: Option<Duration>,
+    last_sent: Option<Instant>,
+    /// Proxy id and send time of the ping waiting for an answer.
+    outstanding: Option<(u64, Instant)>,
+}
+
+impl Health {
+    pub fn status(&self) -> HealthStatus {
+        self.status
+    }
+
+    pub fn last_latency(&self) -> Option<Duration> {
+        self.last_latency
+    }
+
+    /// Time out a ping that went unanswered, and say whether a new one is due.
+    /// A ping may be queued behind client requests, so one that times out is
+    /// not held against the server while the oldest request in flight, sent
+    /// at `oldest_request`, is younger than `timeout * unhealthy_after`. A
+    /// request older than that is itself a sign the server is stuck.
+    pub fn tick(
+        &mut self,
+        now: Instant,
+        policy: &HealthCheckPolicy,
+        oldest_request: Option<Instant>,
+    ) -> bool {
+        if let Some((_, sent)) = self.outstanding {
+            if now.duration_since(sent) < policy.timeout {
+                return false;
+            }
+            self.outstanding = None;
+            let busy_bound = policy.timeout * policy.unhealthy_after;
+            let busy = oldest_request.is_some_and(|oldest| now.duration_since(oldest) < busy_bound);
+            if !busy {
+                self.failed(policy);
+            }
+        }
+        self.last_sent
+            .is_none_or(|sent| now.duration_since(sent) >= policy.interval)
+    }
+
+    pub fn sent(&mut self, proxy_id: u64, now: Instant) {
+        self.last_sent = Some(now);
+        self.outstanding = Some((proxy_id, now));
+    }
+
+    /// A ping could not be sent or was never answered.
+    pub fn failed(&mut self, policy: &HealthCheckPolicy) {
+        self.failures += 1;
+        if self.failures >= policy.unhealthy_after {
+            self.status = HealthStatus::Unhealthy;
+        }
+    }
+
+    pub fn answered(&mut self, proxy_id: u64, now: Instant) {
+        let Some((outstanding, sent)) = self.outstanding else {
+            return;
+        };
+        if outstanding != proxy_id {
+            return;
+        }
+        self.outstanding = None;
+        self.failures = 0;
+        self.last_latency = Some(now.duration_since(sent));
+        self.status = HealthStatus::Healthy;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> HealthCheckPolicy {
+        HealthCheckPolicy {
+            interval: Duration::from_secs(30),
+            timeout: Duration::from_secs(5),
+            unhealthy_after: 2,
+            ..HealthCheckPolicy::default()
+        }
+    }
+
+    #[test]
+    fn test_answered_ping_records_latency() {
+        let policy = policy();
+        let start = Instant::now();
+        let mut health = Health::default();
+        assert!(health.tick(start, &policy, None));
+        health.sent(7, start);
+        assert!(!health.tick(start + Duration::from_secs(1), &policy, None));
+
+        health.answered(7, start + Duration::from_millis(40));
+        assert_eq!(health.status(), HealthStatus::Healthy);
+        assert_eq!(health.last_latency(), Some(Duration::from_millis(40)));
+        assert!(!health.tick(start + Duration::from_secs(10), &policy, None));
+        assert!(health.tick(start + Duration::from_secs(30), &policy, None));
+    }
+
+    #[test]
+    fn test_unanswered_pings_mark_server_unhealthy() {
+        let policy = policy();
+        let start = Instant::now();
+        let mut health = Health::default();
+        health.sent(1, start);
+        assert!(!health.tick(start + Duration::from_secs(5), &policy, None));
+        assert_eq!(health.status(), HealthStatus::Unknown);
+
+        let second = start + Duration::from_secs(30);
+        assert!(health.tick(second, &policy, None));
+        health.sent(2, second);
+        // A late answer to the first ping does not count.
+        health.answered(1, second);
+        health.tick(second + Duration::from_secs(5), &policy, None);
+        assert_eq!(health.status(), HealthStatus::Unhealthy);
+
+        let third = second + Duration::from_secs(30);
+        health.sent(3, third);
+        health.answered(3, third + Duration::from_millis(5));
+        assert_eq!(health.status(), HealthStatus::Healthy);
+    }
+
+    #[test]
+    fn test_missed_pings_do_not_count_while_requests_are_in_flight() {
+        let policy = policy();
+        let start = Instant::now();
+        let mut health = Health::default();
+        for round in 0..3 {
+            let sent = start + Duration::from_secs(30 * round);
+            // Each round a fresh request is keeping the server busy.
+            health.sent(round, sent);
+            health.tick(sent + Duration::from_secs(5), &policy, Some(sent));
+        }
+        assert_eq!(health.status(), HealthStatus::Unknown);
+    }
+
+    #[test]
+    fn test_server_stuck_on_a_request_becomes_unhealthy() {
+        let policy = policy();
+        let start = Instant::now();
+        let mut health = Health::default();
+        // The request never returns; once it is older than
+        // timeout * unhealthy_after (10s), missed pings count.
+        let stuck = Some(start);
+        health.sent(1, start);
+        health.tick(start + Duration::from_secs(5), &policy, stuck);
+        assert_eq!(health.status(), HealthStatus::Unknown);
+
+        for round in 1..3 {
+            let sent = start + Duration::from_secs(30 * round);
+            health.sent(round + 1, sent);
+            health.tick(sent + Duration::from_secs(5), &policy, stuck);
+        }
+        assert_eq!(health.status(), HealthStatus::Unhealthy);
+    }
+}
@@ -0,0 +1,202 @@
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+use agentterm_shared::socket_path::sanitize_name;
+use tokio::sync::broadcast;
+
+use crate::diagnostics;
+
+/// Stderr lines kept in memory per server.
+pub(super) const LOG_BUFFER_LINES: usize = 1000;
+/// Size at which a server's log file is rotated.
+const MAX_LOG_BYTES: u64 = 1024 * 1024;
+/// Rotated files kept besides the live one (`name.log.1` ...).
+const MAX_ROTATED_FILES: usize = 3;
+
+/// One line an MCP server wrote to stderr.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpServerLogLine {
+    /// Increases with every line of any server, so a line seen both in the
+    /// backlog and live can be told apart from a repeat.
+    pub seq: u64,
+    pub name: String,
+    pub timestamp: String,
+    pub line: String,
+}
+
+impl McpServerLogLine {
+    pub fn new(name: &str, line: &str) -> Self {
+        static NEXT_SEQ: AtomicU64 = AtomicU64::new(1);
+        Self {
+            seq: NEXT_SEQ.fetch_add(1, Ordering::Relaxed),
+            name: name.to_string(),
+            timestamp: OffsetDateTime::now_utc().format(&Rfc3339).unwrap_or_default(),
+            line: line.to_string(),
+        }
+    }
+}
+
+/// The most recent stderr lines of a server, across restarts.
+#[derive(Debug)]
+pub(super) struct LogBuffer {
+    lines: VecDeque<McpServerLogLine>,
+    capacity: usize,
+}
+
+impl LogBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            lines: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    pub fn push(&mut self, line: McpServerLogLine) {
+        if self.lines.len() == self.capacity {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(line);
+    }
+
+    /// The last `count` lines, oldest first.
+    pub fn tail(&self, count: usize) -> Vec<McpServerLogLine> {
+        let skip = self.lines.len().saturating_sub(count);
+        self.lines.iter().skip(skip).cloned().collect()
+    }
+}
+
+/// Append-only log file that rolls over to `path.1`, `path.2`, ... once it
+/// grows past `max_bytes`.
+pub(super) struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    written: u64,
+    max_bytes: u64,
+    keep: usize,
+}
+
+impl RotatingFile {
+    pub fn open(path: PathBuf) -> io::Result<Self> {
+        Self::with_limits(path, MAX_LOG_BYTES, MAX_ROTATED_FILES)
+    }
+
+    fn with_limits(path: PathBuf, max_bytes: u64, keep: usize) -> io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let written = file.metadata()?.len();
+        Ok(Self {
+            path,
+            file,
+            written,
+            max_bytes,
+            keep,
+        })
+    }
+
+    pub fn write_line(&mut self, line: &str) -> io::Result<()> {
+        if self.written > 0 && self.written + line.len() as u64 + 1 > self.max_bytes {
+            self.rotate()?;
+        }
+        writeln!(self.file, "{}", line)?;
+        self.written += line.len() as u64 + 1;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        for index in (1..self.keep).rev() {
+            let from = rotated_path(&self.path, index);
+            if from.exists() {
+                fs::rename(&from, rotated_path(&self.path, index + 1))?;
+            }
+        }
+        if self.keep > 0 {
+            fs::rename(&self.path, rotated_path(&self.path, 1))?;
+        }
+        self.file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(&self.path)?;
+        self.written = 0;
+        Ok(())
+    }
+}
+
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", index));
+    PathBuf::from(name)
+}
+
+/// Where a server's stderr is persisted: `~/.agent-term/logs/mcp/<name>.log`,
+/// with the name sanitized so it cannot point outside that directory.
+pub(super) fn log_path(name: &str) -> PathBuf {
+    diagnostics::log_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("mcp")
+        .join(format!("{}.log", sanitize_name(name)))
+}
+
+static LOG_EVENTS: OnceLock<broadcast::Sender<McpServerLogLine>> = OnceLock::new();
+
+/// Receive every stderr line pooled servers write from now on.
+pub fn subscribe() -> broadcast::Receiver<McpServerLogLine> {
+    LOG_EVENTS
+        .get_or_init(|| broadcast::channel(1024).0)
+        .subscribe()
+}
+
+/// Hand a line to live subscribers, if there are any.
+pub(super) fn publish(line: McpServerLogLine) {
+    if let Some(events) = LOG_EVENTS.get() {
+        let _ = events.send(line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffer_keeps_the_latest_lines() {
+        let mut buffer = LogBuffer::new(3);
+        for index in 0..5 {
+            buffer.push(McpServerLogLine::new("fs", &format!("line {}", index)));
+        }
+        let lines: Vec<String> = buffer.tail(10).into_iter().map(|line| line.line).collect();
+        assert_eq!(lines, vec!["line 2", "line 3", "line 4"]);
+        assert_eq!(buffer.tail(1)[0].line, "line 4");
+        assert!(buffer.tail(2)[0].seq < buffer.tail(1)[0].seq);
+    }
+
+    #[test]
+    fn test_log_path_stays_in_the_log_dir() {
+        let path = log_path("../../etc/cron.d/job");
+        assert_eq!(path.file_name().unwrap(), "______etc_cron_d_job.log");
+        assert_eq!(path.parent().unwrap().file_name().unwrap(), "mcp");
+    }
+
+    #[test]
+    fn test_file_rotates_past_its_limit() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("mcp").join("fs.log");
+        let mut file = RotatingFile::with_limits(path.clone(), 10, 2).unwrap();
+        for line in ["first", "second", "third", "fourth"] {
+            file.write_line(line).unwrap();
+        }
+        assert_eq!(fs::read_to_string(&path).unwrap(), "fourth\n");
+        assert_eq!(fs::read_to_string(rotated_path(&path, 1)).unwrap(), "third\n");
+        assert_eq!(fs::read_to_string(rotated_path(&path, 2)).unwrap(), "second\n");
+        assert!(!rotated_path(&path, 3).exists());
+    }
+}
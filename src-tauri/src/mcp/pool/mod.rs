@@ -1,8 +1,18 @@
+mod handshake;
+mod health;
+pub mod logs;
+mod policy;
 mod pool;
+pub mod recorder;
+mod routing;
 mod socket_proxy;
 pub mod transport;
 pub mod types;
 
 pub use agentterm_shared::socket_path::socket_path_for;
-pub use pool::{socket_alive, Pool, PoolConfig};
+pub use health::HealthCheckPolicy;
+pub use policy::ToolPolicies;
+pub use pool::{socket_alive, spawn_supervisor, Pool, PoolConfig, RestartPolicy};
+pub use socket_proxy::set_peer_resolver;
+pub use recorder::RecorderSettings;
 pub use types::ServerStatus;
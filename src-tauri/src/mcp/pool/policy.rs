@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Value};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+use crate::diagnostics;
+use crate::mcp::config::{expand_tilde, ToolPolicy};
+
+/// JSON-RPC error code for a call the tool policy does not allow.
+pub(super) const TOOL_BLOCKED: i64 = -32001;
+
+/// The tool policies of one pooled server: its own, and stricter ones for
+/// sessions in particular projects.
+#[derive(Debug, Clone)]
+pub struct ToolPolicies {
+    server: ToolPolicy,
+    by_project: Vec<(PathBuf, ToolPolicy)>,
+    /// Where blocked calls are appended, one JSON object per line.
+    pub(super) audit_path: PathBuf,
+}
+
+impl Default for ToolPolicies {
+    fn default() -> Self {
+        Self::new(ToolPolicy::default(), &HashMap::new())
+    }
+}
+
+impl ToolPolicies {
+    pub fn new(server: ToolPolicy, project_policies: &HashMap<String, ToolPolicy>) -> Self {
+        Self {
+            server,
+            by_project: project_policies
+                .iter()
+                .map(|(path, policy)| (expand_tilde(path), policy.clone()))
+                .collect(),
+            audit_path: diagnostics::log_dir()
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join("mcp")
+                .join("policy-audit.jsonl"),
+        }
+    }
+
+    /// Whether a session in `project` may call `tool`. The server policy and
+    /// the policy of every project containing `project` must all allow it.
+    /// A client that did not say which project it is in may call nothing
+    /// once any project has a policy.
+    pub fn allows(&self, project: Option<&str>, tool: &str) -> bool {
+        if !policy_allows(&self.server, tool) {
+            return false;
+        }
+        let Some(project) = project.map(Path::new) else {
+            return self.by_project.is_empty();
+        };
+        self.by_project
+            .iter()
+            .filter(|(path, _)| project.starts_with(path))
+            .all(|(_, policy)| policy_allows(policy, tool))
+    }
+
+    /// Check a client message. Returns the error to answer it with when it
+    /// calls a tool the policy does not allow, or names no tool to check.
+    pub fn check_call(&self, project: Option<&str>, message: &Value) -> Option<Value> {
+        if message.get("method").and_then(Value::as_str) != Some("tools/call") {
+            return None;
+        }
+        let id = message.get("id").cloned().unwrap_or(Value::Null);
+        let Some(tool) = message.pointer("/params/name").and_then(Value::as_str) else {
+            return Some(json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": {
+                    "code": TOOL_BLOCKED,
+                    "message": "tools/call without a tool name is blocked by the MCP tool policy"
+                }
+            }));
+        };
+        if self.allows(project, tool) {
+            return None;
+        }
+        Some(json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": {
+                "code": TOOL_BLOCKED,
+                "message": format!("tool {} is blocked by the MCP tool policy", tool),
+                "data": { "tool": tool }
+            }
+        }))
+    }
+
+    /// Drop tools the client may not call from a `tools/list` result.
+    pub fn filter_tools_list(&self, project: Option<&str>, response: &mut Value) {
+        if let Some(tools) = response
+            .pointer_mut("/result/tools")
+            .and_then(Value::as_array_mut)
+        {
+            tools.retain(|tool| {
+                tool.get("name")
+                    .and_then(Value::as_str)
+                    .is_none_or(|name| self.allows(project, name))
+            });
+        }
+    }
+
+    /// Append a blocked call to the audit log.
+    pub fn audit_blocked(
+        &self,
+        server: &str,
+        session_id: Option<&str>,
+        project: Option<&str>,
+        tool: &str,
+    ) {
+        let entry = json!({
+            "timestamp": OffsetDateTime::now_utc().format(&Rfc3339).unwrap_or_default(),
+            "server": server,
+            "sessionId": session_id,
+            "projectPath": project,
+            "tool": tool,
+            "action": "blocked"
+        });
+        let written = self
+            .audit_path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| {
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&self.audit_path)
+            })
+            .and_then(|mut file| writeln!(file, "{}", entry));
+        if let Err(err) = written {
+            diagnostics::log(format!(
+                "pool_policy_audit_failed name={} error={}",
+                server, err
+            ));
+        }
+    }
+}
+
+fn policy_allows(policy: &ToolPolicy, tool: &str) -> bool {
+    let allowed = policy.allow.is_empty()
+        || policy.allow.iter().any(|pattern| glob_matches(pattern, tool));
+    allowed && !policy.deny.iter().any(|pattern| glob_matches(pattern, tool))
+}
+
+/// Match `name` against a pattern where `*` stands for any run of characters.
+fn glob_matches(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No wildcard: the whole name must match.
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(allow: &[&str], deny: &[&str]) -> ToolPolicy {
+        ToolPolicy {
+            allow: allow.iter().map(|tool| tool.to_string()).collect(),
+            deny: deny.iter().map(|tool| tool.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_glob_matches() {
+        assert!(glob_matches("delete_*", "delete_row"));
+        assert!(!glob_matches("delete_*", "undelete_row"));
+        assert!(glob_matches("*", "anything"));
+        assert!(glob_matches("*_table", "drop_table"));
+        assert!(glob_matches("a*b*c", "a-b-b-c"));
+        assert!(!glob_matches("query", "query_all"));
+        assert!(!glob_matches("ab*ba", "aba"));
+    }
+
+    #[test]
+    fn test_project_policies_narrow_the_server_policy() {
+        let projects = HashMap::from([("/work/prod".to_string(), policy(&["query"], &[]))]);
+        let policies = ToolPolicies::new(policy(&[], &["delete_*"]), &projects);
+
+        assert!(!policies.allows(None, "insert_row"));
+        assert!(!policies.allows(None, "query"));
+        assert!(policies.allows(Some("/work/dev"), "insert_row"));
+        assert!(!policies.allows(Some("/work/dev"), "delete_row"));
+        assert!(policies.allows(Some("/work/prod/api"), "query"));
+        assert!(!policies.allows(Some("/work/prod"), "insert_row"));
+        assert!(policies.allows(Some("/work/production"), "insert_row"));
+    }
+
+    #[test]
+    fn test_blocked_calls_and_filtered_lists() {
+        let policies = ToolPolicies::new(policy(&[], &["delete_*"]), &HashMap::new());
+        let call = json!({
+            "jsonrpc": "2.0",
+            "id": 4,
+            "method": "tools/call",
+            "params": { "name": "delete_row", "arguments": {} }
+        });
+        let error = policies.check_call(None, &call).unwrap();
+        assert_eq!(error["id"], json!(4));
+        assert_eq!(error["error"]["code"], json!(TOOL_BLOCKED));
+        let list = json!({ "jsonrpc": "2.0", "id": 5, "method": "tools/list" });
+        assert!(policies.check_call(None, &list).is_none());
+        // A call whose tool cannot be read is refused, not let through.
+        for params in [json!({}), json!({ "name": 7 })] {
+            let nameless = json!({ "jsonrpc": "2.0", "id": 6, "method": "tools/call", "params": params });
+            let error = policies.check_call(None, &nameless).unwrap();
+            assert_eq!(error["error"]["code"], json!(TOOL_BLOCKED));
+        }
+
+        let mut response = json!({
+            "jsonrpc": "2.0",
+            "id": 5,
+            "result": { "tools": [{ "name": "query" }, { "name": "delete_row" }] }
+        });
+        policies.filter_tools_list(None, &mut response);
+        assert_eq!(response["result"]["tools"], json!([{ "name": "query" }]));
+    }
+
+    #[test]
+    fn test_blocked_calls_are_audited() {
+        let temp = tempfile::tempdir().unwrap();
+        let policies = ToolPolicies {
+            audit_path: temp.path().join("audit").join("policy-audit.jsonl"),
+            ..ToolPolicies::default()
+        };
+        policies.audit_blocked("db", Some("session-1"), Some("/work/prod"), "delete_row");
+
+        let audit = fs::read_to_string(&policies.audit_path).unwrap();
+        let entry: Value = serde_json::from_str(audit.trim()).unwrap();
+        assert_eq!(entry["server"], json!("db"));
+        assert_eq!(entry["tool"], json!("delete_row"));
+        assert_eq!(entry["sessionId"], json!("session-1"));
+    }
+}
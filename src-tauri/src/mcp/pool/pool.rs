@@ -1,15 +1,22 @@
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::{Arc, Weak};
 use std::time::{Duration, Instant};
 
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 
 use agentterm_shared::socket_path::socket_path_for;
 use crate::diagnostics;
+use crate::mcp::agent_server::AGENT_SERVER_NAME;
 use crate::mcp::config::get_agent_term_mcp_run_dir;
+use super::health::HealthCheckPolicy;
+use super::logs::McpServerLogLine;
+use super::policy::ToolPolicies;
+use super::recorder::{RecorderSettings, TrafficQuery, TrafficRecord, TrafficRecorder};
 use super::socket_proxy::SocketProxy;
-use super::types::{McpServerStatus, PoolStatusResponse, ServerStatus};
+use super::types::{HealthStatus, McpServerStatus, PoolStatusResponse, ServerStatus};
+
+const SUPERVISE_INTERVAL: Duration = Duration::from_secs(1);
 
 #[derive(Debug, Clone)]
 pub struct PoolConfig {
@@ -17,10 +24,65 @@ pub struct PoolConfig {
     pub pool_all: bool,
     pub exclude_mcps: Vec<String>,
     pub pool_mcps: Vec<String>,
+    pub restart: RestartPolicy,
+    pub health: HealthCheckPolicy,
+    pub recorder: RecorderSettings,
+    /// Spawn servers when their first client connects rather than up front.
+    pub lazy_start: bool,
+    /// Stop servers that have had no clients for this long. Implies
+    /// `lazy_start`, so the next client brings them back.
+    pub idle_timeout: Option<Duration>,
+}
+
+/// How the supervisor brings crashed servers back.
+#[derive(Debug, Clone)]
+pub struct RestartPolicy {
+    pub enabled: bool,
+    /// Crashes in a row before the server is marked `Failed` and left alone.
+    pub max_crashes: u32,
+    pub base_backoff: Duration,
+    pub max_backoff: Duration,
+    /// A server that stayed up this long has its earlier crashes forgiven.
+    pub stable_after: Duration,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_crashes: 5,
+            base_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+            stable_after: Duration::from_secs(120),
+        }
+    }
+}
+
+impl RestartPolicy {
+    /// Delay before restarting after the `crashes`-th crash in a row.
+    fn backoff(&self, crashes: u32) -> Duration {
+        let factor = 2u32.saturating_pow(crashes.saturating_sub(1));
+        self.base_backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+}
+
+/// Restart bookkeeping for one owned server.
+#[derive(Debug, Default)]
+struct Supervision {
+    restarts: u32,
+    crash_streak: u32,
+    restart_at: Option<Instant>,
+    gave_up: bool,
+    /// Killed for failing health checks; restarted even without auto-restart.
+    killed_unresponsive: bool,
+    /// When the running server last lost its last client.
+    idle_since: Option<Instant>,
 }
 
 pub struct Pool {
     proxies: RwLock<HashMap<String, Arc<SocketProxy>>>,
+    supervision: Mutex<HashMap<String, Supervision>>,
+    recorder: Arc<TrafficRecorder>,
     config: PoolConfig,
 }
 
@@ -28,12 +90,16 @@ impl Pool {
     pub fn new(config: PoolConfig) -> Self {
         Self {
             proxies: RwLock::new(HashMap::new()),
+            supervision: Mutex::new(HashMap::new()),
+            recorder: Arc::new(TrafficRecorder::new(config.recorder.clone())),
             config,
         }
     }
 
     pub fn should_pool(&self, name: &str) -> bool {
-        if !self.config.enabled {
+        // The agent-term server identifies its caller from the session's env,
+        // so every session needs its own process.
+        if !self.config.enabled || name == AGENT_SERVER_NAME {
             return false;
         }
         if self.config.pool_all {
@@ -48,12 +114,12 @@ impl Pool {
 
     pub fn is_running(&self, name: &str) -> bool {
         let proxies = self.proxies.read();
-        if let Some(proxy) = proxies.get(name) {
-            if proxy.status() == ServerStatus::Running {
-                return socket_alive(&proxy.socket_path());
-            }
+        match proxies.get(name).map(|proxy| (proxy.status(), proxy)) {
+            Some((ServerStatus::Running, proxy)) => socket_alive(&proxy.socket_path()),
+            // Listening; probing the socket would spawn the server.
+            Some((ServerStatus::Idle, _)) => true,
+            _ => false,
         }
-        false
     }
 
     pub fn socket_path(&self, name: &str) -> Option<PathBuf> {
@@ -67,28 +133,180 @@ impl Pool {
         command: String,
         args: Vec<String>,
         env: HashMap<String, String>,
+        policies: ToolPolicies,
     ) -> std::io::Result<()> {
-        {
-            let proxies = self.proxies.read();
-            if proxies.contains_key(name) {
-                return Ok(());
+        let existing = self.proxies.read().get(name).cloned();
+        if let Some(proxy) = existing {
+            proxy.set_policies(policies);
+            // Bring back a server that was stopped or gave up after crashing.
+            if proxy.is_owned() && proxy.status() != ServerStatus::Running {
+                self.reset_supervision(name);
+                return proxy.start();
             }
+            return Ok(());
         }
 
         let socket_path = socket_path_for(name);
-        let proxy = Arc::new(SocketProxy::new(
+        let proxy = SocketProxy::new(
             name.to_string(),
             socket_path,
             command,
             args,
             env,
             true,
-        ));
+        )
+        .with_recorder(self.recorder.clone())
+        .with_policies(policies);
+        let proxy = if self.config.lazy_start || self.config.idle_timeout.is_some() {
+            proxy.into_lazy()
+        } else {
+            Arc::new(proxy)
+        };
         proxy.start()?;
         self.proxies.write().insert(name.to_string(), proxy);
         Ok(())
     }
 
+    /// Ping running servers and stop idle ones, then restart owned servers
+    /// that went down on their own, backing off exponentially. A server that
+    /// keeps crashing is marked `Failed`. Without auto-restart, only servers
+    /// killed for failing health checks are restarted.
+    pub fn supervise(&self) {
+        self.check_health();
+        self.stop_idle_servers();
+        let policy = &self.config.restart;
+        let crashed: Vec<(String, Arc<SocketProxy>)> = self
+            .proxies
+            .read()
+            .iter()
+            .filter(|(_, proxy)| proxy.is_owned() && proxy.crashed())
+            .map(|(name, proxy)| (name.clone(), proxy.clone()))
+            .collect();
+        let now = Instant::now();
+        let mut supervision = self.supervision.lock();
+        for (name, proxy) in crashed {
+            let state = supervision.entry(name.clone()).or_default();
+            let killed_unresponsive = std::mem::take(&mut state.killed_unresponsive);
+            if !policy.enabled {
+                if killed_unresponsive {
+                    restart(&name, &proxy, state);
+                }
+                continue;
+            }
+            if state.gave_up {
+                continue;
+            }
+            let Some(restart_at) = state.restart_at else {
+                let ran_for = Duration::from_secs(proxy.uptime_seconds().unwrap_or(0));
+                if ran_for >= policy.stable_after {
+                    state.crash_streak = 0;
+                }
+                state.crash_streak += 1;
+                if state.crash_streak > policy.max_crashes {
+                    state.gave_up = true;
+                    proxy.mark_failed(&format!(
+                        "crashed {} times in a row; not restarting",
+                        state.crash_streak
+                    ));
+                    diagnostics::log(format!(
+                        "pool_server_failed name={} crashes={}",
+                        name, state.crash_streak
+                    ));
+                    continue;
+                }
+                let delay = policy.backoff(state.crash_streak);
+                state.restart_at = Some(now + delay);
+                diagnostics::log(format!(
+                    "pool_server_crashed name={} crashes={} restart_in_ms={}",
+                    name,
+                    state.crash_streak,
+                    delay.as_millis()
+                ));
+                continue;
+            };
+            if now < restart_at {
+                continue;
+            }
+            state.restart_at = None;
+            restart(&name, &proxy, state);
+        }
+    }
+
+    /// Apply new tool policies to a server that is already pooled.
+    pub fn set_policies(&self, name: &str, policies: ToolPolicies) {
+        if let Some(proxy) = self.proxies.read().get(name) {
+            proxy.set_policies(policies);
+        }
+    }
+
+    /// Stop lazy servers that have gone `idle_timeout` without a client.
+    fn stop_idle_servers(&self) {
+        let Some(timeout) = self.config.idle_timeout else {
+            return;
+        };
+        let lazy: Vec<(String, Arc<SocketProxy>)> = self
+            .proxies
+            .read()
+            .iter()
+            .filter(|(_, proxy)| proxy.is_owned() && proxy.is_lazy())
+            .map(|(name, proxy)| (name.clone(), proxy.clone()))
+            .collect();
+        let now = Instant::now();
+        let mut supervision = self.supervision.lock();
+        for (name, proxy) in lazy {
+            let state = supervision.entry(name.clone()).or_default();
+            if proxy.status() != ServerStatus::Running || proxy.client_count() > 0 {
+                state.idle_since = None;
+                continue;
+            }
+            let idle_since = *state.idle_since.get_or_insert(now);
+            if now.duration_since(idle_since) < timeout {
+                continue;
+            }
+            state.idle_since = None;
+            diagnostics::log(format!(
+                "pool_server_idle name={} idle_secs={}",
+                name,
+                timeout.as_secs()
+            ));
+            proxy.stop_idle();
+        }
+    }
+
+    /// Ping owned servers that are due a health check. With
+    /// `restart_unhealthy`, an unhealthy server is killed and the supervisor
+    /// restarts it.
+    fn check_health(&self) {
+        let policy = &self.config.health;
+        if !policy.enabled {
+            return;
+        }
+        let proxies: Vec<(String, Arc<SocketProxy>)> = self
+            .proxies
+            .read()
+            .iter()
+            .map(|(name, proxy)| (name.clone(), proxy.clone()))
+            .collect();
+        for (name, proxy) in proxies {
+            let health = proxy.check_health(policy);
+            if health == HealthStatus::Unhealthy && policy.restart_unhealthy {
+                diagnostics::log(format!("pool_server_unresponsive name={}", name));
+                self.supervision.lock().entry(name).or_default().killed_unresponsive = true;
+                proxy.kill_unresponsive();
+            }
+        }
+    }
+
+    /// Forget crash history, after the user starts, stops or restarts a server.
+    fn reset_supervision(&self, name: &str) {
+        let mut supervision = self.supervision.lock();
+        if let Some(state) = supervision.get_mut(name) {
+            state.crash_streak = 0;
+            state.restart_at = None;
+            state.gave_up = false;
+        }
+    }
+
     pub fn register_external_socket(&self, name: &str, socket_path: PathBuf) {
         let proxy = Arc::new(SocketProxy::new(
             name.to_string(),
@@ -152,6 +370,7 @@ impl Pool {
     /// Get status of all servers in the pool
     pub fn get_status(&self) -> PoolStatusResponse {
         let proxies = self.proxies.read();
+        let supervision = self.supervision.lock();
         let servers: Vec<McpServerStatus> = proxies
             .iter()
             .map(|(name, proxy)| McpServerStatus {
@@ -161,6 +380,10 @@ impl Pool {
                 uptime_seconds: proxy.uptime_seconds(),
                 connection_count: proxy.connection_count(),
                 owned: proxy.is_owned(),
+                restart_count: supervision.get(name).map_or(0, |state| state.restarts),
+                last_error: proxy.last_error(),
+                health: proxy.health(),
+                last_ping_ms: proxy.last_ping_ms(),
             })
             .collect();
 
@@ -171,6 +394,26 @@ impl Pool {
         }
     }
 
+    /// The last `tail` stderr lines of a server, or `None` if it is not in the pool.
+    pub fn logs(&self, name: &str, tail: usize) -> Option<Vec<McpServerLogLine>> {
+        self.proxies.read().get(name).map(|proxy| proxy.logs(tail))
+    }
+
+    /// Recorded traffic matching `query`, oldest first.
+    pub fn traffic(&self, query: &TrafficQuery) -> Vec<TrafficRecord> {
+        self.recorder.query(query)
+    }
+
+    /// Write recorded traffic matching `query` to `path` as JSON lines.
+    pub fn export_traffic(&self, query: &TrafficQuery, path: &Path) -> std::io::Result<usize> {
+        let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        self.recorder.export(query, &mut file)
+    }
+
+    pub fn clear_traffic(&self) {
+        self.recorder.clear();
+    }
+
     /// Restart a specific MCP server by name
     pub async fn restart(&self, name: &str) -> std::io::Result<bool> {
         let (proxy, exit_rx) = {
@@ -185,6 +428,7 @@ impl Pool {
             return Ok(false);
         }
 
+        self.reset_supervision(name);
         proxy.stop()?;
 
         // Wait for actual process exit instead of arbitrary sleep
@@ -204,6 +448,7 @@ impl Pool {
         };
 
         if let Some(proxy) = proxy {
+            self.reset_supervision(name);
             proxy.stop()?;
             Ok(true)
         } else {
@@ -212,6 +457,20 @@ impl Pool {
     }
 }
 
+/// Run `Pool::supervise` until the pool is dropped.
+pub fn spawn_supervisor(pool: &Arc<Pool>) {
+    let pool: Weak<Pool> = Arc::downgrade(pool);
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(SUPERVISE_INTERVAL).await;
+            let Some(pool) = pool.upgrade() else {
+                break;
+            };
+            pool.supervise();
+        }
+    });
+}
+
 pub fn socket_name_from_path(path: &Path) -> Option<String> {
     let file_name = path.file_name()?.to_string_lossy();
     if !file_name.starts_with("agentterm-mcp-") || !file_name.ends_with(".sock") {
@@ -221,6 +480,22 @@ pub fn socket_name_from_path(path: &Path) -> Option<String> {
     Some(trimmed.trim_end_matches(".sock").to_string())
 }
 
+fn restart(name: &str, proxy: &SocketProxy, state: &mut Supervision) {
+    state.restarts += 1;
+    let _ = proxy.stop();
+    match proxy.start() {
+        Ok(()) => diagnostics::log(format!(
+            "pool_server_restarted name={} restarts={}",
+            name, state.restarts
+        )),
+        // The proxy counts a failed start as another crash.
+        Err(err) => diagnostics::log(format!(
+            "pool_server_restart_failed name={} error={}",
+            name, err
+        )),
+    }
+}
+
 #[cfg(unix)]
 pub fn socket_alive(path: &PathBuf) -> bool {
     std::os::unix::net::UnixStream::connect(path).is_ok()
@@ -233,3 +508,81 @@ pub fn socket_alive(path: &PathBuf) -> bool {
         .open(path_str)
         .is_ok()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(max_crashes: u32) -> Pool {
+        Pool::new(PoolConfig {
+            enabled: true,
+            pool_all: true,
+            exclude_mcps: Vec::new(),
+            pool_mcps: Vec::new(),
+            restart: RestartPolicy {
+                max_crashes,
+                base_backoff: Duration::from_millis(10),
+                max_backoff: Duration::from_millis(40),
+                ..RestartPolicy::default()
+            },
+            health: HealthCheckPolicy {
+                enabled: false,
+                ..HealthCheckPolicy::default()
+            },
+            recorder: RecorderSettings::default(),
+            lazy_start: false,
+            idle_timeout: None,
+        })
+    }
+
+    #[test]
+    fn test_pool_all_never_pools_the_agent_server() {
+        let pool = pool(1);
+        assert!(pool.should_pool("github"));
+        assert!(!pool.should_pool(AGENT_SERVER_NAME));
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_the_cap() {
+        let policy = RestartPolicy::default();
+        assert_eq!(policy.backoff(1), Duration::from_secs(1));
+        assert_eq!(policy.backoff(3), Duration::from_secs(4));
+        assert_eq!(policy.backoff(30), Duration::from_secs(60));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_crash_looping_server_is_restarted_then_marked_failed() {
+        let name = format!("supervisor-test-{}", std::process::id());
+        let pool = pool(2);
+        // Child processes need the runtime the proxy tasks run on.
+        let server = tauri::async_runtime::block_on(async {
+            pool.start(
+                &name,
+                "sh".to_string(),
+                vec!["-c".to_string(), "echo boom >&2; exit 3".to_string()],
+                HashMap::new(),
+                ToolPolicies::default(),
+            )
+            .unwrap();
+
+            let deadline = Instant::now() + Duration::from_secs(10);
+            loop {
+                pool.supervise();
+                let status = pool.get_status();
+                let server = status.servers.into_iter().find(|server| server.name == name).unwrap();
+                if server.status == ServerStatus::Failed || Instant::now() > deadline {
+                    break server;
+                }
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        });
+        pool.shutdown();
+
+        assert_eq!(server.status, ServerStatus::Failed);
+        assert_eq!(server.restart_count, 2);
+        let error = server.last_error.unwrap();
+        assert!(error.contains("not restarting"), "{}", error);
+        assert!(error.contains("boom"), "{}", error);
+    }
+}
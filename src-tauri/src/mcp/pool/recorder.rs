@@ -0,0 +1,414 @@
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+/// Params, results and errors larger than this are replaced by a placeholder.
+const MAX_BODY_BYTES: usize = 64 * 1024;
+/// Requests unanswered for this long are no longer waited on; their records
+/// stay, without a duration.
+const IN_FLIGHT_TTL: Duration = Duration::from_secs(10 * 60);
+const REDACTED: &str = "[redacted]";
+
+/// What the traffic recorder keeps and hides.
+#[derive(Debug, Clone)]
+pub struct RecorderSettings {
+    pub enabled: bool,
+    /// Exchanges kept across all servers; the oldest are dropped first.
+    pub max_records: usize,
+    /// Stored params, results and errors across all records; the oldest
+    /// records are dropped until the total fits.
+    pub max_bytes: usize,
+    /// Object keys whose values are never stored, matched case-insensitively.
+    pub redact_fields: Vec<String>,
+}
+
+impl Default for RecorderSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_records: 2000,
+            max_bytes: 32 * 1024 * 1024,
+            redact_fields: Vec::new(),
+        }
+    }
+}
+
+/// One request a client sent to a pooled server, and its answer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrafficRecord {
+    pub id: u64,
+    pub server: String,
+    pub client_id: String,
+    pub session_id: Option<String>,
+    pub method: String,
+    pub started_at: String,
+    /// `None` while the request is still waiting for its answer.
+    pub duration_ms: Option<u64>,
+    pub params: Option<Value>,
+    pub result: Option<Value>,
+    pub error: Option<Value>,
+    pub request_bytes: usize,
+    pub response_bytes: usize,
+}
+
+/// Filter for recorded traffic. Empty fields match everything.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrafficQuery {
+    pub server: Option<String>,
+    pub session_id: Option<String>,
+    pub method: Option<String>,
+    /// Most recent records to return.
+    pub limit: Option<usize>,
+}
+
+impl TrafficQuery {
+    fn matches(&self, record: &TrafficRecord) -> bool {
+        self.server.as_ref().is_none_or(|server| &record.server == server)
+            && self
+                .session_id
+                .as_ref()
+                .is_none_or(|session| record.session_id.as_ref() == Some(session))
+            && self.method.as_ref().is_none_or(|method| &record.method == method)
+    }
+}
+
+#[derive(Default)]
+struct Traffic {
+    next_id: u64,
+    records: VecDeque<TrafficRecord>,
+    /// Stored body bytes of `records`.
+    bytes: usize,
+    /// Each request in flight, by server and proxy id.
+    in_flight: HashMap<(String, u64), InFlight>,
+}
+
+struct InFlight {
+    record_id: u64,
+    client_id: String,
+    started: Instant,
+}
+
+impl Traffic {
+    /// Drop the oldest records until both limits hold.
+    fn evict(&mut self, settings: &RecorderSettings) {
+        while self.records.len() > settings.max_records
+            || (self.bytes > settings.max_bytes && !self.records.is_empty())
+        {
+            if let Some(record) = self.records.pop_front() {
+                self.bytes -= stored_bytes(&record);
+            }
+        }
+    }
+}
+
+/// What a record's bodies cost to keep.
+fn stored_bytes(record: &TrafficRecord) -> usize {
+    body_bytes(&[&record.params, &record.result, &record.error])
+}
+
+fn body_bytes(bodies: &[&Option<Value>]) -> usize {
+    bodies
+        .iter()
+        .filter_map(|body| body.as_ref())
+        .map(|value| value.to_string().len())
+        .sum()
+}
+
+/// Opt-in log of the request/response pairs flowing through the pool.
+///
+/// Every server's proxy reports here; storage is one bounded queue for the
+/// whole pool, with redaction applied before anything is stored.
+pub struct TrafficRecorder {
+    settings: RecorderSettings,
+    traffic: Mutex<Traffic>,
+}
+
+impl TrafficRecorder {
+    pub fn new(settings: RecorderSettings) -> Self {
+        Self {
+            settings,
+            traffic: Mutex::new(Traffic::default()),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.settings.enabled
+    }
+
+    /// Note a request forwarded to `server` under `proxy_id`.
+    pub fn request(
+        &self,
+        server: &str,
+        client_id: &str,
+        session_id: Option<&str>,
+        proxy_id: u64,
+        request: &Value,
+        bytes: usize,
+    ) {
+        if !self.enabled() {
+            return;
+        }
+        let Some(method) = request.get("method").and_then(Value::as_str) else {
+            return;
+        };
+        let mut traffic = self.traffic.lock();
+        traffic.next_id += 1;
+        let id = traffic.next_id;
+        let record = TrafficRecord {
+            id,
+            server: server.to_string(),
+            client_id: client_id.to_string(),
+            session_id: session_id.map(str::to_string),
+            method: method.to_string(),
+            started_at: OffsetDateTime::now_utc().format(&Rfc3339).unwrap_or_default(),
+            duration_ms: None,
+            params: request.get("params").map(|params| self.scrub(params)),
+            result: None,
+            error: None,
+            request_bytes: bytes,
+            response_bytes: 0,
+        };
+        traffic.bytes += stored_bytes(&record);
+        traffic.records.push_back(record);
+        traffic.evict(&self.settings);
+        let now = Instant::now();
+        traffic
+            .in_flight
+            .retain(|_, request| now.duration_since(request.started) < IN_FLIGHT_TTL);
+        traffic.in_flight.insert(
+            (server.to_string(), proxy_id),
+            InFlight {
+                record_id: id,
+                client_id: client_id.to_string(),
+                started: now,
+            },
+        );
+    }
+
+    /// Attach the server's answer to the request it belongs to.
+    pub fn response(&self, server: &str, proxy_id: u64, response: &Value, bytes: usize) {
+        if !self.enabled() {
+            return;
+        }
+        let mut traffic = self.traffic.lock();
+        let Some(request) = traffic.in_flight.remove(&(server.to_string(), proxy_id)) else {
+            return;
+        };
+        let result = response.get("result").map(|result| self.scrub(result));
+        let error = response.get("error").map(|error| self.scrub(error));
+        let added = body_bytes(&[&result, &error]);
+        // Ids only grow, so the record is easy to find if it is still kept.
+        let Ok(index) = traffic
+            .records
+            .binary_search_by_key(&request.record_id, |record| record.id)
+        else {
+            return;
+        };
+        let record = &mut traffic.records[index];
+        record.duration_ms = Some(request.started.elapsed().as_millis() as u64);
+        record.result = result;
+        record.error = error;
+        record.response_bytes = bytes;
+        traffic.bytes += added;
+        traffic.evict(&self.settings);
+    }
+
+    /// Stop waiting on the requests of a client that went away.
+    pub fn client_disconnected(&self, server: &str, client_id: &str) {
+        self.traffic
+            .lock()
+            .in_flight
+            .retain(|(name, _), request| name != server || request.client_id != client_id);
+    }
+
+    /// Forget requests in flight to `server`; a restarted server reuses proxy ids.
+    pub fn server_restarted(&self, server: &str) {
+        self.traffic
+            .lock()
+            .in_flight
+            .retain(|(name, _), _| name != server);
+    }
+
+    /// Matching records, oldest first.
+    pub fn query(&self, query: &TrafficQuery) -> Vec<TrafficRecord> {
+        let traffic = self.traffic.lock();
+        let mut records: Vec<TrafficRecord> = traffic
+            .records
+            .iter()
+            .rev()
+            .filter(|record| query.matches(record))
+            .take(query.limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect();
+        records.reverse();
+        records
+    }
+
+    /// Write matching records as JSON lines. Returns how many were written.
+    pub fn export(&self, query: &TrafficQuery, writer: &mut impl Write) -> std::io::Result<usize> {
+        let records = self.query(query);
+        for record in &records {
+            serde_json::to_writer(&mut *writer, record)?;
+            writer.write_all(b"\n")?;
+        }
+        writer.flush()?;
+        Ok(records.len())
+    }
+
+    pub fn clear(&self) {
+        let mut traffic = self.traffic.lock();
+        traffic.records.clear();
+        traffic.bytes = 0;
+        traffic.in_flight.clear();
+    }
+
+    /// Redact configured fields and replace oversized bodies.
+    fn scrub(&self, value: &Value) -> Value {
+        let mut value = value.clone();
+        redact(&mut value, &self.settings.redact_fields);
+        let bytes = value.to_string().len();
+        if bytes > MAX_BODY_BYTES {
+            return json!({ "truncated": true, "bytes": bytes });
+        }
+        value
+    }
+}
+
+fn redact(value: &mut Value, fields: &[String]) {
+    match value {
+        Value::Object(map) => {
+            for (key, entry) in map.iter_mut() {
+                if fields.iter().any(|field| field.eq_ignore_ascii_case(key)) {
+                    *entry = Value::from(REDACTED);
+                } else {
+                    redact(entry, fields);
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                redact(item, fields);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recorder(max_records: usize) -> TrafficRecorder {
+        TrafficRecorder::new(RecorderSettings {
+            enabled: true,
+            max_records,
+            redact_fields: vec!["apiKey".to_string()],
+            ..RecorderSettings::default()
+        })
+    }
+
+    fn call(id: u64, tool: &str) -> Value {
+        json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "tools/call",
+            "params": { "name": tool, "arguments": { "APIKEY": "hunter2", "path": "/tmp" } }
+        })
+    }
+
+    #[test]
+    fn test_requests_are_paired_with_responses_and_redacted() {
+        let recorder = recorder(10);
+        recorder.request("fs", "fs-client-0", Some("session-1"), 1, &call(1, "read"), 80);
+        recorder.request("git", "git-client-0", None, 1, &call(1, "status"), 70);
+        recorder.response("fs", 1, &json!({ "jsonrpc": "2.0", "id": 1, "result": { "ok": true } }), 40);
+
+        let fs = recorder.query(&TrafficQuery {
+            session_id: Some("session-1".to_string()),
+            ..TrafficQuery::default()
+        });
+        assert_eq!(fs.len(), 1);
+        assert_eq!(fs[0].server, "fs");
+        assert_eq!(fs[0].result, Some(json!({ "ok": true })));
+        assert_eq!(fs[0].response_bytes, 40);
+        assert!(fs[0].duration_ms.is_some());
+        assert_eq!(fs[0].params.as_ref().unwrap()["arguments"]["APIKEY"], json!(REDACTED));
+        assert_eq!(fs[0].params.as_ref().unwrap()["arguments"]["path"], json!("/tmp"));
+
+        let git = recorder.query(&TrafficQuery {
+            server: Some("git".to_string()),
+            ..TrafficQuery::default()
+        });
+        assert_eq!(git[0].duration_ms, None);
+
+        let mut jsonl = Vec::new();
+        let written = recorder
+            .export(&TrafficQuery { method: Some("tools/call".to_string()), ..TrafficQuery::default() }, &mut jsonl)
+            .unwrap();
+        assert_eq!(written, 2);
+        assert_eq!(String::from_utf8(jsonl).unwrap().lines().count(), 2);
+    }
+
+    #[test]
+    fn test_storage_is_bounded_and_bodies_truncated() {
+        let recorder = recorder(2);
+        for id in 1..=3 {
+            recorder.request("fs", "fs-client-0", None, id, &call(id, &format!("tool-{}", id)), 10);
+        }
+        let big = "x".repeat(MAX_BODY_BYTES + 1);
+        recorder.response("fs", 3, &json!({ "id": 3, "result": { "content": big } }), big.len());
+        // The evicted request's answer has nowhere to go.
+        recorder.response("fs", 1, &json!({ "id": 1, "result": {} }), 2);
+
+        let records = recorder.query(&TrafficQuery::default());
+        let tools: Vec<&Value> = records
+            .iter()
+            .map(|record| &record.params.as_ref().unwrap()["name"])
+            .collect();
+        assert_eq!(tools, vec![&json!("tool-2"), &json!("tool-3")]);
+        assert_eq!(records[1].result.as_ref().unwrap()["truncated"], json!(true));
+        assert_eq!(recorder.query(&TrafficQuery { limit: Some(1), ..TrafficQuery::default() })[0].id, 3);
+    }
+
+    #[test]
+    fn test_total_bytes_are_capped() {
+        let recorder = TrafficRecorder::new(RecorderSettings {
+            enabled: true,
+            max_bytes: 2000,
+            ..RecorderSettings::default()
+        });
+        let body = "x".repeat(600);
+        for id in 1..=3 {
+            recorder.request("fs", "fs-client-0", None, id, &call(id, "read"), 10);
+            recorder.response("fs", id, &json!({ "id": id, "result": { "content": body } }), 620);
+        }
+        let ids: Vec<u64> = recorder.query(&TrafficQuery::default()).iter().map(|record| record.id).collect();
+        assert_eq!(ids, vec![2, 3]);
+        assert!(recorder.traffic.lock().bytes <= 2000);
+    }
+
+    #[test]
+    fn test_disconnected_clients_leave_nothing_in_flight() {
+        let recorder = recorder(10);
+        recorder.request("fs", "fs-client-0", None, 1, &call(1, "read"), 10);
+        recorder.request("fs", "fs-client-1", None, 2, &call(2, "read"), 10);
+        recorder.client_disconnected("fs", "fs-client-0");
+        let in_flight: Vec<u64> = recorder.traffic.lock().in_flight.keys().map(|(_, id)| *id).collect();
+        assert_eq!(in_flight, vec![2]);
+    }
+
+    #[test]
+    fn test_disabled_recorder_keeps_nothing() {
+        let recorder = TrafficRecorder::new(RecorderSettings::default());
+        recorder.request("fs", "fs-client-0", None, 1, &call(1, "read"), 10);
+        assert!(recorder.query(&TrafficQuery::default()).is_empty());
+    }
+}
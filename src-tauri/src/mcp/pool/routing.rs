@@ -0,0 +1,647 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::time::Instant;
+
+use agentterm_shared::pool::ClientInfo;
+use serde_json::{json, Value};
+
+use super::handshake::{Handshake, Intercept};
+
+/// A client request forwarded to the server under a proxy-assigned id.
+struct PendingRequest {
+    client_id: String,
+    original_id: Value,
+    method: Option<String>,
+    /// The client's own progress token; the server sees the proxy id instead.
+    progress_token: Option<Value>,
+    forwarded_at: Instant,
+}
+
+/// Requests waiting on the server, keyed by the id the proxy gave them.
+///
+/// Every client numbers its own requests, so two sessions sharing a server
+/// both send `id: 1`. Each request is forwarded under an id unique to this
+/// proxy and the client's own id is put back on the response.
+#[derive(Default)]
+struct PendingRequests {
+    next_id: u64,
+    by_proxy_id: HashMap<u64, PendingRequest>,
+}
+
+impl PendingRequests {
+    fn register(
+        &mut self,
+        client_id: &str,
+        original_id: Value,
+        method: Option<String>,
+        progress_token: Option<Value>,
+    ) -> u64 {
+        self.next_id += 1;
+        self.by_proxy_id.insert(
+            self.next_id,
+            PendingRequest {
+                client_id: client_id.to_string(),
+                original_id,
+                method,
+                progress_token,
+                forwarded_at: Instant::now(),
+            },
+        );
+        self.next_id
+    }
+
+    /// Take an id for a request the proxy sends itself.
+    fn reserve(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    fn resolve(&mut self, proxy_id: u64) -> Option<PendingRequest> {
+        self.by_proxy_id.remove(&proxy_id)
+    }
+
+    fn proxy_id_for(&self, client_id: &str, original_id: &Value) -> Option<u64> {
+        self.by_proxy_id
+            .iter()
+            .find(|(_, pending)| {
+                pending.client_id == client_id && &pending.original_id == original_id
+            })
+            .map(|(proxy_id, _)| *proxy_id)
+    }
+
+    /// Clients with requests in flight, most recent request first.
+    fn active_clients(&self) -> Vec<&str> {
+        let mut requests: Vec<(&u64, &PendingRequest)> = self.by_proxy_id.iter().collect();
+        requests.sort_by(|a, b| b.0.cmp(a.0));
+        requests
+            .into_iter()
+            .map(|(_, pending)| pending.client_id.as_str())
+            .collect()
+    }
+
+    fn drop_client(&mut self, client_id: &str) {
+        self.by_proxy_id.retain(|_, pending| pending.client_id != client_id);
+    }
+}
+
+/// What to do with a message a client sent.
+#[derive(Debug, PartialEq)]
+pub(super) enum ClientAction {
+    Forward,
+    /// Answer the client directly instead of forwarding.
+    Reply(Value),
+    Drop,
+}
+
+/// Where a notification or request the server sent on its own should go.
+#[derive(Debug, PartialEq)]
+pub(super) enum Delivery {
+    Clients(Vec<String>),
+    Broadcast,
+    Drop,
+    /// No client can take it; send this back to the server instead.
+    ReplyToServer(Value),
+}
+
+/// Bookkeeping for routing messages between a server and its clients.
+#[derive(Default)]
+pub(super) struct Routing {
+    pending: PendingRequests,
+    handshake: Handshake,
+    /// Clients subscribed to each resource URI.
+    subscriptions: HashMap<String, BTreeSet<String>>,
+    /// Server-initiated requests awaiting a client's answer, keyed by the
+    /// serialized server id, with the client they went to.
+    server_requests: HashMap<String, (String, Value)>,
+    /// The client that most recently sent a request.
+    last_client: Option<String>,
+    /// Proxy id of the latest health-check ping.
+    health_ping: Option<u64>,
+    /// Proxy ids of other requests the proxy sent on its own behalf.
+    proxy_requests: HashSet<u64>,
+    /// The session and project each client belongs to.
+    client_info: HashMap<String, ClientInfo>,
+    /// Clients that have sent at least one message or were identified by
+    /// their process; a client-info message from them is ignored.
+    seen_clients: HashSet<String>,
+}
+
+impl Routing {
+    /// Rewrite a client message for the server, or handle it here.
+    pub fn client_message(&mut self, client_id: &str, message: &mut Value) -> ClientAction {
+        let first = self.seen_clients.insert(client_id.to_string());
+        if let Some(info) = ClientInfo::from_message(message) {
+            // Only a client's opening message may say who it is; a later one
+            // could move it out from under a project's tool policy.
+            if first {
+                self.client_info.insert(client_id.to_string(), info);
+            }
+            return ClientAction::Drop;
+        }
+        let Some(method) = message.get("method").and_then(Value::as_str).map(str::to_string) else {
+            // An answer to a server-initiated request keeps the server's id.
+            if let Some(id) = message.get("id") {
+                self.server_requests.remove(&id.to_string());
+            }
+            return ClientAction::Forward;
+        };
+        match self.handshake.intercept(client_id, message) {
+            Intercept::Reply(reply) => return ClientAction::Reply(reply),
+            Intercept::Drop => return ClientAction::Drop,
+            Intercept::ForwardInitialize => {
+                if let Some(proxy_id) = self.forward_request(client_id, message) {
+                    self.handshake.forwarded(proxy_id);
+                }
+                return ClientAction::Forward;
+            }
+            Intercept::Forward => {}
+        }
+        match method.as_str() {
+            "notifications/cancelled" => {
+                let proxy_id = message
+                    .pointer("/params/requestId")
+                    .and_then(|request_id| self.pending.proxy_id_for(client_id, request_id));
+                if let (Some(proxy_id), Some(request_id)) =
+                    (proxy_id, message.pointer_mut("/params/requestId"))
+                {
+                    *request_id = Value::from(proxy_id);
+                }
+            }
+            "resources/subscribe" | "resources/unsubscribe" => {
+                if let Some(reply) = self.subscription(client_id, &method, message) {
+                    return ClientAction::Reply(reply);
+                }
+            }
+            _ => {}
+        }
+        self.forward_request(client_id, message);
+        ClientAction::Forward
+    }
+
+    /// Record the session a client's process runs in. Its own client-info
+    /// message, which it could fill with anything, is then ignored.
+    pub fn identify_client(&mut self, client_id: &str, info: ClientInfo) {
+        self.seen_clients.insert(client_id.to_string());
+        self.client_info.insert(client_id.to_string(), info);
+    }
+
+    /// The session a client belongs to, if known.
+    pub fn session(&self, client_id: &str) -> Option<&str> {
+        self.client_info.get(client_id)?.session_id.as_deref()
+    }
+
+    /// The project a client's session runs in, if known.
+    pub fn project(&self, client_id: &str) -> Option<&str> {
+        self.client_info.get(client_id)?.project_path.as_deref()
+    }
+
+    /// The method of the client request a server response will answer.
+    pub fn pending_method(&self, response: &Value) -> Option<&str> {
+        let proxy_id = response.get("id").and_then(Value::as_u64)?;
+        self.pending.by_proxy_id.get(&proxy_id)?.method.as_deref()
+    }
+
+    /// Give a request a proxy-unique id, and its progress token the same value.
+    fn forward_request(&mut self, client_id: &str, message: &mut Value) -> Option<u64> {
+        let id = message.get_mut("id").filter(|id| !id.is_null())?.take();
+        let progress_token = message
+            .pointer_mut("/params/_meta/progressToken")
+            .map(Value::take);
+        let has_token = progress_token.is_some();
+        let method = message.get("method").and_then(Value::as_str).map(str::to_string);
+        let proxy_id = self.pending.register(client_id, id, method, progress_token);
+        message["id"] = Value::from(proxy_id);
+        if has_token {
+            message["params"]["_meta"]["progressToken"] = Value::from(proxy_id);
+        }
+        self.last_client = Some(client_id.to_string());
+        Some(proxy_id)
+    }
+
+    /// Track a resource subscription. Returns a reply when another client
+    /// already holds (or still needs) the server-side subscription.
+    fn subscription(&mut self, client_id: &str, method: &str, message: &Value) -> Option<Value> {
+        let uri = message.pointer("/params/uri").and_then(Value::as_str)?;
+        let id = message.get("id")?.clone();
+        let subscribers = self.subscriptions.entry(uri.to_string()).or_default();
+        let shared = subscribers.iter().any(|subscriber| subscriber != client_id);
+        if method == "resources/subscribe" {
+            subscribers.insert(client_id.to_string());
+        } else {
+            subscribers.remove(client_id);
+            if subscribers.is_empty() {
+                self.subscriptions.remove(uri);
+            }
+        }
+        shared.then(|| json!({ "jsonrpc": "2.0", "id": id, "result": {} }))
+    }
+
+    /// Match a server response to the request it answers and put the
+    /// client's id back. Returns every message owed to a client as a result.
+    pub fn server_response(&mut self, response: &mut Value) -> Vec<(String, Value)> {
+        let Some(proxy_id) = response.get("id").and_then(Value::as_u64) else {
+            return Vec::new();
+        };
+        let mut deliveries = self.handshake.on_response(proxy_id, response);
+        if let Some(request) = self.pending.resolve(proxy_id) {
+            response["id"] = request.original_id;
+            deliveries.push((request.client_id, response.take()));
+        }
+        deliveries
+    }
+
+    /// Decide who gets a notification or request the server sent on its own.
+    pub fn server_message(&mut self, message: &mut Value) -> Delivery {
+        let method = message
+            .get("method")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+        if let Some(id) = message.get("id").filter(|id| !id.is_null()).cloned() {
+            return self.server_request(&method, id);
+        }
+        if method.ends_with("/list_changed") {
+            return Delivery::Broadcast;
+        }
+        match method.as_str() {
+            "notifications/progress" => {
+                let Some(token) = message.pointer_mut("/params/progressToken") else {
+                    return Delivery::Drop;
+                };
+                let request = token
+                    .as_u64()
+                    .and_then(|proxy_id| self.pending.by_proxy_id.get(&proxy_id));
+                match request {
+                    Some(request) => {
+                        *token = request.progress_token.clone().unwrap_or(Value::Null);
+                        Delivery::Clients(vec![request.client_id.clone()])
+                    }
+                    None => Delivery::Drop,
+                }
+            }
+            "notifications/resources/updated" => {
+                let subscribers = message
+                    .pointer("/params/uri")
+                    .and_then(Value::as_str)
+                    .and_then(|uri| self.subscriptions.get(uri));
+                match subscribers {
+                    Some(subscribers) => Delivery::Clients(subscribers.iter().cloned().collect()),
+                    None => Delivery::Drop,
+                }
+            }
+            "notifications/cancelled" => {
+                let request = message
+                    .pointer("/params/requestId")
+                    .and_then(|request_id| self.server_requests.remove(&request_id.to_string()));
+                match request {
+                    Some((client_id, _)) => Delivery::Clients(vec![client_id]),
+                    None => Delivery::Drop,
+                }
+            }
+            // Log messages and anything else go to whoever the server is
+            // most likely working for.
+            _ => match self.likely_client(None) {
+                Some(client_id) => Delivery::Clients(vec![client_id]),
+                None => Delivery::Drop,
+            },
+        }
+    }
+
+    fn server_request(&mut self, method: &str, id: Value) -> Delivery {
+        let capability = match method {
+            "sampling/createMessage" => Some("sampling"),
+            "roots/list" => Some("roots"),
+            "elicitation/create" => Some("elicitation"),
+            _ => None,
+        };
+        match self.likely_client(capability) {
+            Some(client_id) => {
+                self.server_requests
+                    .insert(id.to_string(), (client_id.clone(), id));
+                Delivery::Clients(vec![client_id])
+            }
+            None => Delivery::ReplyToServer(json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": {
+                    "code": -32601,
+                    "message": format!("no connected client supports {}", method)
+                }
+            })),
+        }
+    }
+
+    /// The client a server-initiated message most likely belongs to: the one
+    /// with the most recent request in flight, then the last one to send a
+    /// request, then any client. Only clients with `capability` qualify.
+    fn likely_client(&self, capability: Option<&str>) -> Option<String> {
+        let qualifies = |client_id: &str| {
+            capability.is_none_or(|capability| self.handshake.supports(client_id, capability))
+        };
+        self.pending
+            .active_clients()
+            .into_iter()
+            .chain(self.last_client.as_deref())
+            .chain(capability.map_or_else(Vec::new, |_| self.handshake.clients()))
+            .find(|client_id| qualifies(client_id))
+            .map(str::to_string)
+    }
+
+    /// When the longest-waiting client request was forwarded to the server.
+    pub fn oldest_request(&self) -> Option<Instant> {
+        self.pending
+            .by_proxy_id
+            .values()
+            .map(|pending| pending.forwarded_at)
+            .min()
+    }
+
+    /// A `ping` from the proxy itself, under an id no client request uses.
+    pub fn health_ping(&mut self) -> (u64, Value) {
+        let proxy_id = self.pending.reserve();
+        self.health_ping = Some(proxy_id);
+        (proxy_id, json!({ "jsonrpc": "2.0", "id": proxy_id, "method": "ping" }))
+    }
+
+    /// If `response` answers the latest health-check ping, consume it and
+    /// return its proxy id. It is not for any client.
+    pub fn health_response(&mut self, response: &Value) -> Option<u64> {
+        let proxy_id = response.get("id").and_then(Value::as_u64)?;
+        if self.health_ping != Some(proxy_id) {
+            return None;
+        }
+        self.health_ping = None;
+        Some(proxy_id)
+    }
+
+    /// If `response` answers a request the proxy sent on its own behalf,
+    /// consume it. It is not for any client.
+    pub fn proxy_response(&mut self, response: &Value) -> bool {
+        response
+            .get("id")
+            .and_then(Value::as_u64)
+            .is_some_and(|proxy_id| self.proxy_requests.remove(&proxy_id))
+    }
+
+    /// Drop every request in flight. Returns an error response for each,
+    /// addressed to the client that sent it.
+    pub fn fail_pending(&mut self, message: &str) -> Vec<(String, Value)> {
+        let mut requests: Vec<(u64, PendingRequest)> = self.pending.by_proxy_id.drain().collect();
+        requests.sort_by_key(|(proxy_id, _)| *proxy_id);
+        requests
+            .into_iter()
+            .map(|(_, request)| {
+                let error = json!({
+                    "jsonrpc": "2.0",
+                    "id": request.original_id,
+                    "error": { "code": -32603, "message": message }
+                });
+                (request.client_id, error)
+            })
+            .collect()
+    }
+
+    /// Forget a client. Returns the messages the server is owed: error
+    /// responses for server requests the client will now never answer, and
+    /// an unsubscribe for each resource it was the last subscriber to.
+    pub fn client_disconnected(&mut self, client_id: &str) -> Vec<Value> {
+        self.pending.drop_client(client_id);
+        self.handshake.client_disconnected(client_id);
+        self.client_info.remove(client_id);
+        self.seen_clients.remove(client_id);
+        let mut owed = Vec::new();
+        let mut abandoned = Vec::new();
+        self.subscriptions.retain(|uri, subscribers| {
+            if subscribers.remove(client_id) && subscribers.is_empty() {
+                abandoned.push(uri.clone());
+            }
+            !subscribers.is_empty()
+        });
+        for uri in abandoned {
+            let proxy_id = self.pending.reserve();
+            self.proxy_requests.insert(proxy_id);
+            owed.push(json!({
+                "jsonrpc": "2.0",
+                "id": proxy_id,
+                "method": "resources/unsubscribe",
+                "params": { "uri": uri }
+            }));
+        }
+        if self.last_client.as_deref() == Some(client_id) {
+            self.last_client = None;
+        }
+        self.server_requests.retain(|_, (owner, id)| {
+            if owner != client_id {
+                return true;
+            }
+            owed.push(json!({
+                "jsonrpc": "2.0",
+                "id": id.take(),
+                "error": { "code": -32603, "message": "client disconnected" }
+            }));
+            false
+        });
+        owed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(id: u64, method: &str, params: Value) -> Value {
+        json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+    }
+
+    fn initialize(routing: &mut Routing, client_id: &str, capabilities: Value) {
+        let mut message = request(0, "initialize", json!({ "capabilities": capabilities }));
+        routing.client_message(client_id, &mut message);
+    }
+
+    #[test]
+    fn test_requests_get_proxy_unique_ids() {
+        let mut routing = Routing::default();
+        let mut first = request(1, "tools/list", json!({}));
+        let mut second = request(1, "tools/list", json!({}));
+        assert_eq!(routing.client_message("a", &mut first), ClientAction::Forward);
+        assert_eq!(routing.client_message("b", &mut second), ClientAction::Forward);
+        assert_ne!(first["id"], second["id"]);
+
+        let mut cancel = json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/cancelled",
+            "params": { "requestId": 1 }
+        });
+        routing.client_message("b", &mut cancel);
+        assert_eq!(cancel["params"]["requestId"], second["id"]);
+
+        let mut response = json!({ "jsonrpc": "2.0", "id": first["id"], "result": {} });
+        let deliveries = routing.server_response(&mut response);
+        assert_eq!(deliveries, vec![("a".to_string(), json!({ "jsonrpc": "2.0", "id": 1, "result": {} }))]);
+
+        routing.client_disconnected("b");
+        let mut response = json!({ "jsonrpc": "2.0", "id": second["id"], "result": {} });
+        assert!(routing.server_response(&mut response).is_empty());
+    }
+
+    #[test]
+    fn test_progress_goes_to_the_requesting_client() {
+        let mut routing = Routing::default();
+        let mut call = request(5, "tools/call", json!({ "name": "build", "_meta": { "progressToken": "tok" } }));
+        routing.client_message("a", &mut call);
+        let mut other = request(5, "tools/call", json!({ "name": "build", "_meta": { "progressToken": "tok" } }));
+        routing.client_message("b", &mut other);
+        assert_eq!(call["params"]["_meta"]["progressToken"], call["id"]);
+
+        let mut progress = json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/progress",
+            "params": { "progressToken": call["id"], "progress": 1 }
+        });
+        assert_eq!(routing.server_message(&mut progress), Delivery::Clients(vec!["a".to_string()]));
+        assert_eq!(progress["params"]["progressToken"], json!("tok"));
+
+        let mut stale = json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/progress",
+            "params": { "progressToken": 999, "progress": 1 }
+        });
+        assert_eq!(routing.server_message(&mut stale), Delivery::Drop);
+    }
+
+    #[test]
+    fn test_resource_updates_go_to_subscribers() {
+        let mut routing = Routing::default();
+        let uri = json!({ "uri": "file:///log" });
+        let mut subscribe = request(1, "resources/subscribe", uri.clone());
+        assert_eq!(routing.client_message("a", &mut subscribe), ClientAction::Forward);
+        let mut subscribe = request(1, "resources/subscribe", uri.clone());
+        assert!(matches!(routing.client_message("b", &mut subscribe), ClientAction::Reply(_)));
+
+        let mut updated = json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/resources/updated",
+            "params": { "uri": "file:///log" }
+        });
+        assert_eq!(
+            routing.server_message(&mut updated),
+            Delivery::Clients(vec!["a".to_string(), "b".to_string()])
+        );
+
+        // b still needs the subscription, so a's unsubscribe stays local.
+        let mut unsubscribe = request(2, "resources/unsubscribe", uri.clone());
+        assert!(matches!(routing.client_message("a", &mut unsubscribe), ClientAction::Reply(_)));
+        let mut unsubscribe = request(2, "resources/unsubscribe", uri);
+        assert_eq!(routing.client_message("b", &mut unsubscribe), ClientAction::Forward);
+        assert_eq!(routing.server_message(&mut updated), Delivery::Drop);
+
+        let mut changed = json!({ "jsonrpc": "2.0", "method": "notifications/tools/list_changed" });
+        assert_eq!(routing.server_message(&mut changed), Delivery::Broadcast);
+    }
+
+    #[test]
+    fn test_last_subscriber_disconnecting_unsubscribes() {
+        let mut routing = Routing::default();
+        let uri = json!({ "uri": "file:///log" });
+        let mut subscribe = request(1, "resources/subscribe", uri.clone());
+        routing.client_message("a", &mut subscribe);
+        let mut subscribe = request(1, "resources/subscribe", uri);
+        routing.client_message("b", &mut subscribe);
+
+        assert!(routing.client_disconnected("a").is_empty());
+        let owed = routing.client_disconnected("b");
+        assert_eq!(owed.len(), 1);
+        assert_eq!(owed[0]["method"], json!("resources/unsubscribe"));
+        assert_eq!(owed[0]["params"]["uri"], json!("file:///log"));
+
+        let response = json!({ "jsonrpc": "2.0", "id": owed[0]["id"], "result": {} });
+        assert!(routing.proxy_response(&response));
+        assert!(!routing.proxy_response(&response));
+    }
+
+    #[test]
+    fn test_server_requests_go_to_a_capable_client() {
+        let mut routing = Routing::default();
+        initialize(&mut routing, "a", json!({ "sampling": {} }));
+        initialize(&mut routing, "b", json!({ "roots": {} }));
+        let mut call = request(3, "tools/call", json!({ "name": "summarize" }));
+        routing.client_message("b", &mut call);
+
+        let mut sampling = request(70, "sampling/createMessage", json!({}));
+        assert_eq!(routing.server_message(&mut sampling), Delivery::Clients(vec!["a".to_string()]));
+        let mut roots = request(71, "roots/list", json!({}));
+        assert_eq!(routing.server_message(&mut roots), Delivery::Clients(vec!["b".to_string()]));
+        let mut elicit = request(72, "elicitation/create", json!({}));
+        assert!(matches!(routing.server_message(&mut elicit), Delivery::ReplyToServer(_)));
+
+        let failed = routing.fail_pending("MCP server exited");
+        assert!(failed.iter().any(|(client_id, error)| {
+            client_id == "b"
+                && error["id"] == json!(3)
+                && error["error"]["message"] == json!("MCP server exited")
+        }));
+
+        let owed = routing.client_disconnected("a");
+        assert_eq!(owed.len(), 1);
+        assert_eq!(owed[0]["id"], json!(70));
+        assert!(owed[0]["error"].is_object());
+    }
+
+    #[test]
+    fn test_client_info_names_the_session_and_project() {
+        let mut routing = Routing::default();
+        let info = ClientInfo {
+            session_id: Some("session-7".to_string()),
+            project_path: Some("/work/app".to_string()),
+        };
+        assert_eq!(routing.client_message("a", &mut info.to_message()), ClientAction::Drop);
+        assert_eq!(routing.session("a"), Some("session-7"));
+        assert_eq!(routing.project("a"), Some("/work/app"));
+
+        let moved = ClientInfo {
+            session_id: Some("session-7".to_string()),
+            project_path: Some("/work/other".to_string()),
+        };
+        assert_eq!(routing.client_message("a", &mut moved.to_message()), ClientAction::Drop);
+        assert_eq!(routing.project("a"), Some("/work/app"));
+        let mut list = request(1, "tools/list", json!({}));
+        routing.client_message("b", &mut list);
+        routing.client_message("b", &mut moved.to_message());
+        assert_eq!(routing.project("b"), None);
+
+        routing.client_disconnected("a");
+        assert_eq!(routing.session("a"), None);
+    }
+
+    #[test]
+    fn test_identified_client_cannot_claim_another_project() {
+        let mut routing = Routing::default();
+        let session = ClientInfo {
+            session_id: Some("session-7".to_string()),
+            project_path: Some("/work/app".to_string()),
+        };
+        routing.identify_client("a", session);
+        let claimed = ClientInfo {
+            session_id: Some("session-9".to_string()),
+            project_path: Some("/work/prod".to_string()),
+        };
+        assert_eq!(routing.client_message("a", &mut claimed.to_message()), ClientAction::Drop);
+        assert_eq!(routing.session("a"), Some("session-7"));
+        assert_eq!(routing.project("a"), Some("/work/app"));
+    }
+
+    #[test]
+    fn test_health_ping_answer_is_not_routed_to_clients() {
+        let mut routing = Routing::default();
+        let mut list = request(1, "tools/list", json!({}));
+        routing.client_message("a", &mut list);
+        let (proxy_id, ping) = routing.health_ping();
+        assert_eq!(ping["method"], json!("ping"));
+        assert_ne!(Some(proxy_id), list["id"].as_u64());
+
+        let mut answer = json!({ "jsonrpc": "2.0", "id": proxy_id, "result": {} });
+        assert_eq!(routing.health_response(&answer), Some(proxy_id));
+        assert!(routing.server_response(&mut answer).is_empty());
+        let client_answer = json!({ "jsonrpc": "2.0", "id": list["id"], "result": {} });
+        assert_eq!(routing.health_response(&client_answer), None);
+    }
+}
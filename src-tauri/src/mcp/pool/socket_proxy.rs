@@ -1,26 +1,76 @@
 use std::collections::HashMap;
-use std::fs::{create_dir_all, File};
 use std::io;
 use std::path::PathBuf;
 use std::process::Stdio;
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, OnceLock, RwLock, Weak};
 
 #[cfg(windows)]
 const CREATE_NO_WINDOW: u32 = 0x08000000;
 
-use serde_json::Value;
+use agentterm_shared::pool::ClientInfo;
+use serde_json::{json, Value};
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::process::{ChildStderr, ChildStdin, ChildStdout, Command};
 use tokio::sync::{mpsc, oneshot};
 use tokio::time::{sleep, Duration, Instant};
 
 use crate::diagnostics;
+use super::health::{Health, HealthCheckPolicy};
+use super::logs::{self, LogBuffer, McpServerLogLine, RotatingFile, LOG_BUFFER_LINES};
+use super::policy::ToolPolicies;
+use super::recorder::TrafficRecorder;
+use super::routing::{ClientAction, Delivery, Routing};
 use super::transport::{self, LocalListener, LocalStream};
-use super::types::ServerStatus;
+use super::types::{HealthStatus, ServerStatus};
 
 type ClientSender = mpsc::Sender<String>;
 
+/// Stderr lines kept for the error shown when a server keeps crashing.
+const STDERR_TAIL_LINES: usize = 20;
+
+/// Finds the session a client process runs in.
+pub type PeerResolver = Box<dyn Fn(u32) -> Option<ClientInfo> + Send + Sync>;
+
+static PEER_RESOLVER: OnceLock<PeerResolver> = OnceLock::new();
+
+/// Identify pool clients by their process instead of their client-info
+/// message. Clients no session owns are still taken at their word.
+pub fn set_peer_resolver(resolver: PeerResolver) {
+    let _ = PEER_RESOLVER.set(resolver);
+}
+
+/// The session of the process on the other end of a client connection.
+async fn peer_session(stream: &LocalStream) -> Option<ClientInfo> {
+    let pid = transport::peer_pid(stream)?;
+    let resolver = PEER_RESOLVER.get()?;
+    // Resolving walks the process tree.
+    tauri::async_runtime::spawn_blocking(move || resolver(pid))
+        .await
+        .ok()?
+}
+
+/// Handles the client and server tasks share to pass messages along.
+#[derive(Clone)]
+struct Shared {
+    name: String,
+    request_tx: Arc<Mutex<Option<mpsc::Sender<String>>>>,
+    routing: Arc<Mutex<Routing>>,
+    clients: Arc<Mutex<HashMap<String, ClientSender>>>,
+    recorder: Option<Arc<TrafficRecorder>>,
+    policies: Arc<RwLock<Arc<ToolPolicies>>>,
+}
+
+/// Why the exit task kills the child.
+#[derive(Debug, PartialEq)]
+enum Kill {
+    Stop,
+    /// Health checks failed; the exit counts as a crash.
+    Unresponsive,
+    /// No clients for a while; the listener stays up to start it again.
+    Idle,
+}
+
 pub struct SocketProxy {
     name: String,
     socket_path: PathBuf,
@@ -30,11 +80,25 @@ pub struct SocketProxy {
     status: Arc<Mutex<ServerStatus>>,
     owned: bool,
     request_tx: Arc<Mutex<Option<mpsc::Sender<String>>>>,
-    kill_tx: Arc<Mutex<Option<oneshot::Sender<()>>>>,
+    kill_tx: Arc<Mutex<Option<oneshot::Sender<Kill>>>>,
     listener: Mutex<Option<Arc<LocalListener>>>,
     clients: Arc<Mutex<HashMap<String, ClientSender>>>,
-    request_map: Arc<Mutex<HashMap<String, String>>>,
-    shutdown: Arc<AtomicBool>,
+    routing: Arc<Mutex<Routing>>,
+    /// Shutdown flag of the current run. Each start gets a fresh one so tasks
+    /// left over from a previous run never pick up the new run's state.
+    shutdown: Mutex<Arc<AtomicBool>>,
+    /// Set when the child exits or fails to start without being asked to stop.
+    crashed: Arc<AtomicBool>,
+    last_error: Arc<Mutex<Option<String>>>,
+    /// Recent stderr, kept across restarts.
+    logs: Arc<Mutex<LogBuffer>>,
+    health: Arc<Mutex<Health>>,
+    recorder: Option<Arc<TrafficRecorder>>,
+    /// Swapped in place when the config changes, so running servers and
+    /// connected clients pick up new policies.
+    policies: Arc<RwLock<Arc<ToolPolicies>>>,
+    /// Set for servers whose child is spawned by the first client to connect.
+    lazy: Option<Weak<SocketProxy>>,
     started_at: Mutex<Option<Instant>>,
     total_connections: Arc<AtomicU32>,
     exit_complete_tx: Arc<Mutex<Option<oneshot::Sender<()>>>>,
@@ -62,8 +126,15 @@ impl SocketProxy {
             kill_tx: Arc::new(Mutex::new(None)),
             listener: Mutex::new(None),
             clients: Arc::new(Mutex::new(HashMap::new())),
-            request_map: Arc::new(Mutex::new(HashMap::new())),
-            shutdown: Arc::new(AtomicBool::new(false)),
+            routing: Arc::new(Mutex::new(Routing::default())),
+            shutdown: Mutex::new(Arc::new(AtomicBool::new(false))),
+            crashed: Arc::new(AtomicBool::new(false)),
+            last_error: Arc::new(Mutex::new(None)),
+            logs: Arc::new(Mutex::new(LogBuffer::new(LOG_BUFFER_LINES))),
+            health: Arc::new(Mutex::new(Health::default())),
+            recorder: None,
+            policies: Arc::new(RwLock::new(Arc::new(ToolPolicies::default()))),
+            lazy: None,
             started_at: Mutex::new(None),
             total_connections: Arc::new(AtomicU32::new(0)),
             exit_complete_tx: Arc::new(Mutex::new(None)),
@@ -71,6 +142,52 @@ impl SocketProxy {
         }
     }
 
+    /// Report the requests clients send, and their answers, to `recorder`.
+    pub fn with_recorder(mut self, recorder: Arc<TrafficRecorder>) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    /// Hide and block the tools `policies` does not allow.
+    pub fn with_policies(self, policies: ToolPolicies) -> Self {
+        *self.policies.write().unwrap() = Arc::new(policies);
+        self
+    }
+
+    /// Replace the tool policies of a running proxy. Connected clients are
+    /// told to fetch the tool list again, since it is filtered by policy.
+    pub fn set_policies(&self, policies: ToolPolicies) {
+        *self.policies.write().unwrap() = Arc::new(policies);
+        let changed = json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/tools/list_changed"
+        })
+        .to_string();
+        for sender in self.clients.lock().unwrap().values() {
+            let _ = sender.try_send(changed.clone());
+        }
+    }
+
+    /// Listen without spawning the server; the first client to connect
+    /// starts it. The accept loop needs to reach the proxy, hence the `Arc`.
+    pub fn into_lazy(mut self) -> Arc<Self> {
+        Arc::new_cyclic(|this| {
+            self.lazy = Some(this.clone());
+            self
+        })
+    }
+
+    fn shared(&self) -> Shared {
+        Shared {
+            name: self.name.clone(),
+            request_tx: self.request_tx.clone(),
+            routing: self.routing.clone(),
+            clients: self.clients.clone(),
+            recorder: self.recorder.clone(),
+            policies: self.policies.clone(),
+        }
+    }
+
     pub fn status(&self) -> ServerStatus {
         *self.status.lock().unwrap()
     }
@@ -91,6 +208,101 @@ impl SocketProxy {
         self.total_connections.load(Ordering::SeqCst)
     }
 
+    /// Clients connected right now.
+    pub fn client_count(&self) -> usize {
+        self.clients.lock().unwrap().len()
+    }
+
+    pub fn is_lazy(&self) -> bool {
+        self.lazy.is_some()
+    }
+
+    /// Whether the server went down on its own and has not been restarted yet.
+    pub fn crashed(&self) -> bool {
+        self.crashed.load(Ordering::SeqCst)
+    }
+
+    pub fn last_error(&self) -> Option<String> {
+        self.last_error.lock().unwrap().clone()
+    }
+
+    /// Give up on the server: mark it `Failed` with `reason`, how it last
+    /// exited and the last lines it wrote to stderr.
+    pub fn mark_failed(&self, reason: &str) {
+        let tail: Vec<String> = self
+            .logs(STDERR_TAIL_LINES)
+            .into_iter()
+            .map(|line| line.line)
+            .collect();
+        let mut last_error = self.last_error.lock().unwrap();
+        let mut message = match last_error.take() {
+            Some(exit) => format!("{} (last exit: {})", reason, exit),
+            None => reason.to_string(),
+        };
+        if !tail.is_empty() {
+            message = format!("{}\n{}", message, tail.join("\n"));
+        }
+        *last_error = Some(message);
+        *self.status.lock().unwrap() = ServerStatus::Failed;
+    }
+
+    /// The last `tail` lines the server wrote to stderr.
+    pub fn logs(&self, tail: usize) -> Vec<McpServerLogLine> {
+        self.logs.lock().unwrap().tail(tail)
+    }
+
+    pub fn health(&self) -> HealthStatus {
+        self.health.lock().unwrap().status()
+    }
+
+    pub fn last_ping_ms(&self) -> Option<u64> {
+        self.health
+            .lock()
+            .unwrap()
+            .last_latency()
+            .map(|latency| latency.as_millis() as u64)
+    }
+
+    /// Send the server a `ping` when one is due and return its health.
+    /// Only running servers the pool owns are checked.
+    pub fn check_health(&self, policy: &HealthCheckPolicy) -> HealthStatus {
+        if !self.owned || self.status() != ServerStatus::Running {
+            return self.health();
+        }
+        let now = std::time::Instant::now();
+        let oldest_request = self.routing.lock().unwrap().oldest_request();
+        let mut health = self.health.lock().unwrap();
+        let previous = health.status();
+        if health.tick(now, policy, oldest_request) {
+            let sender = self.request_tx.lock().unwrap().clone();
+            let (proxy_id, ping) = self.routing.lock().unwrap().health_ping();
+            match sender.map(|sender| sender.try_send(ping.to_string())) {
+                Some(Ok(())) => health.sent(proxy_id, now),
+                _ => health.failed(policy),
+            }
+        }
+        let status = health.status();
+        if status != previous {
+            diagnostics::log(format!(
+                "pool_server_health name={} health={:?}",
+                self.name, status
+            ));
+        }
+        status
+    }
+
+    /// Kill a server that stopped answering pings. The supervisor then
+    /// restarts it.
+    pub fn kill_unresponsive(&self) {
+        if let Some(kill_tx) = self.kill_tx.lock().unwrap().take() {
+            let _ = kill_tx.send(Kill::Unresponsive);
+        }
+    }
+
+    fn shutdown_flag(&self) -> Arc<AtomicBool> {
+        self.shutdown.lock().unwrap().clone()
+    }
+
     /// Takes the exit completion receiver, allowing the caller to await process exit.
     /// Returns None if start() hasn't been called or receiver was already taken.
     pub fn take_exit_receiver(&self) -> Option<oneshot::Receiver<()>> {
@@ -98,16 +310,108 @@ impl SocketProxy {
     }
 
     pub fn start(&self) -> io::Result<()> {
-        if self.status() == ServerStatus::Running {
+        if matches!(self.status(), ServerStatus::Running | ServerStatus::Idle) {
             return Ok(());
         }
         if !self.owned {
             *self.status.lock().unwrap() = ServerStatus::Running;
             return Ok(());
         }
+        if self.lazy.is_some() {
+            return self.listen().inspect_err(|err| self.failed_to_start(err));
+        }
 
         *self.status.lock().unwrap() = ServerStatus::Starting;
+        self.run()
+    }
 
+    /// Spawn the child of a lazy server waiting for its first client.
+    /// Fails when the server is neither idle nor up, so the client is
+    /// turned away and retries once the supervisor has brought it back.
+    fn wake(&self) -> io::Result<()> {
+        {
+            let mut status = self.status.lock().unwrap();
+            match *status {
+                ServerStatus::Idle => *status = ServerStatus::Starting,
+                ServerStatus::Running | ServerStatus::Starting => return Ok(()),
+                ServerStatus::Stopped | ServerStatus::Failed => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::NotConnected,
+                        "MCP server is not running",
+                    ))
+                }
+            }
+        }
+        diagnostics::log(format!("pool_server_waking name={}", self.name));
+        self.run()
+    }
+
+    /// Spawn a fresh child process.
+    fn run(&self) -> io::Result<()> {
+        // A new process needs its own handshake.
+        *self.routing.lock().unwrap() = Routing::default();
+        *self.shutdown.lock().unwrap() = Arc::new(AtomicBool::new(false));
+        *self.last_error.lock().unwrap() = None;
+        *self.health.lock().unwrap() = Health::default();
+        if let Some(recorder) = &self.recorder {
+            recorder.server_restarted(&self.name);
+        }
+        self.crashed.store(false, Ordering::SeqCst);
+
+        self.launch().inspect_err(|err| self.failed_to_start(err))
+    }
+
+    fn failed_to_start(&self, err: &io::Error) {
+        if let Some(kill_tx) = self.kill_tx.lock().unwrap().take() {
+            let _ = kill_tx.send(Kill::Stop);
+        }
+        // Counts as a crash so the supervisor tries again.
+        *self.last_error.lock().unwrap() = Some(format!("failed to start: {}", err));
+        *self.status.lock().unwrap() = ServerStatus::Failed;
+        self.crashed.store(true, Ordering::SeqCst);
+    }
+
+    /// Bind the socket of a lazy server and wait for clients.
+    fn listen(&self) -> io::Result<()> {
+        let listener = Arc::new(transport::bind(&self.socket_path)?);
+        *self.listener.lock().unwrap() = Some(listener.clone());
+        self.spawn_accept_loop(listener);
+        *self.status.lock().unwrap() = ServerStatus::Idle;
+        diagnostics::log(format!(
+            "pool_proxy_listening name={} socket={}",
+            self.name,
+            self.socket_path.display()
+        ));
+        Ok(())
+    }
+
+    /// Stop the child of a lazy server no client is using. The listener
+    /// stays up, and the next client to connect starts the server again.
+    pub fn stop_idle(&self) {
+        if self.lazy.is_none() {
+            return;
+        }
+        // The accept loop wakes the server and adds its client under the
+        // same lock, so a client either counts here or finds the proxy
+        // already idle and starts a fresh child.
+        let clients = self.clients.lock().unwrap();
+        {
+            let mut status = self.status.lock().unwrap();
+            if *status != ServerStatus::Running || !clients.is_empty() {
+                return;
+            }
+            *status = ServerStatus::Idle;
+        }
+        if let Some(kill_tx) = self.kill_tx.lock().unwrap().take() {
+            let _ = kill_tx.send(Kill::Idle);
+        }
+        *self.request_tx.lock().unwrap() = None;
+        *self.started_at.lock().unwrap() = None;
+        *self.health.lock().unwrap() = Health::default();
+        drop(clients);
+    }
+
+    fn launch(&self) -> io::Result<()> {
         diagnostics::log(format!(
             "pool_proxy_starting name={} command={} args={:?}",
             self.name, self.command, self.args
@@ -155,7 +459,7 @@ impl SocketProxy {
         *self.exit_complete_rx.lock().unwrap() = Some(exit_rx);
 
         if let Some(stderr) = stderr {
-            self.spawn_stderr_logger(stderr)?;
+            self.spawn_stderr_logger(stderr);
         }
 
         if let Some(stdout) = stdout {
@@ -167,22 +471,51 @@ impl SocketProxy {
         }
 
         let status = self.status.clone();
-        let shutdown = self.shutdown.clone();
+        let shutdown = self.shutdown_flag();
+        let crashed = self.crashed.clone();
+        let last_error = self.last_error.clone();
+        let clients = self.clients.clone();
+        let routing = self.routing.clone();
         let name = self.name.clone();
         let exit_complete_tx = self.exit_complete_tx.clone();
 
         tauri::async_runtime::spawn(async move {
-            let exit = tokio::select! {
-                res = child.wait() => res,
+            let (exit, kill) = tokio::select! {
+                res = child.wait() => (res, None),
                 signal = kill_rx => {
-                    if signal.is_ok() {
+                    let kill = signal.ok();
+                    if kill.is_some() {
                         let _ = child.start_kill();
                     }
-                    child.wait().await
+                    (child.wait().await, kill)
                 }
             };
+            let requested = matches!(kill, Some(Kill::Stop | Kill::Idle));
 
-            *status.lock().unwrap() = ServerStatus::Stopped;
+            if !requested {
+                // Answer in-flight requests before the clients are disconnected,
+                // so agents see an error instead of waiting forever.
+                let failed = routing.lock().unwrap().fail_pending("MCP server exited");
+                for (client_id, message) in failed {
+                    let sender = clients.lock().unwrap().get(&client_id).cloned();
+                    if let Some(sender) = sender {
+                        let _ = sender.try_send(message.to_string());
+                    }
+                }
+                *last_error.lock().unwrap() = Some(match &exit {
+                    _ if kill == Some(Kill::Unresponsive) => {
+                        "killed after failing health checks".to_string()
+                    }
+                    Ok(exit) => format!("exited with {}", exit),
+                    Err(err) => err.to_string(),
+                });
+                crashed.store(true, Ordering::SeqCst);
+            }
+            // `stop_idle` already marked an idled server; by now a client
+            // may have started the next run.
+            if kill != Some(Kill::Idle) {
+                *status.lock().unwrap() = ServerStatus::Stopped;
+            }
             shutdown.store(true, Ordering::SeqCst);
 
             // Signal exit completion to any waiting callers
@@ -198,10 +531,12 @@ impl SocketProxy {
             }
         });
 
-        let listener = Arc::new(transport::bind(&self.socket_path)?);
-        *self.listener.lock().unwrap() = Some(listener.clone());
-
-        self.spawn_accept_loop(listener);
+        // A lazy server's listener outlives its child.
+        if self.lazy.is_none() {
+            let listener = Arc::new(transport::bind(&self.socket_path)?);
+            *self.listener.lock().unwrap() = Some(listener.clone());
+            self.spawn_accept_loop(listener);
+        }
 
         *self.status.lock().unwrap() = ServerStatus::Running;
         *self.started_at.lock().unwrap() = Some(Instant::now());
@@ -214,14 +549,16 @@ impl SocketProxy {
     }
 
     pub fn stop(&self) -> io::Result<()> {
-        self.shutdown.store(true, Ordering::SeqCst);
+        self.shutdown_flag().store(true, Ordering::SeqCst);
+        self.crashed.store(false, Ordering::SeqCst);
         if let Some(listener) = self.listener.lock().unwrap().take() {
             drop(listener);
         }
 
+        let mut signalled = false;
         if self.owned {
             if let Some(kill_tx) = self.kill_tx.lock().unwrap().take() {
-                let _ = kill_tx.send(());
+                signalled = kill_tx.send(Kill::Stop).is_ok();
             }
             #[cfg(unix)]
             {
@@ -231,29 +568,34 @@ impl SocketProxy {
 
         *self.request_tx.lock().unwrap() = None;
         self.clients.lock().unwrap().clear();
-        self.request_map.lock().unwrap().clear();
+        *self.routing.lock().unwrap() = Routing::default();
         *self.started_at.lock().unwrap() = None;
-        // For non-owned servers, set status here since there's no background task.
-        // For owned servers, the background task sets Stopped after process exits.
-        if !self.owned {
+        *self.health.lock().unwrap() = Health::default();
+        // Without a running child (external or idle servers) there is no
+        // background task to set Stopped after the process exits.
+        if !signalled {
             *self.status.lock().unwrap() = ServerStatus::Stopped;
         }
         Ok(())
     }
 
-    fn spawn_stderr_logger(&self, stderr: ChildStderr) -> io::Result<()> {
-        let log_dir = diagnostics::log_dir().unwrap_or_else(|| PathBuf::from("."));
-        let pool_dir = log_dir.join("mcppool");
-        create_dir_all(&pool_dir)?;
-        let log_path = pool_dir.join(format!("{}_socket.log", self.name));
-        diagnostics::log(format!(
-            "pool_proxy_stderr_log name={} path={}",
-            self.name,
-            log_path.display()
-        ));
-        let file = File::create(log_path)?;
+    fn spawn_stderr_logger(&self, stderr: ChildStderr) {
+        let log_path = logs::log_path(&self.name);
+        let mut file = match RotatingFile::open(log_path.clone()) {
+            Ok(file) => Some(file),
+            Err(err) => {
+                diagnostics::log(format!(
+                    "pool_proxy_stderr_log_failed name={} path={} error={}",
+                    self.name,
+                    log_path.display(),
+                    err
+                ));
+                None
+            }
+        };
+        let name = self.name.clone();
+        let recent = self.logs.clone();
         tauri::async_runtime::spawn(async move {
-            let mut file = tokio::fs::File::from_std(file);
             let mut reader = BufReader::new(stderr);
             let mut buffer = String::new();
             loop {
@@ -261,46 +603,82 @@ impl SocketProxy {
                 match reader.read_line(&mut buffer).await {
                     Ok(0) => break,
                     Ok(_) => {
-                        if file.write_all(buffer.as_bytes()).await.is_err() {
-                            break;
+                        let line = McpServerLogLine::new(&name, buffer.trim_end());
+                        if let Some(writer) = file.as_mut() {
+                            if writer.write_line(&line.line).is_err() {
+                                file = None;
+                            }
                         }
+                        recent.lock().unwrap().push(line.clone());
+                        logs::publish(line);
                     }
                     Err(_) => break,
                 }
             }
         });
-        Ok(())
     }
 
     fn spawn_accept_loop(&self, listener: Arc<LocalListener>) {
-        let clients = self.clients.clone();
-        let request_map = self.request_map.clone();
-        let request_tx = self.request_tx.clone();
-        let shutdown = self.shutdown.clone();
+        let shared = self.shared();
+        let shutdown = self.shutdown_flag();
+        let lazy = self.lazy.clone();
         let name = self.name.clone();
         let total_connections = self.total_connections.clone();
 
         tauri::async_runtime::spawn(async move {
             let mut counter = 0;
             loop {
-                if shutdown.load(Ordering::SeqCst) {
+                let listening = match &lazy {
+                    // A lazy listener lasts until the proxy drops or replaces it.
+                    Some(this) => this.upgrade().is_some_and(|proxy| {
+                        proxy
+                            .listener
+                            .lock()
+                            .unwrap()
+                            .as_ref()
+                            .is_some_and(|current| Arc::ptr_eq(current, &listener))
+                    }),
+                    None => !shutdown.load(Ordering::SeqCst),
+                };
+                if !listening {
                     break;
                 }
                 match listener.accept().await {
                     Ok(stream) => {
                         let client_id = format!("{}-client-{}", name, counter);
+                        let (tx, rx) = mpsc::channel::<String>(128);
+                        let joined = {
+                            // Held until the client is added, so `stop_idle`
+                            // cannot stop the run it joins.
+                            let mut clients = shared.clients.lock().unwrap();
+                            // Each run of a lazy server has its own shutdown flag.
+                            let shutdown = match lazy.as_ref().and_then(Weak::upgrade) {
+                                Some(proxy) => proxy.wake().map(|()| proxy.shutdown_flag()),
+                                None => Ok(shutdown.clone()),
+                            };
+                            if shutdown.is_ok() {
+                                clients.insert(client_id.clone(), tx);
+                            }
+                            shutdown
+                        };
+                        let shutdown = match joined {
+                            Ok(shutdown) => shutdown,
+                            Err(err) => {
+                                diagnostics::log(format!(
+                                    "pool_wake_failed name={} error={}",
+                                    name, err
+                                ));
+                                continue;
+                            }
+                        };
                         counter += 1;
                         total_connections.fetch_add(1, Ordering::SeqCst);
-                        let (tx, rx) = mpsc::channel::<String>(128);
-                        clients.lock().unwrap().insert(client_id.clone(), tx);
                         diagnostics::log(format!(
                             "pool_client_connected name={} client_id={}",
                             name, client_id
                         ));
 
-                        let clients_for_drop = clients.clone();
-                        let request_map_for_drop = request_map.clone();
-                        let request_tx_for_client = request_tx.clone();
+                        let shared_for_client = shared.clone();
                         let shutdown_for_client = shutdown.clone();
                         let client_id_clone = client_id.clone();
 
@@ -308,9 +686,7 @@ impl SocketProxy {
                             handle_client(
                                 stream,
                                 client_id_clone,
-                                request_tx_for_client,
-                                request_map_for_drop,
-                                clients_for_drop,
+                                shared_for_client,
                                 shutdown_for_client,
                                 rx,
                             )
@@ -330,9 +706,9 @@ impl SocketProxy {
     }
 
     fn spawn_stdout_router(&self, stdout: ChildStdout) {
-        let clients = self.clients.clone();
-        let request_map = self.request_map.clone();
-        let shutdown = self.shutdown.clone();
+        let shared = self.shared();
+        let health = self.health.clone();
+        let shutdown = self.shutdown_flag();
         let name = self.name.clone();
         tauri::async_runtime::spawn(async move {
             let mut reader = BufReader::new(stdout);
@@ -349,7 +725,7 @@ impl SocketProxy {
                         if line.is_empty() {
                             continue;
                         }
-                        route_response(&line, &clients, &request_map).await;
+                        route_response(&line, &shared, &health).await;
                     }
                     Err(err) => {
                         diagnostics::log(format!(
@@ -365,7 +741,7 @@ impl SocketProxy {
     }
 
     fn spawn_stdin_writer(&self, stdin: ChildStdin, mut rx: mpsc::Receiver<String>) {
-        let shutdown = self.shutdown.clone();
+        let shutdown = self.shutdown_flag();
         let name = self.name.clone();
         tauri::async_runtime::spawn(async move {
             let mut stdin = stdin;
@@ -405,16 +781,25 @@ impl SocketProxy {
 async fn handle_client(
     stream: LocalStream,
     client_id: String,
-    request_tx: Arc<Mutex<Option<mpsc::Sender<String>>>>,
-    request_map: Arc<Mutex<HashMap<String, String>>>,
-    clients: Arc<Mutex<HashMap<String, ClientSender>>>,
+    shared: Shared,
     shutdown: Arc<AtomicBool>,
     mut rx: mpsc::Receiver<String>,
 ) {
+    let Shared {
+        name,
+        request_tx,
+        routing,
+        clients,
+        recorder,
+        policies,
+    } = shared;
     diagnostics::log(format!(
         "pool_handle_client_started client_id={}",
         client_id
     ));
+    if let Some(info) = peer_session(&stream).await {
+        routing.lock().unwrap().identify_client(&client_id, info);
+    }
 
     let (read_half, mut write_half) = tokio::io::split(stream);
     let mut reader = BufReader::new(read_half);
@@ -423,6 +808,13 @@ async fn handle_client(
 
     loop {
         if shutdown.load(Ordering::SeqCst) {
+            // Deliver what is already queued, such as errors for requests
+            // the exited server will never answer.
+            while let Ok(message) = rx.try_recv() {
+                if write_line(&mut write_half, message).await.is_err() {
+                    break;
+                }
+            }
             break;
         }
         tokio::select! {
@@ -441,26 +833,84 @@ async fn handle_client(
                         if line.is_empty() {
                             continue;
                         }
-                        if let Ok(value) = serde_json::from_str::<Value>(&line) {
-                            if let Some(id) = value.get("id").and_then(id_key) {
-                                request_map.lock().unwrap().insert(id, client_id.clone());
+                        let lines = match serde_json::from_str::<Value>(&line) {
+                            Ok(value) => {
+                                // Each message of a batch is checked and routed on its own.
+                                let messages = match value {
+                                    Value::Array(messages) => messages,
+                                    message => vec![message],
+                                };
+                                let policies = policies.read().unwrap().clone();
+                                let mut lines = Vec::new();
+                                let mut replies = Vec::new();
+                                for message in messages {
+                                    match admit(&name, &client_id, &routing, &policies, recorder.as_deref(), message) {
+                                        Admitted::Forward(line) => lines.push(line),
+                                        Admitted::Reply(reply) => replies.push(reply),
+                                        Admitted::Drop => {}
+                                    }
+                                }
+                                let mut write_failed = false;
+                                for reply in replies {
+                                    if let Err(err) = write_line(&mut write_half, reply.to_string()).await {
+                                        diagnostics::log(format!(
+                                            "pool_client_write_failed client_id={} error={}",
+                                            client_id, err
+                                        ));
+                                        write_failed = true;
+                                        break;
+                                    }
+                                }
+                                if write_failed {
+                                    break;
+                                }
+                                lines
                             }
-                        } else if parse_failures < 3 {
-                            parse_failures += 1;
-                            diagnostics::log(format!(
-                                "pool_request_parse_failed client_id={} bytes={}",
-                                client_id,
-                                line.len()
-                            ));
-                        }
+                            Err(err) => {
+                                if parse_failures < 3 {
+                                    parse_failures += 1;
+                                    diagnostics::log(format!(
+                                        "pool_request_parse_failed client_id={} bytes={}",
+                                        client_id,
+                                        line.len()
+                                    ));
+                                }
+                                // Never forwarded: the server might read it differently and
+                                // run a call the policy, recorder and id routing never saw.
+                                let reply = json!({
+                                    "jsonrpc": "2.0",
+                                    "id": null,
+                                    "error": { "code": -32700, "message": format!("Parse error: {}", err) }
+                                });
+                                if let Err(err) = write_line(&mut write_half, reply.to_string()).await {
+                                    diagnostics::log(format!(
+                                        "pool_client_write_failed client_id={} error={}",
+                                        client_id, err
+                                    ));
+                                    break;
+                                }
+                                continue;
+                            }
+                        };
                         let sender = request_tx.lock().unwrap().clone();
-                        if let Some(sender) = sender {
+                        let Some(sender) = sender else {
+                            if !lines.is_empty() {
+                                diagnostics::log(format!(
+                                    "pool_stdin_missing client_id={}",
+                                    client_id
+                                ));
+                            }
+                            continue;
+                        };
+                        let mut send_failed = false;
+                        for line in lines {
                             if sender.send(line.clone()).await.is_err() {
                                 diagnostics::log(format!(
                                     "pool_request_write_failed client_id={} error={}",
                                     client_id,
                                     "stdin channel closed"
                                 ));
+                                send_failed = true;
                                 break;
                             }
                             diagnostics::log(format!(
@@ -468,11 +918,9 @@ async fn handle_client(
                                 client_id,
                                 line.len()
                             ));
-                        } else {
-                            diagnostics::log(format!(
-                                "pool_stdin_missing client_id={}",
-                                client_id
-                            ));
+                        }
+                        if send_failed {
+                            break;
                         }
                     }
                     Err(err) => {
@@ -487,22 +935,13 @@ async fn handle_client(
             message = rx.recv() => {
                 match message {
                     Some(message) => {
-                        let mut bytes = message.into_bytes();
-                        bytes.push(b'\n');
-                        if let Err(err) = write_half.write_all(&bytes).await {
+                        if let Err(err) = write_line(&mut write_half, message).await {
                             diagnostics::log(format!(
                                 "pool_client_write_failed client_id={} error={}",
                                 client_id, err
                             ));
                             break;
                         }
-                        if let Err(err) = write_half.flush().await {
-                            diagnostics::log(format!(
-                                "pool_client_flush_failed client_id={} error={}",
-                                client_id, err
-                            ));
-                            break;
-                        }
                     }
                     None => break,
                 }
@@ -512,46 +951,180 @@ async fn handle_client(
     }
 
     clients.lock().unwrap().remove(&client_id);
+    if let Some(recorder) = &recorder {
+        recorder.client_disconnected(&name, &client_id);
+    }
+    let owed = routing.lock().unwrap().client_disconnected(&client_id);
+    let sender = request_tx.lock().unwrap().clone();
+    if let Some(sender) = sender {
+        for message in owed {
+            let _ = sender.send(message.to_string()).await;
+        }
+    }
 }
 
-async fn route_response(
-    line: &str,
-    clients: &Arc<Mutex<HashMap<String, ClientSender>>>,
-    request_map: &Arc<Mutex<HashMap<String, String>>>,
-) {
-    let mut target = None;
-    let parsed = serde_json::from_str::<Value>(line);
-    if let Ok(value) = parsed {
-        if let Some(id) = value.get("id").and_then(id_key) {
-            target = request_map.lock().unwrap().remove(&id);
+/// What becomes of one message a client sent.
+enum Admitted {
+    Forward(String),
+    Reply(Value),
+    Drop,
+}
+
+/// Check one client message against the tool policy and route it.
+fn admit(
+    name: &str,
+    client_id: &str,
+    routing: &Mutex<Routing>,
+    policies: &ToolPolicies,
+    recorder: Option<&TrafficRecorder>,
+    mut value: Value,
+) -> Admitted {
+    let mut routing = routing.lock().unwrap();
+    let project = routing.project(client_id).map(str::to_string);
+    let session = routing.session(client_id).map(str::to_string);
+    if let Some(blocked) = policies.check_call(project.as_deref(), &value) {
+        drop(routing);
+        // Writing the audit log is file I/O; keep it outside the routing lock.
+        policies.audit_blocked(
+            name,
+            session.as_deref(),
+            project.as_deref(),
+            value.pointer("/params/name").and_then(Value::as_str).unwrap_or_default(),
+        );
+        return Admitted::Reply(blocked);
+    }
+    let action = routing.client_message(client_id, &mut value);
+    drop(routing);
+    match action {
+        ClientAction::Forward => {
+            let line = value.to_string();
+            if let (Some(recorder), Some(proxy_id)) = (recorder, value.get("id").and_then(Value::as_u64)) {
+                recorder.request(name, client_id, session.as_deref(), proxy_id, &value, line.len());
+            }
+            Admitted::Forward(line)
         }
-    } else {
-        diagnostics::log(format!(
-            "pool_response_parse_failed bytes={}",
-            line.len()
-        ));
+        ClientAction::Reply(reply) => Admitted::Reply(reply),
+        ClientAction::Drop => Admitted::Drop,
     }
+}
 
-    if let Some(client_id) = target {
-        let sender = clients.lock().unwrap().get(&client_id).cloned();
-        if let Some(sender) = sender {
-            if sender.send(line.to_string()).await.is_ok() {
-                diagnostics::log(format!(
-                    "pool_response_routed client_id={} bytes={}",
-                    client_id,
-                    line.len()
-                ));
-            } else {
-                diagnostics::log(format!(
-                    "pool_response_send_failed client_id={}",
-                    client_id
-                ));
-            }
-        } else {
+async fn write_line(
+    write_half: &mut tokio::io::WriteHalf<LocalStream>,
+    line: String,
+) -> io::Result<()> {
+    let mut bytes = line.into_bytes();
+    bytes.push(b'\n');
+    write_half.write_all(&bytes).await?;
+    write_half.flush().await
+}
+
+async fn route_response(line: &str, shared: &Shared, health: &Arc<Mutex<Health>>) {
+    let Shared {
+        name,
+        request_tx,
+        routing,
+        clients,
+        recorder,
+        policies,
+    } = shared;
+    let mut value = match serde_json::from_str::<Value>(line) {
+        Ok(value) => value,
+        Err(_) => {
+            diagnostics::log(format!(
+                "pool_response_parse_failed bytes={}",
+                line.len()
+            ));
             broadcast_to_all(line, clients).await;
+            return;
+        }
+    };
+
+    if value.get("method").is_none() {
+        let ping = routing.lock().unwrap().health_response(&value);
+        if let Some(proxy_id) = ping {
+            health.lock().unwrap().answered(proxy_id, std::time::Instant::now());
+            return;
+        }
+        if routing.lock().unwrap().proxy_response(&value) {
+            return;
+        }
+        if let (Some(recorder), Some(proxy_id)) = (recorder, value.get("id").and_then(Value::as_u64)) {
+            recorder.response(name, proxy_id, &value, line.len());
         }
+        let deliveries = {
+            let mut routing = routing.lock().unwrap();
+            let lists_tools = routing.pending_method(&value) == Some("tools/list");
+            let mut deliveries = routing.server_response(&mut value);
+            if lists_tools {
+                let policies = policies.read().unwrap().clone();
+                for (client_id, message) in &mut deliveries {
+                    policies.filter_tools_list(routing.project(client_id), message);
+                }
+            }
+            deliveries
+        };
+        if deliveries.is_empty() {
+            diagnostics::log(format!(
+                "pool_response_unmatched id={} bytes={}",
+                value["id"],
+                line.len()
+            ));
+        }
+        for (client_id, message) in deliveries {
+            send_to_client(clients, &client_id, message.to_string()).await;
+        }
+        return;
+    }
+
+    let delivery = routing.lock().unwrap().server_message(&mut value);
+    match delivery {
+        Delivery::Clients(client_ids) => {
+            let message = value.to_string();
+            for client_id in client_ids {
+                send_to_client(clients, &client_id, message.clone()).await;
+            }
+        }
+        Delivery::Broadcast => broadcast_to_all(line, clients).await,
+        Delivery::Drop => {
+            diagnostics::log(format!(
+                "pool_message_dropped method={} bytes={}",
+                value["method"],
+                line.len()
+            ));
+        }
+        Delivery::ReplyToServer(reply) => {
+            let sender = request_tx.lock().unwrap().clone();
+            if let Some(sender) = sender {
+                let _ = sender.send(reply.to_string()).await;
+            }
+        }
+    }
+}
+
+async fn send_to_client(
+    clients: &Arc<Mutex<HashMap<String, ClientSender>>>,
+    client_id: &str,
+    line: String,
+) {
+    let sender = clients.lock().unwrap().get(client_id).cloned();
+    let Some(sender) = sender else {
+        diagnostics::log(format!(
+            "pool_response_client_gone client_id={}",
+            client_id
+        ));
+        return;
+    };
+    let bytes = line.len();
+    if sender.send(line).await.is_ok() {
+        diagnostics::log(format!(
+            "pool_response_routed client_id={} bytes={}",
+            client_id, bytes
+        ));
     } else {
-        broadcast_to_all(line, clients).await;
+        diagnostics::log(format!(
+            "pool_response_send_failed client_id={}",
+            client_id
+        ));
     }
 }
 
@@ -567,12 +1140,349 @@ async fn broadcast_to_all(line: &str, clients: &Arc<Mutex<HashMap<String, Client
     ));
 }
 
-fn id_key(value: &Value) -> Option<String> {
-    match value {
-        Value::String(value) => Some(format!("s:{}", value)),
-        Value::Number(value) => Some(format!("n:{}", value)),
-        Value::Bool(value) => Some(format!("b:{}", value)),
-        Value::Null => None,
-        _ => Some(value.to_string()),
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Stdio server that answers each request with its id and params, and
+    /// answers `initialize` with how many it has seen. Notifications get no reply.
+    #[cfg(unix)]
+    const ECHO_SERVER: &str = r#"n=0
+while IFS= read -r line; do
+  case "$line" in
+    *'"method":"initialize"'*) n=$((n+1)); printf '%s\n' "$line" | sed "s/\"method\":\"[^\"]*\"/\"result\":{\"initializeCount\":$n}/" ;;
+    *'"id":'*) printf '%s\n' "$line" | sed 's/"method":"[^"]*"/"result":"ok"/' ;;
+  esac
+done"#;
+
+    #[cfg(unix)]
+    fn echo_proxy(temp: &tempfile::TempDir, name: &str) -> SocketProxy {
+        SocketProxy::new(
+            name.to_string(),
+            temp.path().join("echo.sock"),
+            "sh".to_string(),
+            vec!["-c".to_string(), ECHO_SERVER.to_string()],
+            HashMap::new(),
+            true,
+        )
+    }
+
+    #[cfg(unix)]
+    fn start_echo_proxy(temp: &tempfile::TempDir, name: &str) -> SocketProxy {
+        let proxy = echo_proxy(temp, name);
+        proxy.start().unwrap();
+        proxy
+    }
+
+    #[cfg(unix)]
+    async fn read_json<R: tokio::io::AsyncBufRead + Unpin>(reader: &mut R) -> Value {
+        let mut line = String::new();
+        tokio::time::timeout(Duration::from_secs(10), reader.read_line(&mut line))
+            .await
+            .unwrap()
+            .unwrap();
+        serde_json::from_str(&line).unwrap()
+    }
+
+    #[cfg(unix)]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_concurrent_clients_with_same_ids_get_their_own_responses() {
+        use tokio::net::UnixStream;
+
+        let temp = tempfile::tempdir().unwrap();
+        let proxy = start_echo_proxy(&temp, "id-rewrite-test");
+
+        let mut handles = Vec::new();
+        for client in 0..4 {
+            let socket_path = proxy.socket_path();
+            handles.push(tokio::spawn(async move {
+                let stream = UnixStream::connect(socket_path).await.unwrap();
+                let (read_half, mut write_half) = tokio::io::split(stream);
+                for id in 1..=5 {
+                    let request = json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "method": "tools/call",
+                        "params": { "name": "read", "client": client }
+                    });
+                    write_half
+                        .write_all(format!("{}\n", request).as_bytes())
+                        .await
+                        .unwrap();
+                }
+                let mut reader = BufReader::new(read_half);
+                let mut ids = Vec::new();
+                for _ in 1..=5 {
+                    let response = read_json(&mut reader).await;
+                    assert_eq!(response["params"]["client"], json!(client));
+                    ids.push(response["id"].as_u64().unwrap());
+                }
+                ids.sort();
+                assert_eq!(ids, vec![1, 2, 3, 4, 5]);
+            }));
+        }
+        for handle in handles {
+            handle.await.unwrap();
+        }
+        proxy.stop().unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_unparsable_lines_never_reach_the_server_with_their_own_id() {
+        use tokio::net::UnixStream;
+
+        let temp = tempfile::tempdir().unwrap();
+        let proxy = start_echo_proxy(&temp, "parse-error-test");
+
+        let stream = UnixStream::connect(proxy.socket_path()).await.unwrap();
+        let (read_half, mut write_half) = tokio::io::split(stream);
+        // The echo server would answer the raw id if the line got through.
+        let unparsable = r#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"read","x":"\ud800"}}"#;
+        let request = json!({ "jsonrpc": "2.0", "id": 2, "method": "tools/call", "params": { "name": "read" } });
+        write_half
+            .write_all(format!("{}\n{}\n", unparsable, request).as_bytes())
+            .await
+            .unwrap();
+        let mut reader = BufReader::new(read_half);
+        let error = read_json(&mut reader).await;
+        assert_eq!(error["error"]["code"], json!(-32700));
+        let response = read_json(&mut reader).await;
+        assert_eq!(response["id"], json!(2));
+        assert_eq!(response["result"], json!("ok"));
+        proxy.stop().unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_server_sees_one_initialize_for_all_clients() {
+        use tokio::net::UnixStream;
+
+        let temp = tempfile::tempdir().unwrap();
+        let proxy = start_echo_proxy(&temp, "handshake-test");
+
+        let mut handles = Vec::new();
+        for client in 0..3 {
+            let socket_path = proxy.socket_path();
+            handles.push(tokio::spawn(async move {
+                let stream = UnixStream::connect(socket_path).await.unwrap();
+                let (read_half, mut write_half) = tokio::io::split(stream);
+                let initialize = json!({
+                    "jsonrpc": "2.0",
+                    "id": 0,
+                    "method": "initialize",
+                    "params": { "capabilities": {}, "clientInfo": { "name": format!("client-{}", client) } }
+                });
+                let initialized = json!({ "jsonrpc": "2.0", "method": "notifications/initialized" });
+                write_half
+                    .write_all(format!("{}\n{}\n", initialize, initialized).as_bytes())
+                    .await
+                    .unwrap();
+                let mut reader = BufReader::new(read_half);
+                let response = read_json(&mut reader).await;
+                assert_eq!(response["id"], json!(0));
+                assert_eq!(response["result"]["initializeCount"], json!(1));
+            }));
+        }
+        for handle in handles {
+            handle.await.unwrap();
+        }
+        proxy.stop().unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_health_check_pings_the_server() {
+        let temp = tempfile::tempdir().unwrap();
+        let proxy = start_echo_proxy(&temp, "health-test");
+        let policy = HealthCheckPolicy {
+            interval: Duration::from_millis(20),
+            ..HealthCheckPolicy::default()
+        };
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while proxy.check_health(&policy) != HealthStatus::Healthy && Instant::now() < deadline {
+            sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(proxy.health(), HealthStatus::Healthy);
+        assert!(proxy.last_ping_ms().is_some());
+
+        proxy.stop().unwrap();
+        assert_eq!(proxy.health(), HealthStatus::Unknown);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_recorder_sees_session_requests_and_responses() {
+        use super::super::recorder::{RecorderSettings, TrafficQuery};
+        use tokio::net::UnixStream;
+
+        let temp = tempfile::tempdir().unwrap();
+        let recorder = Arc::new(TrafficRecorder::new(RecorderSettings {
+            enabled: true,
+            ..RecorderSettings::default()
+        }));
+        let proxy = echo_proxy(&temp, "recorder-test").with_recorder(recorder.clone());
+        proxy.start().unwrap();
+
+        let stream = UnixStream::connect(proxy.socket_path()).await.unwrap();
+        let (read_half, mut write_half) = tokio::io::split(stream);
+        let hello = agentterm_shared::pool::ClientInfo {
+            session_id: Some("session-9".to_string()),
+            project_path: None,
+        }
+        .to_message();
+        let call = json!({ "jsonrpc": "2.0", "id": 5, "method": "tools/call", "params": { "name": "read" } });
+        write_half
+            .write_all(format!("{}\n{}\n", hello, call).as_bytes())
+            .await
+            .unwrap();
+        let mut reader = BufReader::new(read_half);
+        let response = read_json(&mut reader).await;
+        assert_eq!(response["id"], json!(5));
+
+        let records = recorder.query(&TrafficQuery {
+            session_id: Some("session-9".to_string()),
+            ..TrafficQuery::default()
+        });
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].server, "recorder-test");
+        assert_eq!(records[0].method, "tools/call");
+        assert_eq!(records[0].result, Some(json!("ok")));
+        assert!(records[0].duration_ms.is_some());
+        proxy.stop().unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_project_policy_blocks_calls_before_the_server() {
+        use crate::mcp::config::ToolPolicy;
+        use tokio::net::UnixStream;
+
+        let temp = tempfile::tempdir().unwrap();
+        let projects = HashMap::from([(
+            "/work/prod".to_string(),
+            ToolPolicy {
+                allow: Vec::new(),
+                deny: vec!["delete_*".to_string()],
+            },
+        )]);
+        let mut policies = ToolPolicies::new(ToolPolicy::default(), &projects);
+        policies.audit_path = temp.path().join("policy-audit.jsonl");
+        let proxy = echo_proxy(&temp, "policy-test").with_policies(policies);
+        proxy.start().unwrap();
+
+        let stream = UnixStream::connect(proxy.socket_path()).await.unwrap();
+        let (read_half, mut write_half) = tokio::io::split(stream);
+        let hello = agentterm_shared::pool::ClientInfo {
+            session_id: Some("session-3".to_string()),
+            project_path: Some("/work/prod/api".to_string()),
+        }
+        .to_message();
+        let blocked = json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/call", "params": { "name": "delete_row" } });
+        let allowed = json!({ "jsonrpc": "2.0", "id": 2, "method": "tools/call", "params": { "name": "read" } });
+        write_half
+            .write_all(format!("{}\n{}\n{}\n", hello, blocked, allowed).as_bytes())
+            .await
+            .unwrap();
+        let mut reader = BufReader::new(read_half);
+        let first = read_json(&mut reader).await;
+        assert_eq!(first["id"], json!(1));
+        assert_eq!(first["error"]["data"]["tool"], json!("delete_row"));
+        let second = read_json(&mut reader).await;
+        assert_eq!(second["id"], json!(2));
+        assert_eq!(second["result"], json!("ok"));
+
+        let audit = std::fs::read_to_string(temp.path().join("policy-audit.jsonl")).unwrap();
+        assert!(audit.contains("\"sessionId\":\"session-3\""));
+
+        // A batch cannot smuggle a blocked call past the policy.
+        let blocked = json!({ "jsonrpc": "2.0", "id": 3, "method": "tools/call", "params": { "name": "delete_row" } });
+        let allowed = json!({ "jsonrpc": "2.0", "id": 4, "method": "tools/call", "params": { "name": "read" } });
+        write_half
+            .write_all(format!("{}\n", json!([blocked, allowed])).as_bytes())
+            .await
+            .unwrap();
+        let first = read_json(&mut reader).await;
+        assert_eq!(first["id"], json!(3));
+        assert_eq!(first["error"]["data"]["tool"], json!("delete_row"));
+        let second = read_json(&mut reader).await;
+        assert_eq!(second["id"], json!(4));
+        assert_eq!(second["result"], json!("ok"));
+
+        // Neither can a line only the server would parse.
+        let unparsable = r#"{"jsonrpc":"2.0","id":6,"method":"tools/call","params":{"name":"delete_row","x":"\ud800"}}"#;
+        write_half
+            .write_all(format!("{}\n", unparsable).as_bytes())
+            .await
+            .unwrap();
+        let error = read_json(&mut reader).await;
+        assert_eq!(error["id"], Value::Null);
+        assert_eq!(error["error"]["code"], json!(-32700));
+
+        // New policies reach the running proxy.
+        proxy.set_policies(ToolPolicies::new(ToolPolicy::default(), &HashMap::new()));
+        let changed = read_json(&mut reader).await;
+        assert_eq!(changed["method"], json!("notifications/tools/list_changed"));
+        let unblocked = json!({ "jsonrpc": "2.0", "id": 5, "method": "tools/call", "params": { "name": "delete_row" } });
+        write_half
+            .write_all(format!("{}\n", unblocked).as_bytes())
+            .await
+            .unwrap();
+        assert_eq!(read_json(&mut reader).await["result"], json!("ok"));
+        proxy.stop().unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_lazy_server_starts_on_connect_and_stops_when_idle() {
+        use tokio::net::UnixStream;
+
+        async fn wait_for(proxy: &SocketProxy, status: ServerStatus) {
+            let deadline = Instant::now() + Duration::from_secs(10);
+            while proxy.status() != status {
+                assert!(Instant::now() < deadline, "never became {:?}", status);
+                sleep(Duration::from_millis(20)).await;
+            }
+        }
+
+        async fn call(proxy: &SocketProxy) {
+            let stream = UnixStream::connect(proxy.socket_path()).await.unwrap();
+            let (read_half, mut write_half) = tokio::io::split(stream);
+            let request = json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/call", "params": { "name": "read" } });
+            write_half
+                .write_all(format!("{}\n", request).as_bytes())
+                .await
+                .unwrap();
+            let response = read_json(&mut BufReader::new(read_half)).await;
+            assert_eq!(response["result"], json!("ok"));
+        }
+
+        let temp = tempfile::tempdir().unwrap();
+        let proxy = echo_proxy(&temp, "lazy-test").into_lazy();
+        proxy.start().unwrap();
+        assert_eq!(proxy.status(), ServerStatus::Idle);
+        assert_eq!(proxy.uptime_seconds(), None);
+
+        call(&proxy).await;
+        assert_eq!(proxy.status(), ServerStatus::Running);
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while proxy.client_count() > 0 {
+            assert!(Instant::now() < deadline, "client never went away");
+            sleep(Duration::from_millis(20)).await;
+        }
+
+        proxy.stop_idle();
+        // Marked before the child is gone, so a client arriving now starts a new run.
+        assert_eq!(proxy.status(), ServerStatus::Idle);
+        sleep(Duration::from_millis(100)).await;
+        assert_eq!(proxy.status(), ServerStatus::Idle);
+        assert!(!proxy.crashed());
+
+        call(&proxy).await;
+        assert_eq!(proxy.status(), ServerStatus::Running);
+        proxy.stop().unwrap();
+        wait_for(&proxy, ServerStatus::Stopped).await;
     }
 }
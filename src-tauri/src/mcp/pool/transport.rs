@@ -20,6 +20,18 @@ pub type LocalStream = UnixStream;
 #[cfg(windows)]
 pub type LocalStream = NamedPipeServer;
 
+/// Process id of the connected client, where the platform reports it.
+#[cfg(unix)]
+pub fn peer_pid(stream: &LocalStream) -> Option<u32> {
+    let pid = stream.peer_cred().ok()?.pid()?;
+    u32::try_from(pid).ok()
+}
+
+#[cfg(windows)]
+pub fn peer_pid(_stream: &LocalStream) -> Option<u32> {
+    None
+}
+
 pub fn bind(path: &PathBuf) -> io::Result<LocalListener> {
     #[cfg(unix)]
     {
@@ -7,11 +7,23 @@ pub enum ServerStatus {
     Starting,
     Running,
     Failed,
+    /// Listening with no process; the next client to connect starts it.
+    Idle,
 }
 
 impl ServerStatus {
 }
 
+/// Outcome of the proxy's own `ping` checks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HealthStatus {
+    /// Not checked yet, or not checked at all (external servers).
+    #[default]
+    Unknown,
+    Healthy,
+    Unhealthy,
+}
+
 /// Detailed status for a single MCP server in the pool
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -22,6 +34,13 @@ pub struct McpServerStatus {
     pub uptime_seconds: Option<u64>,
     pub connection_count: u32,
     pub owned: bool,
+    /// Automatic restarts after crashes.
+    pub restart_count: u32,
+    /// Why the server last went down, with its recent stderr once it has failed.
+    pub last_error: Option<String>,
+    pub health: HealthStatus,
+    /// Round trip of the last answered health-check ping.
+    pub last_ping_ms: Option<u64>,
 }
 
 /// Response for pool status command
@@ -1,16 +1,23 @@
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, OnceLock};
 use std::time::Duration;
 
 use parking_lot::Mutex;
+use tauri::{AppHandle, Manager};
 
 use crate::diagnostics;
+use crate::session::SessionManager;
 
 use super::config::{MCPDef, UserConfig};
 use super::error::{McpError, McpResult};
+use super::pool::logs::McpServerLogLine;
+use super::pool::recorder::{TrafficQuery, TrafficRecord};
 use super::pool::types::PoolStatusResponse;
-use super::pool::{socket_alive, socket_path_for, Pool, PoolConfig};
+use super::pool::{
+    set_peer_resolver, socket_alive, socket_path_for, spawn_supervisor, HealthCheckPolicy, Pool,
+    PoolConfig, RecorderSettings, RestartPolicy, ToolPolicies,
+};
 
 static GLOBAL_POOL: OnceLock<Mutex<Option<Arc<Pool>>>> = OnceLock::new();
 
@@ -33,7 +40,27 @@ pub fn initialize_global_pool(config: &UserConfig) -> McpResult<Option<Arc<Pool>
         pool_all: config.mcp_pool.pool_all,
         exclude_mcps: config.mcp_pool.exclude_mcps.clone(),
         pool_mcps: config.mcp_pool.pool_mcps.clone(),
+        restart: RestartPolicy {
+            enabled: config.mcp_pool.auto_restart,
+            max_crashes: config.mcp_pool.max_restarts,
+            ..RestartPolicy::default()
+        },
+        health: HealthCheckPolicy {
+            enabled: config.mcp_pool.health_checks,
+            restart_unhealthy: config.mcp_pool.restart_unhealthy,
+            ..HealthCheckPolicy::default()
+        },
+        recorder: RecorderSettings {
+            enabled: config.mcp_pool.record_traffic,
+            max_records: config.mcp_pool.record_max_entries,
+            redact_fields: config.mcp_pool.record_redact.clone(),
+            ..RecorderSettings::default()
+        },
+        lazy_start: config.mcp_pool.start_on_demand,
+        idle_timeout: (config.mcp_pool.idle_timeout_minutes > 0)
+            .then(|| Duration::from_secs(config.mcp_pool.idle_timeout_minutes * 60)),
     }));
+    spawn_supervisor(&pool);
 
     let discovered = pool.discover_existing_sockets();
     if discovered > 0 {
@@ -51,6 +78,14 @@ pub fn initialize_global_pool(config: &UserConfig) -> McpResult<Option<Arc<Pool>
     Ok(Some(pool))
 }
 
+/// Let pooled servers tell which session a client runs in from its process,
+/// so per-project tool policies don't rest on the project a client claims.
+pub fn install_peer_resolver(app: AppHandle) {
+    set_peer_resolver(Box::new(move |pid| {
+        app.state::<SessionManager>().client_info_for_pid(pid)
+    }));
+}
+
 pub fn ensure_global_pool(config: &UserConfig) -> McpResult<Option<Arc<Pool>>> {
     initialize_global_pool(config)
 }
@@ -81,6 +116,7 @@ pub fn start_pool_mcp(pool: &Pool, name: &str, def: &MCPDef) -> McpResult<()> {
         return Ok(());
     }
     if pool.is_running(name) {
+        pool.set_policies(name, tool_policies(def));
         return Ok(());
     }
     if !def.url.is_empty() {
@@ -97,10 +133,24 @@ pub fn start_pool_mcp(pool: &Pool, name: &str, def: &MCPDef) -> McpResult<()> {
         def.command.clone(),
         def.args.clone(),
         def.env.clone(),
+        tool_policies(def),
     )
     .map_err(|err| McpError::IoError(err.to_string()))
 }
 
+fn tool_policies(def: &MCPDef) -> ToolPolicies {
+    ToolPolicies::new(def.policy.clone(), &def.project_policies)
+}
+
+/// Push the tool policies in `config` to every pooled server already running.
+pub fn update_pool_policies(config: &UserConfig) {
+    if let Some(pool) = get_global_pool() {
+        for (name, def) in &config.mcps {
+            pool.set_policies(name, tool_policies(def));
+        }
+    }
+}
+
 fn start_pool_mcps(pool: &Pool, mcps: &HashMap<String, MCPDef>) -> McpResult<()> {
     for (name, def) in mcps {
         if def.command.is_empty() && def.url.is_empty() {
@@ -140,6 +190,36 @@ pub fn get_pool_status() -> PoolStatusResponse {
     }
 }
 
+/// Recent stderr of a pooled MCP server
+pub fn pool_server_logs(name: &str, tail: usize) -> McpResult<Vec<McpServerLogLine>> {
+    get_global_pool()
+        .and_then(|pool| pool.logs(name, tail))
+        .ok_or_else(|| McpError::MCPNotFound(name.to_string()))
+}
+
+/// Traffic recorded through the pool; empty when the pool is off
+pub fn pool_traffic(query: &TrafficQuery) -> Vec<TrafficRecord> {
+    get_global_pool()
+        .map(|pool| pool.traffic(query))
+        .unwrap_or_default()
+}
+
+/// Write recorded pool traffic to `path` as JSON lines
+pub fn export_pool_traffic(query: &TrafficQuery, path: &Path) -> McpResult<usize> {
+    let records = match get_global_pool() {
+        Some(pool) => pool.export_traffic(query, path),
+        None => return Err(McpError::InvalidInput("MCP pool is not enabled".to_string())),
+    };
+    records.map_err(|e| McpError::IoError(e.to_string()))
+}
+
+/// Drop all recorded pool traffic
+pub fn clear_pool_traffic() {
+    if let Some(pool) = get_global_pool() {
+        pool.clear_traffic();
+    }
+}
+
 /// Restart a specific MCP server in the pool
 pub async fn restart_pool_server(name: &str) -> McpResult<bool> {
     match get_global_pool() {
@@ -9,10 +9,11 @@ use crate::diagnostics;
 use super::error::{McpError, McpResult};
 
 const PROXY_COMMAND_ENV: &str = "AGENTTERM_MCP_PROXY_CMD";
+const PROXY_BINARY: &str = "agentterm-mcp-proxy";
 
 /// Version of the bundled proxy binary (from agentterm-mcp-proxy crate).
 /// This should match the version in crates/agentterm-mcp-proxy/Cargo.toml.
-const BUNDLED_PROXY_VERSION: &str = "0.1.0";
+const BUNDLED_PROXY_VERSION: &str = "0.2.0";
 
 /// Query the version of an installed proxy binary.
 /// Returns None if binary doesn't exist or version can't be determined.
@@ -32,13 +33,13 @@ fn get_binary_version(path: &Path) -> Option<String> {
 }
 
 /// Check if installed version differs from bundled version.
-fn needs_update(installed_path: &Path) -> bool {
+fn needs_update(installed_path: &Path, bundled_version: &str) -> bool {
     let installed_version = match get_binary_version(installed_path) {
         Some(v) => v,
         None => return true, // Can't determine version, assume update needed
     };
 
-    installed_version != BUNDLED_PROXY_VERSION
+    installed_version != bundled_version
 }
 
 pub fn proxy_command() -> String {
@@ -53,17 +54,43 @@ pub fn proxy_bin_dir() -> McpResult<PathBuf> {
 }
 
 pub fn proxy_install_path() -> McpResult<PathBuf> {
-    let mut path = proxy_bin_dir()?;
-    if cfg!(windows) {
-        path.push("agentterm-mcp-proxy.exe");
-    } else {
-        path.push("agentterm-mcp-proxy");
-    }
-    Ok(path)
+    binary_install_path(PROXY_BINARY)
+}
+
+/// Where a bundled helper binary is installed, next to the proxy.
+pub(crate) fn binary_install_path(base: &str) -> McpResult<PathBuf> {
+    Ok(proxy_bin_dir()?.join(binary_file_name(base)))
 }
 
 pub fn ensure_proxy_installed(app: &AppHandle) -> McpResult<()> {
-    let install_path = proxy_install_path()?;
+    let install_path = install_bundled_binary(app, PROXY_BINARY, BUNDLED_PROXY_VERSION)?;
+
+    let command = if install_path.exists() {
+        PROXY_BINARY.to_string()
+    } else if let Some(fallback) = resolve_bundled_binary(app, PROXY_BINARY) {
+        fallback.display().to_string()
+    } else if proxy_in_path() {
+        PROXY_BINARY.to_string()
+    } else {
+        return Err(McpError::ConfigNotFound(
+            "proxy binary not found".to_string(),
+        ));
+    };
+
+    ensure_path_contains(&proxy_bin_dir()?)?;
+    env::set_var(PROXY_COMMAND_ENV, &command);
+    diagnostics::log(format!("proxy_command_set command={}", command));
+    Ok(())
+}
+
+/// Copy a bundled binary into `proxy_bin_dir()` when missing or out of date.
+/// Returns the install path, which may not exist if no bundled copy was found.
+pub(crate) fn install_bundled_binary(
+    app: &AppHandle,
+    base: &str,
+    bundled_version: &str,
+) -> McpResult<PathBuf> {
+    let install_path = binary_install_path(base)?;
     if let Some(parent) = install_path.parent() {
         fs::create_dir_all(parent)
             .map_err(|e| McpError::ConfigWriteError(format!("create_dir_all: {}", e)))?;
@@ -71,17 +98,18 @@ pub fn ensure_proxy_installed(app: &AppHandle) -> McpResult<()> {
 
     // Check if we need to install or update the proxy binary
     let should_install = if install_path.exists() {
-        needs_update(&install_path)
+        needs_update(&install_path, bundled_version)
     } else {
         true
     };
 
     if should_install {
-        if let Some(source) = resolve_proxy_source(app) {
+        if let Some(source) = resolve_bundled_binary(app, base) {
             let old_version = get_binary_version(&install_path);
             if let Err(err) = fs::copy(&source, &install_path) {
                 diagnostics::log(format!(
-                    "proxy_install_failed source={} dest={} error={}",
+                    "proxy_install_failed binary={} source={} dest={} error={}",
+                    base,
                     source.display(),
                     install_path.display(),
                     err
@@ -94,58 +122,45 @@ pub fn ensure_proxy_installed(app: &AppHandle) -> McpResult<()> {
                 }
                 match old_version {
                     Some(old) => diagnostics::log(format!(
-                        "proxy_updated old={} new={} dest={}",
+                        "proxy_updated binary={} old={} new={} dest={}",
+                        base,
                         old,
-                        BUNDLED_PROXY_VERSION,
+                        bundled_version,
                         install_path.display()
                     )),
                     None => diagnostics::log(format!(
-                        "proxy_installed version={} dest={}",
-                        BUNDLED_PROXY_VERSION,
+                        "proxy_installed binary={} version={} dest={}",
+                        base,
+                        bundled_version,
                         install_path.display()
                     )),
                 }
             }
         } else {
-            diagnostics::log("proxy_source_not_found");
+            diagnostics::log(format!("proxy_source_not_found binary={}", base));
         }
     }
-
-    let command = if install_path.exists() {
-        "agentterm-mcp-proxy".to_string()
-    } else if let Some(fallback) = resolve_proxy_source(app) {
-        fallback.display().to_string()
-    } else if proxy_in_path() {
-        "agentterm-mcp-proxy".to_string()
-    } else {
-        return Err(McpError::ConfigNotFound(
-            "proxy binary not found".to_string(),
-        ));
-    };
-
-    ensure_path_contains(&proxy_bin_dir()?)?;
-    env::set_var(PROXY_COMMAND_ENV, &command);
-    diagnostics::log(format!("proxy_command_set command={}", command));
-    Ok(())
+    Ok(install_path)
 }
 
-fn resolve_proxy_source(app: &AppHandle) -> Option<PathBuf> {
+/// Locate a sidecar binary shipped with the app.
+pub(crate) fn resolve_bundled_binary(app: &AppHandle, base: &str) -> Option<PathBuf> {
     let resource_dir = app.path().resource_dir().ok();
     if let Some(dir) = resource_dir.as_ref() {
-        if let Some(path) = find_proxy_in_dir(dir) {
+        if let Some(path) = find_binary_in_dir(dir, base) {
             return Some(path);
         }
-        if let Some(path) = find_proxy_in_dir(&dir.join("bin")) {
+        if let Some(path) = find_binary_in_dir(&dir.join("bin"), base) {
             return Some(path);
         }
     }
 
     let exe_dir = app.path().executable_dir().ok();
     if let Some(dir) = exe_dir.as_ref() {
-        if let Some(path) = find_proxy_in_dir(dir) {
+        if let Some(path) = find_binary_in_dir(dir, base) {
             return Some(path);
         }
-        if let Some(path) = find_proxy_in_dir(&dir.join("bin")) {
+        if let Some(path) = find_binary_in_dir(&dir.join("bin"), base) {
             return Some(path);
         }
     }
@@ -153,9 +168,9 @@ fn resolve_proxy_source(app: &AppHandle) -> Option<PathBuf> {
     None
 }
 
-fn find_proxy_in_dir(dir: &Path) -> Option<PathBuf> {
+fn find_binary_in_dir(dir: &Path, base: &str) -> Option<PathBuf> {
     let entries = fs::read_dir(dir).ok()?;
-    let exact = proxy_file_name();
+    let exact = binary_file_name(base);
     let mut fallback = None;
     for entry in entries.flatten() {
         let path = entry.path();
@@ -166,18 +181,18 @@ fn find_proxy_in_dir(dir: &Path) -> Option<PathBuf> {
         if name == exact {
             return Some(path);
         }
-        if name.starts_with("agentterm-mcp-proxy") {
+        if name.starts_with(base) {
             fallback = Some(path);
         }
     }
     fallback
 }
 
-fn proxy_file_name() -> String {
+fn binary_file_name(base: &str) -> String {
     if cfg!(windows) {
-        "agentterm-mcp-proxy.exe".to_string()
+        format!("{}.exe", base)
     } else {
-        "agentterm-mcp-proxy".to_string()
+        base.to_string()
     }
 }
 
@@ -208,7 +223,7 @@ fn ensure_path_contains(dir: &Path) -> McpResult<()> {
 fn proxy_in_path() -> bool {
     let separator = if cfg!(windows) { ';' } else { ':' };
     let current = env::var("PATH").unwrap_or_default();
-    let file_name = proxy_file_name();
+    let file_name = binary_file_name(PROXY_BINARY);
     for entry in current.split(separator) {
         if entry.is_empty() {
             continue;
@@ -0,0 +1,372 @@
+//! Task graph files: the steps of a workflow, their dependencies and prompts.
+//!
+//! Graphs are TOML (or JSON for `.json` files) and use the same snake_case keys
+//! as config.toml:
+//!
+//! ```toml
+//! name = "plan-implement-review"
+//! project_path = "~/code/app"
+//!
+//! [[steps]]
+//! id = "plan"
+//! tool = "claude"
+//! prompt = "Write an implementation plan for: {{input}}. Save it to PLAN.md."
+//! output_file = "PLAN.md"
+//!
+//! [[steps]]
+//! id = "backend"
+//! tool = "claude"
+//! depends_on = ["plan"]
+//! worktree = true
+//! prompt = "Implement the backend part of this plan:\n{{steps.plan.output}}"
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Lines of session output captured as a step's output when it has no `output_file`.
+const DEFAULT_OUTPUT_LINES: usize = 60;
+
+/// A workflow of agent sessions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TaskGraph {
+    pub name: String,
+    /// Working directory for steps that don't set their own.
+    #[serde(default)]
+    pub project_path: Option<String>,
+    /// Section the step sessions are created in.
+    #[serde(default)]
+    pub section_id: Option<String>,
+    pub steps: Vec<StepDef>,
+}
+
+/// One session in a task graph.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StepDef {
+    pub id: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    /// shell, claude, gemini, codex, opencode or a custom command name.
+    #[serde(default = "default_step_tool")]
+    pub tool: String,
+    #[serde(default)]
+    pub command: Option<String>,
+    #[serde(default)]
+    pub project_path: Option<String>,
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// Typed into the session once its tool is ready. Supports `{{...}}` placeholders.
+    /// Without one, the step completes as soon as its tool is idle at the prompt.
+    #[serde(default)]
+    pub prompt: Option<String>,
+    /// Run the step in its own git worktree on a new branch.
+    #[serde(default)]
+    pub worktree: bool,
+    /// File, relative to the step's working directory, read as the step's output.
+    #[serde(default)]
+    pub output_file: Option<String>,
+    /// Lines of session output used when `output_file` is not set.
+    #[serde(default = "default_output_lines")]
+    pub output_lines: usize,
+    #[serde(default)]
+    pub timeout_minutes: Option<u32>,
+}
+
+fn default_step_tool() -> String {
+    "claude".to_string()
+}
+
+fn default_output_lines() -> usize {
+    DEFAULT_OUTPUT_LINES
+}
+
+impl TaskGraph {
+    /// Read and validate a graph file. Relative project paths are resolved
+    /// against the file's directory.
+    pub fn load(path: &Path) -> Result<TaskGraph, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read task graph {}: {}", path.display(), e))?;
+        let mut graph = TaskGraph::parse(&content, path)?;
+        let base = path.parent().unwrap_or_else(|| Path::new("."));
+        graph.project_path = graph.project_path.map(|p| resolve_path(base, &p));
+        for step in &mut graph.steps {
+            step.project_path = step.project_path.take().map(|p| resolve_path(base, &p));
+        }
+        Ok(graph)
+    }
+
+    pub fn parse(content: &str, path: &Path) -> Result<TaskGraph, String> {
+        let graph: TaskGraph = if path.extension().is_some_and(|ext| ext == "json") {
+            serde_json::from_str(content).map_err(|e| format!("Invalid task graph: {}", e))?
+        } else {
+            toml::from_str(content).map_err(|e| format!("Invalid task graph: {}", e))?
+        };
+        graph.validate()?;
+        Ok(graph)
+    }
+
+    pub fn step(&self, id: &str) -> Option<&StepDef> {
+        self.steps.iter().find(|step| step.id == id)
+    }
+
+    /// Check ids, dependencies and placeholders, and reject cycles.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.steps.is_empty() {
+            return Err("Task graph has no steps".to_string());
+        }
+        let mut ids = HashSet::new();
+        for step in &self.steps {
+            let valid_id = !step.id.is_empty()
+                && step
+                    .id
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+            if !valid_id {
+                return Err(format!(
+                    "Invalid step id '{}': use letters, digits, '-' and '_'",
+                    step.id
+                ));
+            }
+            if !ids.insert(step.id.as_str()) {
+                return Err(format!("Duplicate step id '{}'", step.id));
+            }
+        }
+        for step in &self.steps {
+            for dep in &step.depends_on {
+                if !ids.contains(dep.as_str()) {
+                    return Err(format!("Step '{}' depends on unknown step '{}'", step.id, dep));
+                }
+            }
+            if step.worktree && self.step_project_path(step).is_none() {
+                return Err(format!("Step '{}' uses a worktree but has no project_path", step.id));
+            }
+        }
+        self.order()?;
+
+        for step in &self.steps {
+            let Some(prompt) = &step.prompt else {
+                continue;
+            };
+            let ancestors = self.ancestors(&step.id);
+            for key in placeholders(prompt)? {
+                match key.split('.').collect::<Vec<_>>().as_slice() {
+                    ["input"] | ["run", "id"] => {}
+                    ["steps", id, "output" | "worktree" | "branch" | "session_id"] => {
+                        if !ancestors.contains(*id) {
+                            return Err(format!(
+                                "Step '{}' uses {{{{{}}}}} but does not depend on '{}'",
+                                step.id, key, id
+                            ));
+                        }
+                    }
+                    _ => {
+                        return Err(format!("Step '{}' uses unknown placeholder {{{{{}}}}}", step.id, key));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Step ids in dependency order.
+    pub fn order(&self) -> Result<Vec<String>, String> {
+        let mut remaining: HashMap<&str, usize> = self
+            .steps
+            .iter()
+            .map(|step| (step.id.as_str(), step.depends_on.len()))
+            .collect();
+        let mut order = Vec::new();
+        let mut ready: Vec<&str> = self
+            .steps
+            .iter()
+            .filter(|step| step.depends_on.is_empty())
+            .map(|step| step.id.as_str())
+            .collect();
+        while let Some(id) = ready.pop() {
+            order.push(id.to_string());
+            for step in &self.steps {
+                if step.depends_on.iter().any(|dep| dep == id) {
+                    let count = remaining.get_mut(step.id.as_str()).expect("known step");
+                    *count -= 1;
+                    if *count == 0 {
+                        ready.push(&step.id);
+                    }
+                }
+            }
+        }
+        if order.len() != self.steps.len() {
+            let cyclic: Vec<&str> = self
+                .steps
+                .iter()
+                .map(|step| step.id.as_str())
+                .filter(|id| !order.iter().any(|done| done == id))
+                .collect();
+            return Err(format!("Task graph has a dependency cycle: {}", cyclic.join(", ")));
+        }
+        Ok(order)
+    }
+
+    /// Every step `id` depends on, directly or transitively.
+    fn ancestors(&self, id: &str) -> HashSet<String> {
+        let mut found = HashSet::new();
+        let mut stack = vec![id.to_string()];
+        while let Some(current) = stack.pop() {
+            if let Some(step) = self.step(&current) {
+                for dep in &step.depends_on {
+                    if found.insert(dep.clone()) {
+                        stack.push(dep.clone());
+                    }
+                }
+            }
+        }
+        found
+    }
+
+    pub fn step_project_path<'a>(&'a self, step: &'a StepDef) -> Option<&'a str> {
+        step.project_path
+            .as_deref()
+            .or(self.project_path.as_deref())
+            .filter(|path| !path.is_empty())
+    }
+}
+
+/// Expand `~/` and make relative paths absolute against `base`.
+fn resolve_path(base: &Path, path: &str) -> String {
+    let expanded = match path.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir()
+            .map(|home| home.join(rest))
+            .unwrap_or_else(|| PathBuf::from(path)),
+        None => PathBuf::from(path),
+    };
+    if expanded.is_absolute() {
+        expanded.display().to_string()
+    } else {
+        base.join(expanded).display().to_string()
+    }
+}
+
+/// Keys of the `{{key}}` placeholders in `template`.
+fn placeholders(template: &str) -> Result<Vec<String>, String> {
+    let mut keys = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let end = after
+            .find("}}")
+            .ok_or_else(|| "Unclosed {{ in prompt".to_string())?;
+        keys.push(after[..end].trim().to_string());
+        rest = &after[end + 2..];
+    }
+    Ok(keys)
+}
+
+/// Replace `{{key}}` placeholders with values from `values`.
+///
+/// Example:
+/// ```rust,ignore
+/// let values = HashMap::from([("input".to_string(), "dark mode".to_string())]);
+/// assert_eq!(render("Add {{ input }}", &values)?, "Add dark mode");
+/// ```
+pub fn render(template: &str, values: &HashMap<String, String>) -> Result<String, String> {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after
+            .find("}}")
+            .ok_or_else(|| "Unclosed {{ in prompt".to_string())?;
+        let key = after[..end].trim();
+        let value = values
+            .get(key)
+            .ok_or_else(|| format!("No value for {{{{{}}}}}", key))?;
+        out.push_str(value);
+        rest = &after[end + 2..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GRAPH: &str = r#"
+name = "review"
+project_path = "/tmp/app"
+
+[[steps]]
+id = "plan"
+prompt = "Plan {{input}}"
+
+[[steps]]
+id = "impl"
+depends_on = ["plan"]
+worktree = true
+prompt = "Do {{ steps.plan.output }}"
+
+[[steps]]
+id = "review"
+depends_on = ["impl"]
+prompt = "Review {{steps.impl.branch}} against {{steps.plan.output}}"
+"#;
+
+    fn parse(content: &str) -> Result<TaskGraph, String> {
+        TaskGraph::parse(content, Path::new("graph.toml"))
+    }
+
+    #[test]
+    fn test_parse_orders_steps_by_dependency() {
+        let graph = parse(GRAPH).unwrap();
+        assert_eq!(graph.order().unwrap(), vec!["plan", "impl", "review"]);
+        assert_eq!(graph.steps[0].tool, "claude");
+        assert_eq!(graph.steps[0].output_lines, DEFAULT_OUTPUT_LINES);
+    }
+
+    #[test]
+    fn test_validate_rejects_cycles_and_unknown_dependencies() {
+        let cycle = r#"
+name = "cycle"
+[[steps]]
+id = "a"
+depends_on = ["b"]
+[[steps]]
+id = "b"
+depends_on = ["a"]
+"#;
+        assert!(parse(cycle).unwrap_err().contains("cycle"));
+
+        let unknown = "name = \"x\"\n[[steps]]\nid = \"a\"\ndepends_on = [\"missing\"]\n";
+        assert!(parse(unknown).unwrap_err().contains("unknown step"));
+    }
+
+    #[test]
+    fn test_validate_requires_dependency_for_step_placeholders() {
+        let graph = r#"
+name = "x"
+[[steps]]
+id = "a"
+[[steps]]
+id = "b"
+prompt = "{{steps.a.output}}"
+"#;
+        assert!(parse(graph).unwrap_err().contains("does not depend on 'a'"));
+    }
+
+    #[test]
+    fn test_render_replaces_placeholders() {
+        let values = HashMap::from([
+            ("input".to_string(), "dark mode".to_string()),
+            ("steps.plan.output".to_string(), "1. toggle".to_string()),
+        ]);
+        assert_eq!(
+            render("Add {{ input }}: {{steps.plan.output}}", &values).unwrap(),
+            "Add dark mode: 1. toggle"
+        );
+        assert!(render("{{missing}}", &values).is_err());
+    }
+}
@@ -0,0 +1,737 @@
+//! Multi-agent task orchestration.
+//!
+//! A task graph file describes a workflow as steps, each one agent session, and
+//! the steps they depend on. A run starts a step's session through
+//! [`SessionManager`] once its dependencies complete, fills earlier steps'
+//! outputs into its prompt, and marks it complete once the agent has worked on
+//! the prompt and is back at its prompt (a step without a prompt, once it first
+//! reaches it), or when the process exits. Runs are persisted under
+//! `~/.agent-term/orchestrator` and picked up again after a restart.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use tauri::{AppHandle, Emitter, Manager, State};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::diagnostics;
+use crate::mcp::McpManager;
+use crate::session::{default_tool_command, NewSessionInput, SessionManager, SessionStatus, SessionTool};
+
+mod graph;
+mod run;
+mod worktree;
+
+pub use graph::TaskGraph;
+pub use run::{RunRecord, RunStatus, StepState, StepStatus};
+
+use graph::StepDef;
+use run::RunStore;
+
+/// How often running steps are checked and ready steps launched.
+const TICK_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Consecutive ticks a step's agent must sit at its prompt after finishing a turn
+/// of work before the step counts as done, so a short pause between turns is not
+/// mistaken for completion.
+const IDLE_TICKS_TO_COMPLETE: u32 = 2;
+
+/// Step outputs are cut to this many characters before being passed on.
+const MAX_OUTPUT_CHARS: usize = 20_000;
+
+/// Runtime-only tracking for a running step.
+struct StepProgress {
+    /// Turns of work the session had finished when the step launched, or last
+    /// seen while its prompt was still queued or being worked on. Completion
+    /// needs a turn after that.
+    turns_before: u64,
+    idle_ticks: u32,
+}
+
+enum StepCheck {
+    Busy,
+    Done,
+    Exited(u32),
+    Failed(String),
+}
+
+/// Owns orchestrator runs and advances them.
+///
+/// Example:
+/// ```rust,ignore
+/// let orchestrator = build_orchestrator_manager()?;
+/// let run = orchestrator.start_run(&app, "/work/review.toml", Some("add dark mode".to_string()))?;
+/// ```
+pub struct OrchestratorManager {
+    root: PathBuf,
+    store: RunStore,
+    runs: Mutex<Vec<RunRecord>>,
+    progress: Mutex<HashMap<(String, String), StepProgress>>,
+    /// Runs being advanced right now; the tick and a command can race for one.
+    advancing: Mutex<HashSet<String>>,
+}
+
+pub fn build_orchestrator_manager() -> Result<OrchestratorManager, String> {
+    let root = dirs::home_dir()
+        .ok_or_else(|| "Home directory not found".to_string())?
+        .join(".agent-term")
+        .join("orchestrator");
+    Ok(OrchestratorManager::new(root))
+}
+
+/// Start the orchestrator thread. Runs for the lifetime of the app.
+pub fn spawn_orchestrator(app: AppHandle) {
+    thread::spawn(move || loop {
+        thread::sleep(TICK_INTERVAL);
+        let orchestrator = app.state::<OrchestratorManager>();
+        orchestrator.tick(&app);
+    });
+}
+
+impl OrchestratorManager {
+    pub fn new(root: PathBuf) -> OrchestratorManager {
+        let store = RunStore::new(&root);
+        let mut runs = store.load_all();
+        // Sessions don't survive a restart. Steps that were running go back to
+        // pending and are relaunched in their existing session with their prompt.
+        for run in runs.iter_mut().filter(|run| run.status == RunStatus::Running) {
+            for step in run
+                .steps
+                .iter_mut()
+                .filter(|step| step.status == StepStatus::Running)
+            {
+                step.status = StepStatus::Pending;
+            }
+            diagnostics::log(format!("orchestrator_run_resumed id={}", run.id));
+        }
+        OrchestratorManager {
+            root,
+            store,
+            runs: Mutex::new(runs),
+            progress: Mutex::new(HashMap::new()),
+            advancing: Mutex::new(HashSet::new()),
+        }
+    }
+
+    pub fn list_runs(&self) -> Vec<RunRecord> {
+        self.runs.lock().clone()
+    }
+
+    pub fn get_run(&self, id: &str) -> Result<RunRecord, String> {
+        self.runs
+            .lock()
+            .iter()
+            .find(|run| run.id == id)
+            .cloned()
+            .ok_or_else(|| "Run not found".to_string())
+    }
+
+    /// Load a graph file and launch the steps that have no dependencies.
+    pub fn start_run(
+        &self,
+        app: &AppHandle,
+        graph_path: &str,
+        input: Option<String>,
+    ) -> Result<RunRecord, String> {
+        let graph = TaskGraph::load(Path::new(graph_path))?;
+        let run = RunRecord::new(
+            Uuid::new_v4().to_string(),
+            graph,
+            graph_path.to_string(),
+            input.filter(|input| !input.trim().is_empty()),
+            now(),
+        );
+        self.store.save(&run)?;
+        diagnostics::log(format!(
+            "orchestrator_run_started id={} graph={} steps={}",
+            run.id,
+            run.graph.name,
+            run.steps.len()
+        ));
+        let id = run.id.clone();
+        self.runs.lock().push(run);
+        self.advance_run(app, &id);
+        self.get_run(&id)
+    }
+
+    /// Stop the sessions of running steps and end the run.
+    pub fn cancel_run(&self, app: &AppHandle, id: &str) -> Result<(), String> {
+        let (run, session_ids) = {
+            let mut runs = self.runs.lock();
+            let run = runs
+                .iter_mut()
+                .find(|run| run.id == id)
+                .ok_or_else(|| "Run not found".to_string())?;
+            if run.status != RunStatus::Running {
+                return Err("Run is not running".to_string());
+            }
+            let finished_at = now();
+            let mut session_ids = Vec::new();
+            for step in run
+                .steps
+                .iter_mut()
+                .filter(|step| step.status == StepStatus::Running)
+            {
+                session_ids.extend(step.session_id.clone());
+                step.status = StepStatus::Cancelled;
+                step.finished_at = Some(finished_at.clone());
+                self.progress.lock().remove(&(id.to_string(), step.id.clone()));
+            }
+            run.status = RunStatus::Cancelled;
+            run.finished_at = Some(finished_at);
+            self.store.save(run)?;
+            (run.clone(), session_ids)
+        };
+        // Stopping waits for each process to exit, so it happens unlocked.
+        let sessions = app.state::<SessionManager>();
+        for session_id in session_ids {
+            let _ = sessions.stop_session(&session_id);
+        }
+        let _ = app.emit("orchestrator-run-updated", run);
+        diagnostics::log(format!("orchestrator_run_cancelled id={}", id));
+        Ok(())
+    }
+
+    /// Put failed and cancelled steps back to pending and continue the run.
+    /// Retried steps reuse their session and worktree.
+    pub fn retry_run(&self, app: &AppHandle, id: &str) -> Result<(), String> {
+        {
+            let mut runs = self.runs.lock();
+            let run = runs
+                .iter_mut()
+                .find(|run| run.id == id)
+                .ok_or_else(|| "Run not found".to_string())?;
+            if run.status == RunStatus::Running || run.status == RunStatus::Completed {
+                return Err("Only failed or cancelled runs can be retried".to_string());
+            }
+            for step in run.steps.iter_mut().filter(|step| {
+                step.status == StepStatus::Failed || step.status == StepStatus::Cancelled
+            }) {
+                step.status = StepStatus::Pending;
+                step.error = None;
+                step.exit_code = None;
+                step.prompt_sent = false;
+            }
+            run.status = RunStatus::Running;
+            run.finished_at = None;
+            self.store.save(run)?;
+        }
+        diagnostics::log(format!("orchestrator_run_retried id={}", id));
+        self.advance_run(app, id);
+        Ok(())
+    }
+
+    /// Forget a finished run. Worktrees are removed only when asked; their
+    /// branches are always kept.
+    pub fn delete_run(&self, id: &str, remove_worktrees: bool) -> Result<(), String> {
+        let mut runs = self.runs.lock();
+        let index = runs
+            .iter()
+            .position(|run| run.id == id)
+            .ok_or_else(|| "Run not found".to_string())?;
+        if runs[index].status == RunStatus::Running {
+            return Err("Cancel the run before deleting it".to_string());
+        }
+        if remove_worktrees {
+            let run = &runs[index];
+            for step in &run.steps {
+                let (Some(path), Some(def)) = (&step.worktree, run.graph.step(&step.id)) else {
+                    continue;
+                };
+                if let Some(repo) = run.graph.step_project_path(def) {
+                    worktree::remove(Path::new(repo), Path::new(path))?;
+                }
+            }
+        }
+        self.store.delete(id)?;
+        runs.remove(index);
+        Ok(())
+    }
+
+    /// Advance every running run.
+    pub fn tick(&self, app: &AppHandle) {
+        let running: Vec<String> = self
+            .runs
+            .lock()
+            .iter()
+            .filter(|run| run.status == RunStatus::Running)
+            .map(|run| run.id.clone())
+            .collect();
+        for id in running {
+            self.advance_run(app, &id);
+        }
+    }
+
+    /// Settle finished steps, launch ready ones and end the run once nothing is left.
+    fn advance_run(&self, app: &AppHandle, id: &str) {
+        if !self.advancing.lock().insert(id.to_string()) {
+            return;
+        }
+        self.advance_run_once(app, id);
+        self.advancing.lock().remove(id);
+    }
+
+    /// Starting sessions and creating worktrees is slow, so the run is advanced
+    /// on a copy with `runs` unlocked, then written back if it is still running.
+    fn advance_run_once(&self, app: &AppHandle, id: &str) {
+        let sessions = app.state::<SessionManager>();
+        let Some(mut run) = self
+            .runs
+            .lock()
+            .iter()
+            .find(|run| run.id == id && run.status == RunStatus::Running)
+            .cloned()
+        else {
+            return;
+        };
+        let mut changed = false;
+
+        let running: Vec<String> = run
+            .steps
+            .iter()
+            .filter(|step| step.status == StepStatus::Running)
+            .map(|step| step.id.clone())
+            .collect();
+        for step_id in running {
+            let check = self.check_step(&sessions, &run, &step_id);
+            if !matches!(check, StepCheck::Busy) {
+                self.finish_step(&sessions, &mut run, &step_id, check);
+                changed = true;
+            }
+        }
+
+        // Launching one step can't make another ready, so one pass is enough.
+        let mut launched = Vec::new();
+        for step_id in run.ready_steps() {
+            match self.launch_step(app, &sessions, &mut run, &step_id) {
+                Ok(session_id) => launched.push(session_id),
+                Err(err) => {
+                    diagnostics::log(format!(
+                        "orchestrator_step_launch_failed run={} step={} error={}",
+                        run.id, step_id, err
+                    ));
+                    if let Some(step) = run.step_mut(&step_id) {
+                        step.status = StepStatus::Failed;
+                        step.error = Some(err);
+                        step.finished_at = Some(now());
+                    }
+                }
+            }
+            changed = true;
+        }
+
+        if let Some(status) = run.settled_status() {
+            run.status = status;
+            run.finished_at = Some(now());
+            diagnostics::log(format!("orchestrator_run_finished id={} status={:?}", run.id, status));
+            changed = true;
+        }
+
+        if !changed {
+            return;
+        }
+        {
+            let mut runs = self.runs.lock();
+            match runs.iter_mut().find(|current| current.id == id) {
+                Some(current) if current.status == RunStatus::Running => {
+                    *current = run.clone();
+                    if let Err(err) = self.store.save(&run) {
+                        diagnostics::log(format!("orchestrator_save_failed id={} error={}", run.id, err));
+                    }
+                }
+                _ => {
+                    // Cancelled or deleted meanwhile: undo what this pass started.
+                    drop(runs);
+                    for session_id in launched {
+                        let _ = sessions.stop_session(&session_id);
+                    }
+                    return;
+                }
+            }
+        }
+        let _ = app.emit("orchestrator-run-updated", run);
+    }
+
+    fn check_step(&self, sessions: &SessionManager, run: &RunRecord, step_id: &str) -> StepCheck {
+        let (Some(state), Some(def)) = (run.step(step_id), run.graph.step(step_id)) else {
+            return StepCheck::Failed("step not found".to_string());
+        };
+        let Some(session_id) = state.session_id.as_deref() else {
+            return StepCheck::Failed("step has no session".to_string());
+        };
+        if let Some(code) = sessions.session_exit_code(session_id) {
+            return StepCheck::Exited(code);
+        }
+        if !sessions.is_session_running(session_id) {
+            return StepCheck::Failed("session was stopped".to_string());
+        }
+        let timed_out = def.timeout_minutes.is_some_and(|minutes| {
+            state
+                .started_at
+                .as_deref()
+                .and_then(|started| OffsetDateTime::parse(started, &Rfc3339).ok())
+                .is_some_and(|started| {
+                    OffsetDateTime::now_utc() - started >= time::Duration::minutes(i64::from(minutes))
+                })
+        });
+        if timed_out {
+            return StepCheck::Failed("timed out".to_string());
+        }
+
+        let turns = sessions.finished_turns(session_id);
+        let mut progress = self.progress.lock();
+        let progress = progress
+            .entry((run.id.clone(), step_id.to_string()))
+            .or_insert_with(|| StepProgress {
+                turns_before: turns,
+                idle_ticks: 0,
+            });
+        // Typing the prompt isn't progress; only a turn that ends after it is.
+        if !sessions.list_prompt_queue(session_id).is_empty() || sessions.prompt_in_flight(session_id) {
+            progress.turns_before = turns;
+            progress.idle_ticks = 0;
+            return StepCheck::Busy;
+        }
+        let at_prompt = matches!(
+            sessions.live_status(session_id),
+            Some(SessionStatus::Waiting | SessionStatus::Idle)
+        );
+        // A step without a prompt never gets a turn of its own; it is done once
+        // its tool settles at the prompt.
+        if at_prompt && (def.prompt.is_none() || turns > progress.turns_before) {
+            progress.idle_ticks += 1;
+        } else {
+            progress.idle_ticks = 0;
+        }
+        if progress.idle_ticks >= IDLE_TICKS_TO_COMPLETE {
+            StepCheck::Done
+        } else {
+            StepCheck::Busy
+        }
+    }
+
+    fn finish_step(&self, sessions: &SessionManager, run: &mut RunRecord, step_id: &str, check: StepCheck) {
+        self.progress
+            .lock()
+            .remove(&(run.id.clone(), step_id.to_string()));
+        let Some(def) = run.graph.step(step_id).cloned() else {
+            return;
+        };
+        let Some(step) = run.step_mut(step_id) else {
+            return;
+        };
+        let session_id = step.session_id.clone().unwrap_or_default();
+        let (status, exit_code, error) = match check {
+            StepCheck::Busy => return,
+            StepCheck::Done => (StepStatus::Completed, None, None),
+            StepCheck::Exited(0) => (StepStatus::Completed, Some(0), None),
+            StepCheck::Exited(code) => (
+                StepStatus::Failed,
+                Some(code),
+                Some(format!("exited with code {}", code)),
+            ),
+            StepCheck::Failed(error) => (StepStatus::Failed, None, Some(error)),
+        };
+        if status == StepStatus::Completed {
+            step.output = Some(capture_output(sessions, &def, step, &session_id));
+        }
+        // Free the PTY left behind by an exited process, and stop steps that ran too long.
+        if exit_code.is_some() || error.as_deref() == Some("timed out") {
+            let _ = sessions.stop_session(&session_id);
+        }
+        step.status = status;
+        step.exit_code = exit_code;
+        step.error = error;
+        step.finished_at = Some(now());
+        diagnostics::log(format!(
+            "orchestrator_step_finished run={} step={} status={:?} exit_code={:?}",
+            run.id, step_id, status, exit_code
+        ));
+    }
+
+    /// Start a ready step, returning its session.
+    fn launch_step(
+        &self,
+        app: &AppHandle,
+        sessions: &SessionManager,
+        run: &mut RunRecord,
+        step_id: &str,
+    ) -> Result<String, String> {
+        let def = run
+            .graph
+            .step(step_id)
+            .cloned()
+            .ok_or_else(|| "step not found".to_string())?;
+        let mut project_path = run
+            .graph
+            .step_project_path(&def)
+            .map(str::to_string)
+            .unwrap_or_default();
+        let mut branch = None;
+        if def.worktree {
+            let path = worktree::worktree_path(&self.root, &run.id, &def.id);
+            let name = worktree::branch_name(&run.id, &def.id);
+            worktree::create(Path::new(&project_path), &path, &name)?;
+            project_path = path.display().to_string();
+            branch = Some(name);
+        }
+        let prompt = def
+            .prompt
+            .as_deref()
+            .map(|prompt| graph::render(prompt, &template_values(run)))
+            .transpose()?;
+
+        let existing = run
+            .step(step_id)
+            .and_then(|step| step.session_id.clone())
+            .filter(|id| sessions.get_session(id).is_ok());
+        // A resumed step's session still has the prompt queued or already did it.
+        let prompt_sent = existing.is_some() && run.step(step_id).is_some_and(|step| step.prompt_sent);
+        let session_id = match existing {
+            Some(id) => id,
+            None => {
+                let tool = SessionTool::from_name(&def.tool);
+                let command = def
+                    .command
+                    .clone()
+                    .unwrap_or_else(|| default_tool_command(app, &tool));
+                let record = sessions.create_session(NewSessionInput {
+                    title: def
+                        .title
+                        .clone()
+                        .unwrap_or_else(|| format!("{}: {}", run.graph.name, def.id)),
+                    project_path: project_path.clone(),
+                    section_id: run
+                        .graph
+                        .section_id
+                        .clone()
+                        .unwrap_or_else(|| crate::control::DEFAULT_SECTION_ID.to_string()),
+                    tool,
+                    command,
+                    icon: None,
+                })?;
+                sessions.attach_section_mcps(&app.state::<McpManager>(), &record);
+                let _ = app.emit("session-created", record.clone());
+                record.id
+            }
+        };
+
+        // Record the session before starting so a failed start can reuse it on retry.
+        if let Some(step) = run.step_mut(step_id) {
+            step.session_id = Some(session_id.clone());
+            step.worktree = def.worktree.then_some(project_path);
+            step.branch = branch;
+        }
+        if !sessions.is_session_running(&session_id) {
+            sessions.start_session(app, &session_id, None, None)?;
+        }
+        self.progress.lock().insert(
+            (run.id.clone(), step_id.to_string()),
+            StepProgress {
+                turns_before: sessions.finished_turns(&session_id),
+                idle_ticks: 0,
+            },
+        );
+        let prompt = prompt.filter(|_| !prompt_sent);
+        let send_prompt = prompt.is_some();
+        if let Some(prompt) = prompt {
+            sessions.enqueue_prompt(&session_id, prompt)?;
+        }
+
+        if let Some(step) = run.step_mut(step_id) {
+            step.status = StepStatus::Running;
+            step.started_at = Some(now());
+            step.finished_at = None;
+            step.output = None;
+            step.prompt_sent |= send_prompt;
+        }
+        diagnostics::log(format!(
+            "orchestrator_step_started run={} step={} session_id={}",
+            run.id, step_id, session_id
+        ));
+        Ok(session_id)
+    }
+}
+
+/// Values for `{{...}}` placeholders: the run input and what completed steps produced.
+fn template_values(run: &RunRecord) -> HashMap<String, String> {
+    let mut values = HashMap::new();
+    values.insert("input".to_string(), run.input.clone().unwrap_or_default());
+    values.insert("run.id".to_string(), run.id.clone());
+    for step in run
+        .steps
+        .iter()
+        .filter(|step| step.status == StepStatus::Completed)
+    {
+        let fields = [
+            ("output", &step.output),
+            ("worktree", &step.worktree),
+            ("branch", &step.branch),
+            ("session_id", &step.session_id),
+        ];
+        for (field, value) in fields {
+            values.insert(
+                format!("steps.{}.{}", step.id, field),
+                value.clone().unwrap_or_default(),
+            );
+        }
+    }
+    values
+}
+
+/// A finished step's output: its `output_file`, or the tail of its session output.
+fn capture_output(sessions: &SessionManager, def: &StepDef, step: &StepState, session_id: &str) -> String {
+    let output = match &def.output_file {
+        Some(file) => {
+            let dir = match &step.worktree {
+                Some(worktree) => PathBuf::from(worktree),
+                None => sessions
+                    .get_session(session_id)
+                    .map(|record| PathBuf::from(record.project_path))
+                    .unwrap_or_default(),
+            };
+            std::fs::read_to_string(dir.join(file))
+                .unwrap_or_else(|e| format!("(could not read {}: {})", file, e))
+        }
+        None => sessions
+            .read_output(session_id, def.output_lines)
+            .unwrap_or_default()
+            .join("\n"),
+    };
+    truncate_output(output)
+}
+
+/// Keep the end of long outputs; that is where agents put their summary.
+fn truncate_output(output: String) -> String {
+    let count = output.chars().count();
+    if count <= MAX_OUTPUT_CHARS {
+        return output;
+    }
+    output.chars().skip(count - MAX_OUTPUT_CHARS).collect()
+}
+
+fn now() -> String {
+    OffsetDateTime::now_utc().format(&Rfc3339).unwrap_or_default()
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn orchestrator_list_runs(state: State<'_, OrchestratorManager>) -> Result<Vec<RunRecord>, String> {
+    Ok(state.list_runs())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn orchestrator_get_run(state: State<'_, OrchestratorManager>, id: String) -> Result<RunRecord, String> {
+    state.get_run(&id)
+}
+
+/// Parse and validate a graph file without running it.
+#[tauri::command(rename_all = "camelCase")]
+pub fn orchestrator_load_graph(graph_path: String) -> Result<TaskGraph, String> {
+    TaskGraph::load(Path::new(&graph_path))
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn orchestrator_start_run(
+    app: AppHandle,
+    state: State<'_, OrchestratorManager>,
+    graph_path: String,
+    input: Option<String>,
+) -> Result<RunRecord, String> {
+    state.start_run(&app, &graph_path, input)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn orchestrator_cancel_run(
+    app: AppHandle,
+    state: State<'_, OrchestratorManager>,
+    id: String,
+) -> Result<(), String> {
+    state.cancel_run(&app, &id)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn orchestrator_retry_run(
+    app: AppHandle,
+    state: State<'_, OrchestratorManager>,
+    id: String,
+) -> Result<(), String> {
+    state.retry_run(&app, &id)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn orchestrator_delete_run(
+    state: State<'_, OrchestratorManager>,
+    id: String,
+    remove_worktrees: Option<bool>,
+) -> Result<(), String> {
+    state.delete_run(&id, remove_worktrees.unwrap_or(false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GRAPH: &str = r#"
+name = "pair"
+[[steps]]
+id = "plan"
+[[steps]]
+id = "build"
+depends_on = ["plan"]
+"#;
+
+    #[test]
+    fn test_new_requeues_running_steps_after_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let graph = TaskGraph::parse(GRAPH, Path::new("graph.toml")).unwrap();
+        let mut run = RunRecord::new("run-1".to_string(), graph, String::new(), None, now());
+        run.step_mut("plan").unwrap().status = StepStatus::Completed;
+        let build = run.step_mut("build").unwrap();
+        build.status = StepStatus::Running;
+        build.session_id = Some("session-1".to_string());
+        build.prompt_sent = true;
+        RunStore::new(dir.path()).save(&run).unwrap();
+
+        let manager = OrchestratorManager::new(dir.path().to_path_buf());
+        let loaded = manager.get_run("run-1").unwrap();
+        let build = loaded.step("build").unwrap();
+        assert_eq!(loaded.status, RunStatus::Running);
+        assert_eq!(build.status, StepStatus::Pending);
+        assert_eq!(build.session_id.as_deref(), Some("session-1"));
+        // The session's persisted queue already holds the prompt.
+        assert!(build.prompt_sent);
+        assert_eq!(loaded.ready_steps(), vec!["build"]);
+    }
+
+    #[test]
+    fn test_template_values_include_completed_steps_only() {
+        let graph = TaskGraph::parse(GRAPH, Path::new("graph.toml")).unwrap();
+        let mut run = RunRecord::new("run-1".to_string(), graph, String::new(), Some("task".to_string()), now());
+        let plan = run.step_mut("plan").unwrap();
+        plan.status = StepStatus::Completed;
+        plan.output = Some("1. do it".to_string());
+
+        let values = template_values(&run);
+        assert_eq!(values["input"], "task");
+        assert_eq!(values["steps.plan.output"], "1. do it");
+        assert_eq!(values["steps.plan.branch"], "");
+        assert!(!values.contains_key("steps.build.output"));
+    }
+
+    #[test]
+    fn test_truncate_output_keeps_the_end() {
+        let long = format!("{}end", "x".repeat(MAX_OUTPUT_CHARS));
+        let truncated = truncate_output(long);
+        assert_eq!(truncated.chars().count(), MAX_OUTPUT_CHARS);
+        assert!(truncated.ends_with("end"));
+    }
+}
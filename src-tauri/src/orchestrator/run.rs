@@ -0,0 +1,272 @@
+//! Orchestrator run state and its persistence under `~/.agent-term/orchestrator/runs`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::graph::TaskGraph;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum RunStatus {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum StepStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// Progress of one step of a run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StepState {
+    pub id: String,
+    pub status: StepStatus,
+    /// Kept across retries so a relaunched step continues in the same session.
+    pub session_id: Option<String>,
+    pub worktree: Option<String>,
+    pub branch: Option<String>,
+    pub output: Option<String>,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+    pub exit_code: Option<u32>,
+    pub error: Option<String>,
+    /// The step's prompt has been queued in its session. Prompt queues are
+    /// persisted, so a step picked up again after a restart doesn't send it twice.
+    #[serde(default)]
+    pub prompt_sent: bool,
+}
+
+impl StepState {
+    fn pending(id: &str) -> StepState {
+        StepState {
+            id: id.to_string(),
+            status: StepStatus::Pending,
+            session_id: None,
+            worktree: None,
+            branch: None,
+            output: None,
+            started_at: None,
+            finished_at: None,
+            exit_code: None,
+            error: None,
+            prompt_sent: false,
+        }
+    }
+}
+
+/// One execution of a task graph.
+///
+/// Example:
+/// ```rust,ignore
+/// let run = RunRecord::new(
+///     Uuid::new_v4().to_string(),
+///     graph,
+///     "/work/review.toml".to_string(),
+///     Some("add dark mode".to_string()),
+///     now(),
+/// );
+/// let ready = run.ready_steps();
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunRecord {
+    pub id: String,
+    pub graph_path: String,
+    /// The graph as it was when the run started; later edits to the file don't apply.
+    pub graph: TaskGraph,
+    pub input: Option<String>,
+    pub status: RunStatus,
+    pub created_at: String,
+    pub finished_at: Option<String>,
+    pub steps: Vec<StepState>,
+}
+
+impl RunRecord {
+    pub fn new(id: String, graph: TaskGraph, graph_path: String, input: Option<String>, now: String) -> RunRecord {
+        let steps = graph.steps.iter().map(|step| StepState::pending(&step.id)).collect();
+        RunRecord {
+            id,
+            graph_path,
+            graph,
+            input,
+            status: RunStatus::Running,
+            created_at: now,
+            finished_at: None,
+            steps,
+        }
+    }
+
+    pub fn step(&self, id: &str) -> Option<&StepState> {
+        self.steps.iter().find(|step| step.id == id)
+    }
+
+    pub fn step_mut(&mut self, id: &str) -> Option<&mut StepState> {
+        self.steps.iter_mut().find(|step| step.id == id)
+    }
+
+    /// Pending steps whose dependencies have all completed.
+    pub fn ready_steps(&self) -> Vec<String> {
+        self.graph
+            .steps
+            .iter()
+            .filter(|def| {
+                self.step(&def.id)
+                    .is_some_and(|state| state.status == StepStatus::Pending)
+                    && def.depends_on.iter().all(|dep| {
+                        self.step(dep)
+                            .is_some_and(|state| state.status == StepStatus::Completed)
+                    })
+            })
+            .map(|def| def.id.clone())
+            .collect()
+    }
+
+    /// The status the run settles on once nothing is running or ready, if it has settled.
+    /// Steps behind a failed step stay pending so a retry can pick them up.
+    pub fn settled_status(&self) -> Option<RunStatus> {
+        let running = self
+            .steps
+            .iter()
+            .any(|step| step.status == StepStatus::Running);
+        if running || !self.ready_steps().is_empty() {
+            return None;
+        }
+        let all_completed = self
+            .steps
+            .iter()
+            .all(|step| step.status == StepStatus::Completed);
+        Some(if all_completed {
+            RunStatus::Completed
+        } else {
+            RunStatus::Failed
+        })
+    }
+}
+
+/// Reads and writes one JSON file per run.
+pub struct RunStore {
+    dir: PathBuf,
+}
+
+impl RunStore {
+    pub fn new(root: &Path) -> RunStore {
+        RunStore {
+            dir: root.join("runs"),
+        }
+    }
+
+    /// All stored runs, oldest first. Unreadable files are skipped.
+    pub fn load_all(&self) -> Vec<RunRecord> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut runs: Vec<RunRecord> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|path| {
+                let content = fs::read_to_string(&path).ok()?;
+                serde_json::from_str(&content).ok()
+            })
+            .collect();
+        runs.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+        runs
+    }
+
+    pub fn save(&self, run: &RunRecord) -> Result<(), String> {
+        fs::create_dir_all(&self.dir).map_err(|e| e.to_string())?;
+        let content = serde_json::to_string_pretty(run).map_err(|e| e.to_string())?;
+        // Write then rename so a crash never leaves a truncated run file.
+        let tmp = self.dir.join(format!("{}.json.tmp", run.id));
+        fs::write(&tmp, content).map_err(|e| e.to_string())?;
+        fs::rename(&tmp, self.path(&run.id)).map_err(|e| e.to_string())
+    }
+
+    pub fn delete(&self, id: &str) -> Result<(), String> {
+        match fs::remove_file(self.path(id)) {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(err.to_string()),
+        }
+    }
+
+    fn path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph() -> TaskGraph {
+        TaskGraph::parse(
+            r#"
+name = "fan-out"
+[[steps]]
+id = "plan"
+[[steps]]
+id = "a"
+depends_on = ["plan"]
+[[steps]]
+id = "b"
+depends_on = ["plan"]
+[[steps]]
+id = "review"
+depends_on = ["a", "b"]
+"#,
+            Path::new("graph.toml"),
+        )
+        .unwrap()
+    }
+
+    fn run() -> RunRecord {
+        RunRecord::new("run-1".to_string(), graph(), String::new(), None, "t0".to_string())
+    }
+
+    #[test]
+    fn test_ready_steps_follow_dependencies() {
+        let mut run = run();
+        assert_eq!(run.ready_steps(), vec!["plan"]);
+        run.step_mut("plan").unwrap().status = StepStatus::Completed;
+        assert_eq!(run.ready_steps(), vec!["a", "b"]);
+        run.step_mut("a").unwrap().status = StepStatus::Completed;
+        run.step_mut("b").unwrap().status = StepStatus::Running;
+        assert!(run.ready_steps().is_empty());
+        assert_eq!(run.settled_status(), None);
+    }
+
+    #[test]
+    fn test_failed_step_settles_run_as_failed() {
+        let mut run = run();
+        run.step_mut("plan").unwrap().status = StepStatus::Completed;
+        run.step_mut("a").unwrap().status = StepStatus::Failed;
+        run.step_mut("b").unwrap().status = StepStatus::Completed;
+        assert_eq!(run.settled_status(), Some(RunStatus::Failed));
+        assert_eq!(run.step("review").unwrap().status, StepStatus::Pending);
+    }
+
+    #[test]
+    fn test_store_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = RunStore::new(dir.path());
+        let run = run();
+        store.save(&run).unwrap();
+        let loaded = store.load_all();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].steps.len(), 4);
+        store.delete(&run.id).unwrap();
+        assert!(store.load_all().is_empty());
+    }
+}
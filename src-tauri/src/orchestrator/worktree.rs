@@ -0,0 +1,96 @@
+//! Git worktrees for steps that run on their own branch.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Branch a step's worktree is checked out on.
+pub fn branch_name(run_id: &str, step_id: &str) -> String {
+    let short: String = run_id.chars().take(8).collect();
+    format!("agent-term/{}/{}", short, step_id)
+}
+
+/// Create a worktree of `repo` at `path` on a new `branch` from HEAD.
+/// An existing worktree at `path` (from an earlier attempt) is reused.
+pub fn create(repo: &Path, path: &Path, branch: &str) -> Result<(), String> {
+    if path.join(".git").exists() {
+        return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let path = path.display().to_string();
+    git(repo, &["worktree", "add", "-b", branch, &path, "HEAD"]).map(|_| ())
+}
+
+/// Remove a worktree. Its branch is kept so finished work is not lost.
+pub fn remove(repo: &Path, path: &Path) -> Result<(), String> {
+    if !path.exists() {
+        return Ok(());
+    }
+    let path = path.display().to_string();
+    git(repo, &["worktree", "remove", "--force", &path]).map(|_| ())
+}
+
+/// Directory for a step's worktree under the orchestrator root.
+pub fn worktree_path(root: &Path, run_id: &str, step_id: &str) -> PathBuf {
+    root.join("worktrees").join(run_id).join(step_id)
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|e| format!("failed to run git: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "git {} failed: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_create_and_remove_worktree() {
+        let repo = TempDir::new().unwrap();
+        git(repo.path(), &["init", "-q"]).unwrap();
+        git(
+            repo.path(),
+            &[
+                "-c",
+                "user.name=test",
+                "-c",
+                "user.email=test@localhost",
+                "commit",
+                "-q",
+                "--allow-empty",
+                "-m",
+                "init",
+            ],
+        )
+        .unwrap();
+
+        let root = TempDir::new().unwrap();
+        let path = worktree_path(root.path(), "0123456789ab", "impl");
+        let branch = branch_name("0123456789ab", "impl");
+        assert_eq!(branch, "agent-term/01234567/impl");
+
+        create(repo.path(), &path, &branch).unwrap();
+        assert!(path.join(".git").exists());
+        // A retry reuses the worktree instead of failing on the existing branch.
+        create(repo.path(), &path, &branch).unwrap();
+
+        remove(repo.path(), &path).unwrap();
+        assert!(!path.exists());
+        let branches = git(repo.path(), &["branch", "--list", &branch]).unwrap();
+        assert!(!branches.trim().is_empty());
+    }
+}
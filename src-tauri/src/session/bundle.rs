@@ -0,0 +1,582 @@
+// ABOUTME: Portable bundles of sections and sessions for handing an agent setup to someone else.
+// ABOUTME: Redacts secrets on export; imports are previewed and approved, then remap paths, IDs and transcripts.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::mcp::config::MCPDef;
+
+use super::model::{
+    SectionRecord, SessionContainer, SessionRecord, SessionRemote, SessionSandbox, SessionTool,
+};
+
+/// Format version written to every bundle.
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// Stands in for redacted secrets; importers fill these in before using the MCP.
+pub const REDACTED: &str = "<redacted>";
+
+/// Stands in for a session's project path inside exported transcripts.
+const PROJECT_PLACEHOLDER: &str = "<agent-term-project>";
+
+/// Words that mark an env var, flag or query parameter as a secret.
+const SECRET_MARKERS: [&str; 8] = [
+    "KEY", "TOKEN", "SECRET", "PASSWORD", "PASSWD", "AUTH", "CREDENTIAL", "COOKIE",
+];
+
+/// Sections, sessions and MCP definitions exported together.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionBundle {
+    pub version: u32,
+    pub exported_at: String,
+    pub sections: Vec<SectionRecord>,
+    pub sessions: Vec<BundledSession>,
+    /// Definitions of every MCP the sessions use, with secrets redacted.
+    #[serde(default)]
+    pub mcps: BTreeMap<String, MCPDef>,
+}
+
+/// A session and what travels with it. Paths under the exporter's home are
+/// written as `~/...`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundledSession {
+    pub record: SessionRecord,
+    /// MCPs loaded in the session or attached to its project.
+    #[serde(default)]
+    pub mcp_names: Vec<String>,
+    /// Plain-text terminal output, most recent last.
+    #[serde(default)]
+    pub scrollback: Option<Vec<String>>,
+    /// Claude JSONL transcript, so the conversation can be resumed.
+    #[serde(default)]
+    pub transcript: Option<String>,
+}
+
+/// What to export: a whole section, or a set of sessions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportBundleInput {
+    pub path: String,
+    #[serde(default)]
+    pub section_id: Option<String>,
+    #[serde(default)]
+    pub session_ids: Vec<String>,
+    #[serde(default)]
+    pub include_scrollback: bool,
+    #[serde(default)]
+    pub include_transcripts: bool,
+}
+
+/// Rewrites paths starting with `from` to start with `to`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PathMapping {
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportBundleInput {
+    pub path: String,
+    #[serde(default)]
+    pub path_mappings: Vec<PathMapping>,
+    /// Put every session in this existing section instead of recreating the bundle's sections.
+    #[serde(default)]
+    pub section_id: Option<String>,
+    /// The `digest` of the [`BundlePreview`] the user approved. Import refuses
+    /// bundles that were not previewed, or that changed since.
+    #[serde(default)]
+    pub approved_digest: Option<String>,
+}
+
+/// What importing a bundle would run and install, for the user to approve.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundlePreview {
+    /// Sections that would be created; empty when importing into an existing one.
+    pub sections: Vec<PreviewSection>,
+    pub sessions: Vec<PreviewSession>,
+    /// MCP definitions that would be added to config.toml.
+    pub new_mcps: BTreeMap<String, MCPDef>,
+    /// MCPs already defined locally; the local definition would be kept.
+    pub existing_mcps: Vec<String>,
+    /// Pass back as `approvedDigest` to import exactly this bundle.
+    pub digest: String,
+}
+
+/// A session as it would be created by an import.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviewSession {
+    pub title: String,
+    pub tool: SessionTool,
+    pub command: String,
+    pub project_path: String,
+    /// Names of the env vars the session would be started with.
+    pub env_names: Vec<String>,
+    /// Image and options of the container the session would run in.
+    pub container: Option<SessionContainer>,
+    /// Host the session would connect to over ssh.
+    pub remote: Option<SessionRemote>,
+    /// Sandbox settings overriding the tool's.
+    pub sandbox: Option<SessionSandbox>,
+}
+
+/// A section and the defaults its new sessions would get.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviewSection {
+    pub name: String,
+    pub path: String,
+    pub tool: Option<SessionTool>,
+    pub command: Option<String>,
+    /// MCPs that would be attached to the section's new sessions.
+    pub mcp_names: Vec<String>,
+    pub env_names: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportBundleResult {
+    pub sections: Vec<SectionRecord>,
+    pub sessions: Vec<SessionRecord>,
+    /// MCPs added to config.toml.
+    pub added_mcps: Vec<String>,
+    /// MCPs already defined locally; the local definition was kept.
+    pub existing_mcps: Vec<String>,
+    /// Added MCPs with redacted values that need filling in.
+    pub mcps_needing_secrets: Vec<String>,
+    /// Env vars left out because the bundle only had them redacted.
+    pub env_needing_secrets: Vec<RedactedEnvVar>,
+}
+
+/// An env var of an imported section's defaults or session that was redacted
+/// on export, so it was not imported and needs setting again.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RedactedEnvVar {
+    /// Name of the section, or title of the session.
+    pub owner: String,
+    pub name: String,
+}
+
+/// Read a bundle along with the digest of its contents.
+pub fn read_bundle(path: &Path) -> Result<(SessionBundle, String), String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read bundle {}: {}", path.display(), e))?;
+    let bundle: SessionBundle =
+        serde_json::from_str(&content).map_err(|e| format!("Invalid bundle: {}", e))?;
+    if bundle.version > BUNDLE_VERSION {
+        return Err(format!(
+            "Bundle version {} is newer than this app supports ({})",
+            bundle.version, BUNDLE_VERSION
+        ));
+    }
+    for session in &bundle.sessions {
+        if let Some(claude_id) = &session.record.claude_session_id {
+            check_claude_session_id(claude_id).map_err(|e| {
+                format!("Invalid bundle: {} (session '{}')", e, session.record.title)
+            })?;
+        }
+    }
+    Ok((bundle, digest(&content)))
+}
+
+/// Claude conversation ids are UUIDs. Anything else may be a path that would
+/// put a transcript outside Claude's projects directory.
+pub fn check_claude_session_id(id: &str) -> Result<(), String> {
+    // Only the 36-character hyphenated form, not the braced or URN forms.
+    if id.len() == 36 && uuid::Uuid::try_parse(id).is_ok() {
+        Ok(())
+    } else {
+        Err(format!("Claude session id {:?} is not a UUID", id))
+    }
+}
+
+/// Identifies a bundle's exact contents between preview and import: the
+/// SHA-256 of the file, in hex.
+fn digest(content: &str) -> String {
+    Sha256::digest(content.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Fail unless the user approved this exact bundle.
+pub fn check_approved(input: &ImportBundleInput, digest: &str) -> Result<(), String> {
+    match input.approved_digest.as_deref() {
+        Some(approved) if approved == digest => Ok(()),
+        Some(_) => Err("The bundle changed since it was previewed; review it again".to_string()),
+        None => Err(
+            "Review the bundle's commands and MCP servers and approve them before importing"
+                .to_string(),
+        ),
+    }
+}
+
+pub fn write_bundle(path: &Path, bundle: &SessionBundle) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(bundle).map_err(|e| e.to_string())?;
+    std::fs::write(path, content)
+        .map_err(|e| format!("Failed to write bundle {}: {}", path.display(), e))
+}
+
+fn is_secret_name(name: &str) -> bool {
+    let upper = name.to_uppercase();
+    SECRET_MARKERS.iter().any(|marker| upper.contains(marker))
+}
+
+/// Replace the values of env vars with secret-looking names by [`REDACTED`].
+/// Returns whether anything was redacted.
+pub fn redact_env<'a>(env: impl IntoIterator<Item = (&'a String, &'a mut String)>) -> bool {
+    let mut changed = false;
+    for (name, value) in env {
+        if is_secret_name(name) && !value.is_empty() {
+            *value = REDACTED.to_string();
+            changed = true;
+        }
+    }
+    changed
+}
+
+/// Remove the env vars whose values were redacted on export, returning their names.
+pub fn take_redacted_env(env: &mut BTreeMap<String, String>) -> Vec<String> {
+    let names: Vec<String> = env
+        .iter()
+        .filter(|(_, value)| *value == REDACTED)
+        .map(|(name, _)| name.clone())
+        .collect();
+    for name in &names {
+        env.remove(name);
+    }
+    names
+}
+
+/// Copy of `def` with secret env vars, flag values and URL credentials replaced
+/// by [`REDACTED`]. Returns whether anything was redacted.
+pub fn redact_mcp(def: &MCPDef) -> (MCPDef, bool) {
+    let mut redacted = def.clone();
+    let mut changed = redact_env(redacted.env.iter_mut());
+
+    let mut secret_flag = false;
+    for arg in redacted.args.iter_mut() {
+        let value = redact_arg(arg, secret_flag);
+        secret_flag = value.is_none() && is_secret_flag(arg);
+        if let Some(value) = value.filter(|value| value != arg) {
+            *arg = value;
+            changed = true;
+        }
+    }
+
+    if !redacted.url.is_empty() {
+        let url = redact_url(&redacted.url);
+        if url != redacted.url {
+            redacted.url = url;
+            changed = true;
+        }
+    }
+    (redacted, changed)
+}
+
+/// A flag whose name marks its value as secret, e.g. `--api-key` or `--token`.
+fn is_secret_flag(arg: &str) -> bool {
+    arg.starts_with('-') && !arg.contains('=') && is_secret_name(arg.trim_start_matches('-'))
+}
+
+/// The redacted form of one argument, or `None` when it is a bare flag whose
+/// value follows. `after_secret_flag` is set for the value of a secret flag.
+fn redact_arg(arg: &str, after_secret_flag: bool) -> Option<String> {
+    if after_secret_flag && !arg.starts_with("--") {
+        return Some(REDACTED.to_string());
+    }
+    if is_secret_flag(arg) {
+        return None;
+    }
+    if arg.contains("://") {
+        return Some(redact_url(arg));
+    }
+    // `--api-key=...`, `GITHUB_TOKEN=...` (e.g. after `-e`) and header values
+    // such as `Authorization: Bearer ...`.
+    for separator in ['=', ':'] {
+        if let Some((name, value)) = arg.split_once(separator) {
+            let bare = name.trim_start_matches('-');
+            if !value.trim().is_empty()
+                && !bare.contains(char::is_whitespace)
+                && is_secret_name(bare)
+            {
+                let space = if separator == ':' { " " } else { "" };
+                return Some(format!("{}{}{}{}", name, separator, space, REDACTED));
+            }
+        }
+    }
+    Some(arg.to_string())
+}
+
+/// Drop `user:password@` and redact secret-looking query parameters.
+fn redact_url(url: &str) -> String {
+    let (base, query) = match url.split_once('?') {
+        Some((base, query)) => (base, Some(query)),
+        None => (url, None),
+    };
+    let base = match base.split_once("://") {
+        Some((scheme, rest)) => {
+            let (authority, path) = match rest.find('/') {
+                Some(index) => rest.split_at(index),
+                None => (rest, ""),
+            };
+            match authority.rsplit_once('@') {
+                Some((_, host)) => format!("{}://{}@{}{}", scheme, REDACTED, host, path),
+                None => base.to_string(),
+            }
+        }
+        None => base.to_string(),
+    };
+    let Some(query) = query else {
+        return base;
+    };
+    let params: Vec<String> = query
+        .split('&')
+        .map(|param| match param.split_once('=') {
+            Some((name, _)) if is_secret_name(name) => format!("{}={}", name, REDACTED),
+            _ => param.to_string(),
+        })
+        .collect();
+    format!("{}?{}", base, params.join("&"))
+}
+
+/// `path` with `home` written as `~`, so the bundle does not depend on the exporter's username.
+pub fn portable_path(path: &str, home: Option<&Path>) -> String {
+    let Some(home) = home else {
+        return path.to_string();
+    };
+    match Path::new(path).strip_prefix(home) {
+        Ok(rest) if rest.as_os_str().is_empty() => "~".to_string(),
+        Ok(rest) => format!("~/{}", rest.display()),
+        Err(_) => path.to_string(),
+    }
+}
+
+/// Apply the longest matching mapping, then expand `~` to the importer's `home`.
+pub fn remap_path(path: &str, mappings: &[PathMapping], home: Option<&Path>) -> String {
+    let best = mappings
+        .iter()
+        .filter_map(|mapping| {
+            let rest = Path::new(path).strip_prefix(&mapping.from).ok()?;
+            Some((mapping, rest))
+        })
+        .max_by_key(|(mapping, _)| mapping.from.len());
+    let mapped = match best {
+        Some((mapping, rest)) if rest.as_os_str().is_empty() => mapping.to.clone(),
+        Some((mapping, rest)) => Path::new(&mapping.to).join(rest).display().to_string(),
+        None => path.to_string(),
+    };
+    match (home, mapped.strip_prefix('~')) {
+        (Some(home), Some("")) => home.display().to_string(),
+        (Some(home), Some(rest)) if rest.starts_with('/') => {
+            home.join(&rest[1..]).display().to_string()
+        }
+        _ => mapped,
+    }
+}
+
+/// Root of Claude's per-project transcript directories.
+pub fn claude_projects_root() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".claude").join("projects"))
+}
+
+/// Claude's transcript directory for a project: the absolute path with every
+/// character other than letters and digits replaced by `-`.
+pub fn claude_project_dir(root: &Path, project_path: &str) -> PathBuf {
+    let encoded: String = project_path
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    root.join(encoded)
+}
+
+/// Find the transcript of a Claude conversation in any project directory.
+pub fn find_claude_transcript(root: &Path, claude_session_id: &str) -> Option<PathBuf> {
+    check_claude_session_id(claude_session_id).ok()?;
+    let file_name = format!("{}.jsonl", claude_session_id);
+    std::fs::read_dir(root)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path().join(&file_name))
+        .find(|path| path.is_file())
+}
+
+/// Transcript text with the project path replaced by a placeholder.
+pub fn export_transcript(content: &str, project_path: &str) -> String {
+    if project_path.is_empty() {
+        return content.to_string();
+    }
+    content.replace(project_path, PROJECT_PLACEHOLDER)
+}
+
+/// Write an exported transcript where Claude looks for it in `project_path`.
+pub fn import_transcript(
+    root: &Path,
+    claude_session_id: &str,
+    content: &str,
+    project_path: &str,
+) -> Result<(), String> {
+    check_claude_session_id(claude_session_id)?;
+    let dir = claude_project_dir(root, project_path);
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let path = dir.join(format!("{}.jsonl", claude_session_id));
+    if path.exists() {
+        // The conversation is already here; don't clobber newer history.
+        return Ok(());
+    }
+    std::fs::write(&path, content.replace(PROJECT_PLACEHOLDER, project_path))
+        .map_err(|e| format!("Failed to write transcript {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_redact_mcp_hides_secrets_only() {
+        let def = MCPDef {
+            command: "npx".to_string(),
+            args: vec![
+                "server".to_string(),
+                "--api-key".to_string(),
+                "sk-123".to_string(),
+                "--token=abc".to_string(),
+                "--port".to_string(),
+                "8080".to_string(),
+            ],
+            env: HashMap::from([
+                ("GITHUB_TOKEN".to_string(), "ghp_x".to_string()),
+                ("LOG_LEVEL".to_string(), "debug".to_string()),
+            ]),
+            url: "https://user:pw@example.com/mcp?api_key=s3cret&region=eu".to_string(),
+            ..MCPDef::default()
+        };
+        let (redacted, changed) = redact_mcp(&def);
+        assert!(changed);
+        assert_eq!(
+            redacted.args,
+            vec!["server", "--api-key", REDACTED, "--token=<redacted>", "--port", "8080"]
+        );
+
+        let args = MCPDef {
+            command: "npx".to_string(),
+            args: vec![
+                "--auth-token".to_string(),
+                "-abc".to_string(),
+                "--header".to_string(),
+                "Authorization: Bearer xyz".to_string(),
+                "-e".to_string(),
+                "SLACK_BOT_TOKEN=xoxb".to_string(),
+                "https://u:p@db.example.com/x?token=t".to_string(),
+                "postgres://admin:pw@localhost/app".to_string(),
+            ],
+            ..MCPDef::default()
+        };
+        assert_eq!(
+            redact_mcp(&args).0.args,
+            vec![
+                "--auth-token",
+                REDACTED,
+                "--header",
+                "Authorization: <redacted>",
+                "-e",
+                "SLACK_BOT_TOKEN=<redacted>",
+                "https://<redacted>@db.example.com/x?token=<redacted>",
+                "postgres://<redacted>@localhost/app",
+            ]
+        );
+        assert_eq!(redacted.env["GITHUB_TOKEN"], REDACTED);
+        assert_eq!(redacted.env["LOG_LEVEL"], "debug");
+        assert_eq!(
+            redacted.url,
+            "https://<redacted>@example.com/mcp?api_key=<redacted>&region=eu"
+        );
+
+        let plain = MCPDef {
+            command: "uvx".to_string(),
+            ..MCPDef::default()
+        };
+        assert!(!redact_mcp(&plain).1);
+    }
+
+    #[test]
+    fn test_import_requires_the_previewed_digest() {
+        let content = r#"{"version":1}"#;
+        let mut input = ImportBundleInput {
+            path: "bundle.json".to_string(),
+            path_mappings: Vec::new(),
+            section_id: None,
+            approved_digest: None,
+        };
+        assert!(check_approved(&input, &digest(content)).is_err());
+
+        assert_eq!(digest(content).len(), 64);
+        input.approved_digest = Some(digest(content));
+        assert!(check_approved(&input, &digest(content)).is_ok());
+        assert!(check_approved(&input, &digest(r#"{"version":1,"mcps":{}}"#)).is_err());
+    }
+
+    #[test]
+    fn test_claude_session_ids_must_be_uuids() {
+        assert!(check_claude_session_id("0f8c2d4e-5b6a-4c3d-9e8f-7a6b5c4d3e2f").is_ok());
+        for id in ["../../../.config/foo", "abc", "0f8c2d4e5b6a4c3d9e8f7a6b5c4d3e2f", ""] {
+            assert!(check_claude_session_id(id).is_err(), "{}", id);
+        }
+        let root = tempfile::tempdir().unwrap();
+        assert!(import_transcript(root.path(), "../escape", "{}", "/home/bob/app").is_err());
+        assert!(!root.path().join("escape.jsonl").exists());
+    }
+
+    #[test]
+    fn test_paths_round_trip_through_home() {
+        let exporter = Path::new("/Users/alice");
+        let importer = Path::new("/home/bob");
+        let portable = portable_path("/Users/alice/code/app", Some(exporter));
+        assert_eq!(portable, "~/code/app");
+        assert_eq!(remap_path(&portable, &[], Some(importer)), "/home/bob/code/app");
+        assert_eq!(portable_path("/opt/app", Some(exporter)), "/opt/app");
+    }
+
+    #[test]
+    fn test_remap_path_prefers_longest_mapping() {
+        let mappings = vec![
+            PathMapping {
+                from: "~/code".to_string(),
+                to: "/work".to_string(),
+            },
+            PathMapping {
+                from: "~/code/app".to_string(),
+                to: "/srv/app".to_string(),
+            },
+        ];
+        assert_eq!(remap_path("~/code/app/web", &mappings, None), "/srv/app/web");
+        assert_eq!(remap_path("~/code/lib", &mappings, None), "/work/lib");
+        // Only whole path components match.
+        assert_eq!(remap_path("~/codex", &mappings, None), "~/codex");
+    }
+
+    #[test]
+    fn test_transcript_moves_to_new_project_dir() {
+        let root = tempfile::tempdir().unwrap();
+        let exported = export_transcript(r#"{"cwd":"/Users/alice/app"}"#, "/Users/alice/app");
+        let id = "0f8c2d4e-5b6a-4c3d-9e8f-7a6b5c4d3e2f";
+        import_transcript(root.path(), id, &exported, "/home/bob/app").unwrap();
+
+        let path = find_claude_transcript(root.path(), id).unwrap();
+        assert_eq!(path.parent().unwrap(), root.path().join("-home-bob-app"));
+        assert_eq!(
+            std::fs::read_to_string(path).unwrap(),
+            r#"{"cwd":"/home/bob/app"}"#
+        );
+    }
+}
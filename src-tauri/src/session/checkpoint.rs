@@ -0,0 +1,331 @@
+// ABOUTME: Snapshots a session's project tree into hidden git refs and restores them on demand.
+// ABOUTME: Uses a throwaway index file so the user's staging area, HEAD and branches are untouched.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+
+use crate::diagnostics;
+
+/// Namespace for checkpoint refs; hidden from `git branch` and `git tag`.
+pub const CHECKPOINT_REF_PREFIX: &str = "refs/agent-term/checkpoints";
+
+/// Oldest checkpoints beyond this count are pruned per session.
+pub const MAX_CHECKPOINTS_PER_SESSION: usize = 50;
+
+/// Metadata for a snapshot of a session's project tree.
+///
+/// Example:
+/// ```rust,ignore
+/// let checkpoint = CheckpointRecord {
+///     id: "checkpoint-1".to_string(),
+///     session_id: "session-1".to_string(),
+///     label: "input".to_string(),
+///     repo_path: "/home/user/project".to_string(),
+///     scope: "packages/api".to_string(),
+///     commit: "4b825dc642cb6eb9a060e54bf8d69288fbee4904".to_string(),
+///     git_ref: "refs/agent-term/checkpoints/session-1/checkpoint-1".to_string(),
+///     created_at: "2025-01-01T00:00:00Z".to_string(),
+/// };
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckpointRecord {
+    pub id: String,
+    pub session_id: String,
+    pub label: String,
+    pub repo_path: String,
+    /// Project directory relative to `repo_path` that the snapshot covers;
+    /// empty for the whole repository.
+    #[serde(default)]
+    pub scope: String,
+    pub commit: String,
+    pub git_ref: String,
+    pub created_at: String,
+}
+
+pub fn checkpoint_ref(session_id: &str, checkpoint_id: &str) -> String {
+    format!("{}/{}/{}", CHECKPOINT_REF_PREFIX, session_id, checkpoint_id)
+}
+
+/// Resolve the root of the git work tree containing `project_path`.
+pub fn repo_root(project_path: &str) -> Option<PathBuf> {
+    if project_path.trim().is_empty() {
+        return None;
+    }
+    git(Path::new(project_path), None, &["rev-parse", "--show-toplevel"])
+        .ok()
+        .map(|out| PathBuf::from(out.trim()))
+}
+
+/// `project_path` relative to its repository root, or empty when it is the root.
+pub fn project_scope(project_path: &str) -> Result<String, String> {
+    let prefix = git(Path::new(project_path), None, &["rev-parse", "--show-prefix"])?;
+    Ok(prefix.trim().trim_end_matches('/').to_string())
+}
+
+/// Pathspec limiting git to `scope`, matched literally.
+fn pathspec(scope: &str) -> String {
+    if scope.is_empty() {
+        ":(literal).".to_string()
+    } else {
+        format!(":(literal){}", scope)
+    }
+}
+
+/// Snapshot every tracked and untracked (non-ignored) file under `scope` of the
+/// repository at `root` into a commit pointed to by `git_ref`. Returns the
+/// commit id.
+pub fn snapshot(root: &Path, scope: &str, git_ref: &str, message: &str) -> Result<String, String> {
+    let index = TempIndex::new(root, "snapshot")?;
+    index.seed_from_real_index(root);
+
+    git(root, Some(index.path()), &["add", "-A", "--", &pathspec(scope)])?;
+    let tree = git(root, Some(index.path()), &["write-tree"])?;
+    let tree = tree.trim();
+
+    let mut args = vec!["commit-tree", tree, "-m", message];
+    let head = git(root, None, &["rev-parse", "--verify", "-q", "HEAD"]).ok();
+    if let Some(head) = head.as_deref() {
+        args.push("-p");
+        args.push(head.trim());
+    }
+    let commit = git(root, None, &args)?;
+    let commit = commit.trim().to_string();
+
+    git(root, None, &["update-ref", git_ref, &commit])?;
+    diagnostics::log(format!(
+        "checkpoint_snapshot ref={} commit={}",
+        git_ref, commit
+    ));
+    Ok(commit)
+}
+
+/// Rewrite the work tree under `scope` of the repository at `root` so it
+/// matches `commit`. Files there that were absent from the snapshot are
+/// removed; ignored files and everything outside `scope` are kept.
+pub fn restore(root: &Path, scope: &str, commit: &str) -> Result<(), String> {
+    let spec = pathspec(scope);
+    let current = list_paths(git(
+        root,
+        None,
+        &["ls-files", "-z", "--cached", "--others", "--exclude-standard", "--", &spec],
+    )?);
+    let mut tree_args = vec!["ls-tree", "-r", "-z", "--name-only", commit];
+    if !scope.is_empty() {
+        tree_args.extend(["--", scope]);
+    }
+    let wanted = list_paths(git(root, None, &tree_args)?);
+
+    if !wanted.is_empty() {
+        // Checked out through a throwaway index, so only `scope` is written.
+        let index = TempIndex::new(root, "restore")?;
+        git(root, Some(index.path()), &["checkout", commit, "--", &spec])?;
+    }
+
+    let mut removed = 0;
+    for path in current.difference(&wanted) {
+        let full = root.join(path);
+        if fs::remove_file(&full).is_ok() {
+            removed += 1;
+            remove_empty_parents(root, &full);
+        }
+    }
+    diagnostics::log(format!(
+        "checkpoint_restore commit={} files={} removed={}",
+        commit,
+        wanted.len(),
+        removed
+    ));
+    Ok(())
+}
+
+/// Delete a checkpoint ref so the snapshot can be garbage collected.
+pub fn delete_ref(root: &Path, git_ref: &str) -> Result<(), String> {
+    git(root, None, &["update-ref", "-d", git_ref]).map(|_| ())
+}
+
+fn list_paths(output: String) -> HashSet<String> {
+    output
+        .split('\0')
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| entry.to_string())
+        .collect()
+}
+
+fn remove_empty_parents(root: &Path, file: &Path) {
+    let mut dir = file.parent();
+    while let Some(current) = dir {
+        if current == root || !current.starts_with(root) {
+            break;
+        }
+        if fs::remove_dir(current).is_err() {
+            break;
+        }
+        dir = current.parent();
+    }
+}
+
+/// Index file inside the git dir, removed on drop.
+struct TempIndex {
+    path: PathBuf,
+}
+
+impl TempIndex {
+    fn new(root: &Path, purpose: &str) -> Result<Self, String> {
+        let git_dir = git(root, None, &["rev-parse", "--absolute-git-dir"])?;
+        let path = PathBuf::from(git_dir.trim()).join(format!(
+            "agent-term-{}-{}.index",
+            purpose,
+            uuid::Uuid::new_v4()
+        ));
+        Ok(Self { path })
+    }
+
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Start from the real index so `git add` can reuse cached stat data.
+    fn seed_from_real_index(&self, root: &Path) {
+        if let Ok(real) = git(root, None, &["rev-parse", "--git-path", "index"]) {
+            let real = root.join(real.trim());
+            if real.exists() {
+                let _ = fs::copy(real, &self.path);
+            }
+        }
+    }
+}
+
+impl Drop for TempIndex {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+fn git(dir: &Path, index: Option<&Path>, args: &[&str]) -> Result<String, String> {
+    let mut cmd = Command::new("git");
+    cmd.arg("-C").arg(dir).args(args);
+    if let Some(index) = index {
+        cmd.env("GIT_INDEX_FILE", index);
+    }
+    // Snapshots must not depend on the user having a git identity configured.
+    cmd.env("GIT_AUTHOR_NAME", "agent-term")
+        .env("GIT_AUTHOR_EMAIL", "agent-term@localhost")
+        .env("GIT_COMMITTER_NAME", "agent-term")
+        .env("GIT_COMMITTER_EMAIL", "agent-term@localhost");
+    let output = cmd
+        .output()
+        .map_err(|e| format!("failed to run git: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "git {} failed: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn init_repo() -> TempDir {
+        let temp = TempDir::new().unwrap();
+        git(temp.path(), None, &["init", "-q"]).unwrap();
+        temp
+    }
+
+    #[test]
+    fn test_repo_root_outside_git() {
+        let temp = TempDir::new().unwrap();
+        assert!(repo_root(&temp.path().display().to_string()).is_none());
+        assert!(repo_root("").is_none());
+    }
+
+    #[test]
+    fn test_snapshot_and_restore_roundtrip() {
+        let repo = init_repo();
+        let root = repo_root(&repo.path().display().to_string()).unwrap();
+        fs::write(root.join("main.rs"), "fn main() {}\n").unwrap();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/lib.rs"), "pub fn a() {}\n").unwrap();
+
+        let git_ref = checkpoint_ref("session-1", "cp-1");
+        let commit = snapshot(&root, "", &git_ref, "checkpoint").unwrap();
+        assert!(!commit.is_empty());
+
+        fs::write(root.join("main.rs"), "broken").unwrap();
+        fs::remove_file(root.join("src/lib.rs")).unwrap();
+        fs::create_dir_all(root.join("junk")).unwrap();
+        fs::write(root.join("junk/new.txt"), "agent output").unwrap();
+
+        restore(&root, "", &commit).unwrap();
+
+        assert_eq!(fs::read_to_string(root.join("main.rs")).unwrap(), "fn main() {}\n");
+        assert_eq!(fs::read_to_string(root.join("src/lib.rs")).unwrap(), "pub fn a() {}\n");
+        assert!(!root.join("junk/new.txt").exists());
+        assert!(!root.join("junk").exists());
+    }
+
+    #[test]
+    fn test_restore_stays_inside_the_project_scope() {
+        let repo = init_repo();
+        let root = repo_root(&repo.path().display().to_string()).unwrap();
+        fs::create_dir_all(root.join("packages/api")).unwrap();
+        fs::create_dir_all(root.join("packages/web")).unwrap();
+        fs::write(root.join("packages/api/main.rs"), "fn main() {}\n").unwrap();
+        fs::write(root.join("packages/web/app.ts"), "v1").unwrap();
+
+        let project = root.join("packages/api").display().to_string();
+        assert_eq!(repo_root(&project).unwrap(), root);
+        let scope = project_scope(&project).unwrap();
+        assert_eq!(scope, "packages/api");
+        assert_eq!(project_scope(&root.display().to_string()).unwrap(), "");
+        let commit = snapshot(&root, &scope, &checkpoint_ref("session-1", "cp-1"), "checkpoint").unwrap();
+
+        // Another session's work elsewhere in the repository.
+        fs::write(root.join("packages/web/app.ts"), "v2").unwrap();
+        fs::write(root.join("packages/web/new.ts"), "new").unwrap();
+        fs::write(root.join("packages/api/main.rs"), "broken").unwrap();
+        fs::write(root.join("packages/api/junk.rs"), "junk").unwrap();
+
+        restore(&root, &scope, &commit).unwrap();
+
+        assert_eq!(fs::read_to_string(root.join("packages/api/main.rs")).unwrap(), "fn main() {}\n");
+        assert!(!root.join("packages/api/junk.rs").exists());
+        assert_eq!(fs::read_to_string(root.join("packages/web/app.ts")).unwrap(), "v2");
+        assert!(root.join("packages/web/new.ts").exists());
+    }
+
+    #[test]
+    fn test_snapshot_leaves_user_index_untouched() {
+        let repo = init_repo();
+        let root = repo_root(&repo.path().display().to_string()).unwrap();
+        fs::write(root.join("untracked.txt"), "hello").unwrap();
+
+        snapshot(&root, "", &checkpoint_ref("session-1", "cp-1"), "checkpoint").unwrap();
+
+        let staged = git(&root, None, &["ls-files", "--cached"]).unwrap();
+        assert!(staged.trim().is_empty());
+        let branches = git(&root, None, &["branch", "--list"]).unwrap();
+        assert!(branches.trim().is_empty());
+    }
+
+    #[test]
+    fn test_delete_ref() {
+        let repo = init_repo();
+        let root = repo_root(&repo.path().display().to_string()).unwrap();
+        fs::write(root.join("a.txt"), "a").unwrap();
+        let git_ref = checkpoint_ref("session-1", "cp-1");
+        snapshot(&root, "", &git_ref, "checkpoint").unwrap();
+
+        delete_ref(&root, &git_ref).unwrap();
+        assert!(git(&root, None, &["rev-parse", "--verify", "-q", &git_ref]).is_err());
+    }
+}
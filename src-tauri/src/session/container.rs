@@ -0,0 +1,252 @@
+// ABOUTME: Runs a session's tool inside a docker or podman container attached to the session PTY.
+// ABOUTME: Mounts the project, MCP config and tool state at their host paths and stops the container on shutdown.
+
+use std::path::PathBuf;
+
+use super::model::{ContainerEngine, SessionContainer};
+use super::tools::CommandSpec;
+use crate::diagnostics;
+use crate::mcp::config::{get_agent_term_mcp_dir, get_agent_term_mcp_run_dir, ResourceLimits};
+use crate::mcp::proxy::proxy_install_path;
+
+/// Where the host's MCP proxy binary is mounted; on PATH in practically every image.
+/// Only a Linux host's binary can run inside a (Linux) container, so it is
+/// mounted from Linux hosts only.
+const CONTAINER_PROXY_PATH: &str = "/usr/local/bin/agentterm-mcp-proxy";
+
+pub fn container_name(session_id: &str) -> String {
+    format!("agentterm-{}", session_id)
+}
+
+pub fn validate(container: &SessionContainer) -> Result<(), String> {
+    if container.image.trim().is_empty() {
+        return Err("Container image is empty".to_string());
+    }
+    Ok(())
+}
+
+/// Rewrite `spec` as `<engine> run --rm -it ...` for the session's container.
+/// Paths are mounted at the same location so host paths in args and env keep working.
+pub fn wrap(
+    spec: CommandSpec,
+    container: &SessionContainer,
+    session_id: &str,
+    project_path: &str,
+    tool_paths: &[PathBuf],
+    limits: &ResourceLimits,
+) -> Result<CommandSpec, String> {
+    validate(container)?;
+    if project_path.trim().is_empty() {
+        return Err("Container sessions need a project path".to_string());
+    }
+    let project = std::fs::canonicalize(project_path)
+        .map_err(|e| format!("Invalid path '{}': {}", project_path, e))?
+        .display()
+        .to_string();
+
+    let mut mounts = vec![format!("{}:{}", project, project)];
+    // MCP configs and the pool sockets only: the rest of ~/.agent-term holds
+    // the control socket, which must not be reachable from the container.
+    let agent_term_dirs = [get_agent_term_mcp_dir(), get_agent_term_mcp_run_dir()];
+    for dir in agent_term_dirs.into_iter().flatten() {
+        if dir.exists() {
+            mounts.push(format!("{}:{}:ro", dir.display(), dir.display()));
+        }
+    }
+    if let Ok(proxy) = proxy_install_path() {
+        if cfg!(target_os = "linux") && proxy.exists() {
+            mounts.push(format!("{}:{}:ro", proxy.display(), CONTAINER_PROXY_PATH));
+        }
+    }
+    for path in tool_paths {
+        if path.exists() {
+            mounts.push(format!("{}:{}", path.display(), path.display()));
+        } else {
+            diagnostics::log(format!("container_mount_missing path={}", path.display()));
+        }
+    }
+
+    let mut env = vec![
+        ("TERM".to_string(), "xterm-256color".to_string()),
+        ("COLORTERM".to_string(), "truecolor".to_string()),
+    ];
+    if let Some(home) = dirs::home_dir() {
+        env.push(("HOME".to_string(), home.display().to_string()));
+    }
+    // The host PATH is meaningless inside the image.
+    env.extend(spec.env.iter().filter(|(key, _)| key != "PATH").cloned());
+
+    // Values go in the engine's own environment and are passed on by name, so
+    // secrets don't show up in the process list.
+    Ok(CommandSpec {
+        program: container.engine.program().to_string(),
+        args: run_args(container, session_id, &project, &mounts, &env, limits, spec),
+        env,
+    })
+}
+
+fn run_args(
+    container: &SessionContainer,
+    session_id: &str,
+    project: &str,
+    mounts: &[String],
+    env: &[(String, String)],
+    limits: &ResourceLimits,
+    spec: CommandSpec,
+) -> Vec<String> {
+    let mut args: Vec<String> = vec![
+        "run".to_string(),
+        "--rm".to_string(),
+        "-it".to_string(),
+        "--name".to_string(),
+        container_name(session_id),
+        "-w".to_string(),
+        project.to_string(),
+    ];
+    args.extend(user_args(container.engine));
+    for mount in mounts {
+        args.extend(["-v".to_string(), mount.clone()]);
+    }
+    for (key, _) in env {
+        args.extend(["-e".to_string(), key.clone()]);
+    }
+    // The engine enforces limits itself rather than an in-container `ulimit`.
+    if let Some(memory_mb) = limits.memory_mb {
+        args.extend(["--memory".to_string(), format!("{}m", memory_mb)]);
+    }
+    if let Some(cpu_seconds) = limits.cpu_seconds {
+        args.extend(["--ulimit".to_string(), format!("cpu={}", cpu_seconds)]);
+    }
+    if let Some(open_files) = limits.open_files {
+        args.extend(["--ulimit".to_string(), format!("nofile={}:{}", open_files, open_files)]);
+    }
+    args.extend(container.run_args.iter().cloned());
+    args.push(container.image.clone());
+    args.push(spec.program);
+    args.extend(spec.args);
+    args
+}
+
+/// Run as the host user so files written to the project keep their owner.
+#[cfg(unix)]
+fn user_args(engine: ContainerEngine) -> Vec<String> {
+    match engine {
+        ContainerEngine::Podman => vec!["--userns=keep-id".to_string()],
+        ContainerEngine::Docker => vec![
+            "--user".to_string(),
+            format!("{}:{}", users::get_current_uid(), users::get_current_gid()),
+        ],
+    }
+}
+
+#[cfg(not(unix))]
+fn user_args(_engine: ContainerEngine) -> Vec<String> {
+    Vec::new()
+}
+
+/// Remove a container left behind under the session's name, e.g. after the
+/// app crashed, so `run --name` doesn't fail on the conflict.
+pub fn remove_stale(container: &SessionContainer, session_id: &str) {
+    let name = container_name(session_id);
+    let result = std::process::Command::new(container.engine.program())
+        .args(["rm", "-f", &name])
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status();
+    if let Err(err) = result {
+        diagnostics::log(format!("container_remove_stale_failed name={} error={}", name, err));
+    }
+}
+
+/// Command that stops the session's container; `--rm` then removes it.
+pub fn stop_command(container: &SessionContainer, session_id: &str) -> Vec<String> {
+    vec![
+        container.engine.program().to_string(),
+        "stop".to_string(),
+        "--time".to_string(),
+        "5".to_string(),
+        container_name(session_id),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn container(engine: ContainerEngine) -> SessionContainer {
+        SessionContainer {
+            engine,
+            image: "ghcr.io/acme/agent:latest".to_string(),
+            run_args: vec!["--network".to_string(), "none".to_string()],
+        }
+    }
+
+    fn spec() -> CommandSpec {
+        CommandSpec {
+            program: "claude".to_string(),
+            args: vec!["--resume".to_string(), "abc".to_string()],
+            env: vec![
+                ("PATH".to_string(), "/host/bin".to_string()),
+                ("CLAUDE_CONFIG_DIR".to_string(), "/home/me/.claude".to_string()),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_wrap_builds_attached_run_command() {
+        let project = TempDir::new().unwrap();
+        let state = TempDir::new().unwrap();
+        let limits = ResourceLimits {
+            memory_mb: Some(4096),
+            cpu_seconds: None,
+            open_files: None,
+        };
+        let wrapped = wrap(
+            spec(),
+            &container(ContainerEngine::Docker),
+            "session-1",
+            &project.path().display().to_string(),
+            &[state.path().to_path_buf()],
+            &limits,
+        )
+        .unwrap();
+
+        let project = project.path().canonicalize().unwrap().display().to_string();
+        let state = state.path().display().to_string();
+        assert_eq!(wrapped.program, "docker");
+        assert!(wrapped
+            .env
+            .contains(&("CLAUDE_CONFIG_DIR".to_string(), "/home/me/.claude".to_string())));
+        let args = wrapped.args.join(" ");
+        assert!(args.starts_with(&format!(
+            "run --rm -it --name agentterm-session-1 -w {}",
+            project
+        )));
+        assert!(args.contains(&format!("-v {}:{}", project, project)));
+        assert!(args.contains(&format!("-v {}:{}", state, state)));
+        assert!(args.contains("-e CLAUDE_CONFIG_DIR "));
+        assert!(!args.contains("/home/me/.claude "));
+        assert!(!args.contains("-e PATH"));
+        assert!(args.contains("--memory 4096m"));
+        assert!(args.ends_with("--network none ghcr.io/acme/agent:latest claude --resume abc"));
+    }
+
+    #[test]
+    fn test_wrap_requires_image_and_project() {
+        let mut no_image = container(ContainerEngine::Podman);
+        no_image.image = " ".to_string();
+        let limits = ResourceLimits::default();
+        assert!(wrap(spec(), &no_image, "s", "/tmp", &[], &limits).is_err());
+        assert!(wrap(spec(), &container(ContainerEngine::Podman), "s", "", &[], &limits).is_err());
+    }
+
+    #[test]
+    fn test_stop_command_targets_named_container() {
+        assert_eq!(
+            stop_command(&container(ContainerEngine::Podman), "session-1"),
+            vec!["podman", "stop", "--time", "5", "agentterm-session-1"]
+        );
+    }
+}
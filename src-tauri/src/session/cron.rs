@@ -0,0 +1,216 @@
+// ABOUTME: Parses five-field cron expressions and computes their next fire time.
+// ABOUTME: Schedules are evaluated in UTC at minute granularity.
+
+use time::{Duration, OffsetDateTime, Time};
+
+/// A parsed cron expression (`minute hour day-of-month month day-of-week`).
+///
+/// Example:
+/// ```rust,ignore
+/// let nightly = CronSchedule::parse("30 2 * * *")?;
+/// let next = nightly.next_after(OffsetDateTime::now_utc());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    dom_restricted: bool,
+    dow_restricted: bool,
+}
+
+impl CronSchedule {
+    pub fn parse(expr: &str) -> Result<Self, String> {
+        let expanded = match expr.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            other => other,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(format!(
+                "cron expression must have 5 fields, got {}: {}",
+                fields.len(),
+                expr
+            ));
+        }
+
+        let mut days_of_week = parse_field(fields[4], 0, 7)?;
+        // Both 0 and 7 mean Sunday.
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week = (days_of_week | 1) & !(1 << 7);
+        }
+
+        Ok(Self {
+            minutes: parse_field(fields[0], 0, 59)?,
+            hours: parse_field(fields[1], 0, 23)?,
+            days_of_month: parse_field(fields[2], 1, 31)?,
+            months: parse_field(fields[3], 1, 12)?,
+            days_of_week,
+            dom_restricted: fields[2] != "*",
+            dow_restricted: fields[4] != "*",
+        })
+    }
+
+    /// First matching minute strictly after `after`, searching up to four years ahead.
+    pub fn next_after(&self, after: OffsetDateTime) -> Option<OffsetDateTime> {
+        let mut t = after.replace_second(0).ok()?.replace_nanosecond(0).ok()? + Duration::minutes(1);
+        let limit = after + Duration::days(366 * 4);
+        while t <= limit {
+            if !self.day_matches(t) {
+                t = t.replace_time(Time::MIDNIGHT) + Duration::days(1);
+                continue;
+            }
+            if !bit(self.hours, t.hour()) {
+                t = t.replace_minute(0).ok()? + Duration::hours(1);
+                continue;
+            }
+            if !bit(self.minutes, t.minute()) {
+                t += Duration::minutes(1);
+                continue;
+            }
+            return Some(t);
+        }
+        None
+    }
+
+    fn day_matches(&self, t: OffsetDateTime) -> bool {
+        if !bit(self.months, u8::from(t.month())) {
+            return false;
+        }
+        let dom = bit(self.days_of_month, t.day());
+        let dow = bit(self.days_of_week, t.weekday().number_days_from_sunday());
+        // Standard cron: when both day fields are restricted, either may match.
+        match (self.dom_restricted, self.dow_restricted) {
+            (true, true) => dom || dow,
+            (true, false) => dom,
+            (false, true) => dow,
+            (false, false) => true,
+        }
+    }
+}
+
+fn bit(mask: u64, value: u8) -> bool {
+    mask & (1 << value) != 0
+}
+
+fn parse_field(field: &str, min: u8, max: u8) -> Result<u64, String> {
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u8 = step
+                    .parse()
+                    .map_err(|_| format!("invalid cron step: {}", part))?;
+                if step == 0 {
+                    return Err(format!("invalid cron step: {}", part));
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((a, b)) = range.split_once('-') {
+            (parse_value(a, min, max)?, parse_value(b, min, max)?)
+        } else {
+            let value = parse_value(range, min, max)?;
+            // "5/15" means from 5 to the end of the range.
+            if part.contains('/') {
+                (value, max)
+            } else {
+                (value, value)
+            }
+        };
+        if start > end {
+            return Err(format!("invalid cron range: {}", part));
+        }
+        let mut value = start;
+        while value <= end {
+            mask |= 1 << value;
+            value = match value.checked_add(step) {
+                Some(next) => next,
+                None => break,
+            };
+        }
+    }
+    Ok(mask)
+}
+
+fn parse_value(value: &str, min: u8, max: u8) -> Result<u8, String> {
+    let parsed: u8 = value
+        .parse()
+        .map_err(|_| format!("invalid cron value: {}", value))?;
+    if parsed < min || parsed > max {
+        return Err(format!(
+            "cron value {} out of range {}-{}",
+            parsed, min, max
+        ));
+    }
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::{Date, Month};
+
+    fn at(year: i32, month: Month, day: u8, hour: u8, minute: u8) -> OffsetDateTime {
+        Date::from_calendar_date(year, month, day)
+            .unwrap()
+            .with_hms(hour, minute, 0)
+            .unwrap()
+            .assume_utc()
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_expressions() {
+        assert!(CronSchedule::parse("* * * *").is_err());
+        assert!(CronSchedule::parse("60 * * * *").is_err());
+        assert!(CronSchedule::parse("*/0 * * * *").is_err());
+        assert!(CronSchedule::parse("5-1 * * * *").is_err());
+        assert!(CronSchedule::parse("a * * * *").is_err());
+    }
+
+    #[test]
+    fn test_next_after_hourly() {
+        let schedule = CronSchedule::parse("@hourly").unwrap();
+        let next = schedule.next_after(at(2025, Month::January, 1, 10, 15)).unwrap();
+        assert_eq!(next, at(2025, Month::January, 1, 11, 0));
+    }
+
+    #[test]
+    fn test_next_after_nightly_rolls_to_next_day() {
+        let schedule = CronSchedule::parse("30 2 * * *").unwrap();
+        let next = schedule.next_after(at(2025, Month::January, 31, 2, 30)).unwrap();
+        assert_eq!(next, at(2025, Month::February, 1, 2, 30));
+    }
+
+    #[test]
+    fn test_next_after_steps_and_lists() {
+        let schedule = CronSchedule::parse("*/20 9-17 * * 1,3,5").unwrap();
+        // 2025-01-04 is a Saturday; the next match is Monday 09:00.
+        let next = schedule.next_after(at(2025, Month::January, 4, 12, 0)).unwrap();
+        assert_eq!(next, at(2025, Month::January, 6, 9, 0));
+        let next = schedule.next_after(next).unwrap();
+        assert_eq!(next, at(2025, Month::January, 6, 9, 20));
+    }
+
+    #[test]
+    fn test_sunday_as_seven_and_dom_dow_union() {
+        let sunday = CronSchedule::parse("0 0 * * 7").unwrap();
+        let next = sunday.next_after(at(2025, Month::January, 1, 0, 0)).unwrap();
+        assert_eq!(next, at(2025, Month::January, 5, 0, 0));
+
+        let either = CronSchedule::parse("0 0 15 * 0").unwrap();
+        let next = either.next_after(at(2025, Month::January, 6, 0, 0)).unwrap();
+        assert_eq!(next, at(2025, Month::January, 12, 0, 0));
+        let next = either.next_after(next).unwrap();
+        assert_eq!(next, at(2025, Month::January, 15, 0, 0));
+    }
+}
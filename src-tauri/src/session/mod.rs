@@ -1,29 +1,59 @@
 // ABOUTME: Coordinates session metadata, runtime management, and Tauri commands for terminals.
 // ABOUTME: Starts, stops, and persists sessions while emitting events to the frontend.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::io::Read;
-use std::sync::mpsc;
+use std::path::Path;
+use std::sync::mpsc::{self, TryRecvError};
 use std::thread;
+use std::time::{Duration, Instant};
 
+use agentterm_shared::control::CALLER_TOKEN_ENV;
+use agentterm_shared::pool::ClientInfo;
 use parking_lot::Mutex;
 use portable_pty::{CommandBuilder, NativePtySystem, PtySize, PtySystem};
 use serde::Serialize;
-use tauri::{AppHandle, Emitter, State};
+use tauri::{AppHandle, Emitter, Manager, State};
+use time::OffsetDateTime;
 use uuid::Uuid;
 
 use crate::diagnostics;
+use crate::mcp::config::IdleSettings;
 use crate::mcp::{McpManager, McpScope};
 
+mod bundle;
+mod checkpoint;
+mod container;
+mod cron;
 mod error;
 mod model;
+mod monitor;
+mod reaper;
+mod remote;
 mod runtime;
+mod sandbox;
+mod scheduler;
+mod screen;
 mod status;
 mod storage;
 mod tools;
 
-pub use model::{NewSessionInput, SectionRecord, SessionRecord, SessionStatus};
+pub use bundle::{
+    BundlePreview, BundledSession, ExportBundleInput, ImportBundleInput, ImportBundleResult,
+    PreviewSection, PreviewSession, RedactedEnvVar, SessionBundle,
+};
+pub use checkpoint::CheckpointRecord;
+pub use model::{
+    BroadcastResult, NewScheduleInput, NewSessionInput, QueuedPrompt, ScheduleOutcome,
+    ScheduleRecord, ScheduleRun, ScheduleTarget, SectionDefaults, SectionRecord, SessionContainer,
+    SessionRecord, SessionRemote, SessionSandbox, SessionStatus, SessionTool,
+};
+pub use monitor::{spawn_process_monitor, SessionUsage};
+pub use reaper::spawn_idle_reaper;
+pub use scheduler::spawn_scheduler;
+use monitor::ProcessMonitor;
 use runtime::SessionRuntime;
+use screen::ScreenBuffer;
 use status::{extract_session_id, prompt_detector, status_tracker, ExtractedSessionId};
 use storage::{default_storage_root, DebouncedStorage, Storage, StorageSnapshot};
 use tools::build_command;
@@ -40,6 +70,30 @@ fn validate_path(path: &str) -> Result<std::path::PathBuf, String> {
         .map_err(|e| format!("Invalid path '{}': {}", path, e))
 }
 
+/// Check that `id` exists and can be nested under `parent_id` without a cycle.
+fn check_section_parent(
+    sections: &[SectionRecord],
+    id: &str,
+    parent_id: Option<&str>,
+) -> Result<(), String> {
+    if !sections.iter().any(|section| section.id == id) {
+        return Err(format!("Section not found: {}", id));
+    }
+    let mut current = parent_id;
+    while let Some(ancestor) = current {
+        if ancestor == id {
+            return Err("A section cannot be nested inside itself".to_string());
+        }
+        current = sections
+            .iter()
+            .find(|section| section.id == ancestor)
+            .ok_or_else(|| "Parent section not found".to_string())?
+            .parent_id
+            .as_deref();
+    }
+    Ok(())
+}
+
 fn redact_path(path: &str) -> String {
     if path.is_empty() {
         return String::new();
@@ -50,6 +104,17 @@ fn redact_path(path: &str) -> String {
         .unwrap_or_else(|| "[redacted]".to_string())
 }
 
+/// Sections and sessions created by an import, and the env vars it left out.
+type ImportedRecords = (Vec<SectionRecord>, Vec<SessionRecord>, Vec<RedactedEnvVar>);
+
+/// Delete the git refs of dropped checkpoints. Runs git, so callers release
+/// the snapshot lock first.
+fn delete_checkpoint_refs(checkpoints: &[CheckpointRecord]) {
+    for checkpoint in checkpoints {
+        let _ = checkpoint::delete_ref(std::path::Path::new(&checkpoint.repo_path), &checkpoint.git_ref);
+    }
+}
+
 #[derive(Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 struct SessionOutput {
@@ -70,6 +135,37 @@ struct SessionStatusEvent {
     status: SessionStatus,
 }
 
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CheckpointEvent {
+    session_id: String,
+    checkpoint: CheckpointRecord,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PromptDeliveredEvent {
+    session_id: String,
+    prompt: QueuedPrompt,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionReapedEvent {
+    session_id: String,
+    title: String,
+    idle_minutes: u64,
+    reason: String,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RemoteReconnectEvent {
+    session_id: String,
+    attempt: u32,
+    gave_up: bool,
+}
+
 #[derive(Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 struct ToolSessionIdEvent {
@@ -78,6 +174,9 @@ struct ToolSessionIdEvent {
     tool: String,
 }
 
+/// How far up the process tree `session_for_pid` looks for a session.
+const MAX_PROCESS_DEPTH: usize = 64;
+
 /// Coordinates session metadata and runtime state.
 ///
 /// Example:
@@ -89,6 +188,18 @@ pub struct SessionManager {
     storage: DebouncedStorage,
     snapshot: Mutex<StorageSnapshot>,
     runtimes: Mutex<HashMap<String, SessionRuntime>>,
+    checkpoint_armed: Mutex<HashSet<String>>,
+    live_statuses: Mutex<HashMap<String, SessionStatus>>,
+    /// Sessions that were sent a queued prompt and have not finished working on it.
+    prompts_in_flight: Mutex<HashSet<String>>,
+    /// Running-to-waiting transitions per session, i.e. turns of work finished.
+    finished_turns: Mutex<HashMap<String, u64>>,
+    /// Secret handed to each running session to authenticate its control API calls.
+    caller_tokens: Mutex<HashMap<String, String>>,
+    last_activity: Mutex<HashMap<String, Instant>>,
+    monitor: ProcessMonitor,
+    reconnect_attempts: Mutex<HashMap<String, u32>>,
+    screens: Mutex<HashMap<String, ScreenBuffer>>,
 }
 
 pub fn build_session_manager() -> Result<SessionManager, String> {
@@ -100,6 +211,15 @@ pub fn build_session_manager() -> Result<SessionManager, String> {
         storage: debounced,
         snapshot: Mutex::new(snapshot),
         runtimes: Mutex::new(HashMap::new()),
+        checkpoint_armed: Mutex::new(HashSet::new()),
+        live_statuses: Mutex::new(HashMap::new()),
+        prompts_in_flight: Mutex::new(HashSet::new()),
+        finished_turns: Mutex::new(HashMap::new()),
+        caller_tokens: Mutex::new(HashMap::new()),
+        last_activity: Mutex::new(HashMap::new()),
+        monitor: ProcessMonitor::default(),
+        reconnect_attempts: Mutex::new(HashMap::new()),
+        screens: Mutex::new(HashMap::new()),
     })
 }
 
@@ -125,7 +245,31 @@ impl SessionManager {
     }
 
     pub fn create_session(&self, input: NewSessionInput) -> Result<SessionRecord, String> {
+        self.create_session_as(input, None)
+    }
+
+    /// Create a session on behalf of another session's agent.
+    pub fn create_agent_session(
+        &self,
+        input: NewSessionInput,
+        caller_id: &str,
+    ) -> Result<SessionRecord, String> {
+        self.create_session_as(input, Some(caller_id.to_string()))
+    }
+
+    fn create_session_as(
+        &self,
+        input: NewSessionInput,
+        created_by: Option<String>,
+    ) -> Result<SessionRecord, String> {
         validate_path(&input.project_path)?;
+        // An empty command takes the section's default.
+        let command = if input.command.trim().is_empty() {
+            self.default_command(&input.section_id, &input.tool).unwrap_or_default()
+        } else {
+            input.command
+        };
+        let defaults = self.section_defaults(&input.section_id);
         let id = Uuid::new_v4().to_string();
         let record = SessionRecord {
             id: id.clone(),
@@ -133,7 +277,7 @@ impl SessionManager {
             project_path: input.project_path,
             section_id: input.section_id,
             tool: input.tool,
-            command: input.command,
+            command,
             icon: input.icon,
             status: SessionStatus::Idle,
             created_at: chrono_now(),
@@ -143,6 +287,11 @@ impl SessionManager {
             loaded_mcp_names: Vec::new(),
             is_open: true,
             tab_order: Some(self.next_tab_order()),
+            sandbox: None,
+            container: None,
+            remote: None,
+            created_by,
+            env: defaults.env,
         };
         diagnostics::log(format!(
             "create_session id={} title={} tool={:?} command={} project_path={} section_id={}",
@@ -192,6 +341,60 @@ impl SessionManager {
         self.storage.save(&snapshot).map_err(|e| e.to_string())
     }
 
+    /// Override the tool's sandbox for one session; `None` falls back to the tool's.
+    /// Takes effect on the next start.
+    pub fn set_session_sandbox(
+        &self,
+        id: &str,
+        sandbox: Option<SessionSandbox>,
+    ) -> Result<(), String> {
+        if let Some(sandbox) = &sandbox {
+            sandbox::validate(sandbox)?;
+        }
+        let mut snapshot = self.snapshot.lock();
+        let session = snapshot
+            .sessions
+            .iter_mut()
+            .find(|session| session.id == id)
+            .ok_or_else(|| "Session not found".to_string())?;
+        session.sandbox = sandbox;
+        self.storage.save(&snapshot).map_err(|e| e.to_string())
+    }
+
+    /// Run the session's tool in a container, or on the host again with `None`.
+    /// Takes effect on the next start.
+    pub fn set_session_container(
+        &self,
+        id: &str,
+        container: Option<SessionContainer>,
+    ) -> Result<(), String> {
+        if let Some(container) = &container {
+            container::validate(container)?;
+        }
+        let mut snapshot = self.snapshot.lock();
+        let session = snapshot
+            .sessions
+            .iter_mut()
+            .find(|session| session.id == id)
+            .ok_or_else(|| "Session not found".to_string())?;
+        session.container = container;
+        self.storage.save(&snapshot).map_err(|e| e.to_string())
+    }
+
+    pub fn set_session_remote(&self, id: &str, remote: Option<SessionRemote>) -> Result<(), String> {
+        if let Some(remote) = &remote {
+            remote::validate(remote)?;
+        }
+        let mut snapshot = self.snapshot.lock();
+        let session = snapshot
+            .sessions
+            .iter_mut()
+            .find(|session| session.id == id)
+            .ok_or_else(|| "Session not found".to_string())?;
+        session.remote = remote;
+        self.storage.save(&snapshot).map_err(|e| e.to_string())
+    }
+
     pub fn delete_session(&self, id: &str) -> Result<(), String> {
         diagnostics::log(format!(
             "delete_session begin id={} os={}",
@@ -199,7 +402,8 @@ impl SessionManager {
             std::env::consts::OS
         ));
 
-        if let Some(mut runtime) = self.runtimes.lock().remove(id) {
+        let runtime = self.runtimes.lock().remove(id);
+        if let Some(mut runtime) = runtime {
             diagnostics::log(format!(
                 "delete_session runtime_found id={} os={}",
                 id,
@@ -214,9 +418,32 @@ impl SessionManager {
             ));
         }
 
+        self.checkpoint_armed.lock().remove(id);
+        self.live_statuses.lock().remove(id);
+        self.prompts_in_flight.lock().remove(id);
+        self.finished_turns.lock().remove(id);
+        self.caller_tokens.lock().remove(id);
+        self.last_activity.lock().remove(id);
+        self.reconnect_attempts.lock().remove(id);
+        self.screens.lock().remove(id);
         let mut snapshot = self.snapshot.lock();
         snapshot.sessions.retain(|session| session.id != id);
+        snapshot.prompt_queues.remove(id);
+        for section in snapshot.sections.iter_mut() {
+            section.defaults.auto_start.retain(|session_id| session_id != id);
+        }
+        // A schedule that reruns this session has nothing left to run.
+        snapshot.schedules.retain(|schedule| {
+            !matches!(&schedule.target, ScheduleTarget::Session { session_id } if session_id == id)
+        });
+        let (removed, kept): (Vec<_>, Vec<_>) = std::mem::take(&mut snapshot.checkpoints)
+            .into_iter()
+            .partition(|checkpoint| checkpoint.session_id == id);
+        snapshot.checkpoints = kept;
         let result = self.storage.save(&snapshot).map_err(|e| e.to_string());
+        drop(snapshot);
+        // git runs outside the snapshot lock.
+        delete_checkpoint_refs(&removed);
         if let Err(ref err) = result {
             diagnostics::log(format!(
                 "delete_session save_error id={} os={} err={}",
@@ -252,14 +479,26 @@ impl SessionManager {
         self.storage.save(&snapshot).map_err(|e| e.to_string())
     }
 
-    pub fn create_section(&self, name: String, path: String) -> Result<SectionRecord, String> {
+    pub fn create_section(
+        &self,
+        name: String,
+        path: String,
+        parent_id: Option<String>,
+    ) -> Result<SectionRecord, String> {
+        if let Some(parent_id) = &parent_id {
+            if !self.list_sections().iter().any(|section| &section.id == parent_id) {
+                return Err("Parent section not found".to_string());
+            }
+        }
         let section = SectionRecord {
             id: Uuid::new_v4().to_string(),
             name,
             path,
             icon: None,
             collapsed: false,
-            order: self.next_section_order(),
+            order: self.next_section_order(parent_id.as_deref()),
+            parent_id,
+            defaults: SectionDefaults::default(),
         };
         let mut snapshot = self.snapshot.lock();
         snapshot.sections.push(section.clone());
@@ -301,12 +540,211 @@ impl SessionManager {
         self.storage.save(&snapshot).map_err(|e| e.to_string())
     }
 
+    /// Delete a section. Its child sections and sessions move up to its parent,
+    /// if it has one.
     pub fn delete_section(&self, id: &str) -> Result<(), String> {
         let mut snapshot = self.snapshot.lock();
+        let parent_id = snapshot
+            .sections
+            .iter()
+            .find(|section| section.id == id)
+            .and_then(|section| section.parent_id.clone());
         snapshot.sections.retain(|section| section.id != id);
+        for section in snapshot.sections.iter_mut() {
+            if section.parent_id.as_deref() == Some(id) {
+                section.parent_id = parent_id.clone();
+            }
+        }
+        if let Some(parent_id) = &parent_id {
+            for session in snapshot.sessions.iter_mut() {
+                if session.section_id == id {
+                    session.section_id = parent_id.clone();
+                }
+            }
+        }
+        self.storage.save(&snapshot).map_err(|e| e.to_string())
+    }
+
+    /// Nest a section under `parent_id`, or make it top-level with `None`.
+    pub fn move_section(&self, id: &str, parent_id: Option<String>) -> Result<(), String> {
+        let order = self.next_section_order(parent_id.as_deref());
+        let mut snapshot = self.snapshot.lock();
+        check_section_parent(&snapshot.sections, id, parent_id.as_deref())?;
+        let section = snapshot
+            .sections
+            .iter_mut()
+            .find(|section| section.id == id)
+            .ok_or_else(|| "Section not found".to_string())?;
+        if section.parent_id != parent_id {
+            section.parent_id = parent_id;
+            section.order = order;
+        }
+        self.storage.save(&snapshot).map_err(|e| e.to_string())
+    }
+
+    /// Place `section_ids` under `parent_id` in the given order. Other sections
+    /// with that parent keep their relative order after them.
+    pub fn reorder_sections(
+        &self,
+        parent_id: Option<String>,
+        section_ids: &[String],
+    ) -> Result<(), String> {
+        let mut snapshot = self.snapshot.lock();
+        // Validate everything before touching any record.
+        for id in section_ids {
+            check_section_parent(&snapshot.sections, id, parent_id.as_deref())?;
+        }
+        let mut siblings: Vec<(u32, String)> = snapshot
+            .sections
+            .iter()
+            .filter(|section| {
+                section.parent_id == parent_id && !section_ids.contains(&section.id)
+            })
+            .map(|section| (section.order, section.id.clone()))
+            .collect();
+        siblings.sort();
+        let ordered = section_ids
+            .iter()
+            .cloned()
+            .chain(siblings.into_iter().map(|(_, id)| id));
+        for (order, id) in ordered.enumerate() {
+            if let Some(section) = snapshot.sections.iter_mut().find(|s| s.id == id) {
+                section.parent_id = parent_id.clone();
+                section.order = order as u32;
+            }
+        }
+        self.storage.save(&snapshot).map_err(|e| e.to_string())
+    }
+
+    /// Give `session_ids` the first tab positions in the given order. Other
+    /// sessions keep their relative order after them.
+    pub fn reorder_sessions(&self, session_ids: &[String]) -> Result<(), String> {
+        let mut snapshot = self.snapshot.lock();
+        for id in session_ids {
+            if !snapshot.sessions.iter().any(|session| &session.id == id) {
+                return Err(format!("Session not found: {}", id));
+            }
+        }
+        let mut rest: Vec<(u32, usize, String)> = snapshot
+            .sessions
+            .iter()
+            .enumerate()
+            .filter(|(_, session)| !session_ids.contains(&session.id))
+            .map(|(index, session)| (session.tab_order.unwrap_or(u32::MAX), index, session.id.clone()))
+            .collect();
+        rest.sort();
+        let ordered = session_ids
+            .iter()
+            .cloned()
+            .chain(rest.into_iter().map(|(_, _, id)| id));
+        for (order, id) in ordered.enumerate() {
+            if let Some(session) = snapshot.sessions.iter_mut().find(|s| s.id == id) {
+                session.tab_order = Some(order as u32);
+            }
+        }
+        self.storage.save(&snapshot).map_err(|e| e.to_string())
+    }
+
+    /// The section's default command for `tool`. A default command only fits the
+    /// section's default tool, or any tool when the section has none.
+    pub fn default_command(&self, section_id: &str, tool: &SessionTool) -> Option<String> {
+        let defaults = self.section_defaults(section_id);
+        match defaults.tool {
+            Some(default_tool) if &default_tool != tool => None,
+            _ => defaults.command,
+        }
+    }
+
+    pub fn set_section_defaults(&self, id: &str, defaults: SectionDefaults) -> Result<(), String> {
+        let mut snapshot = self.snapshot.lock();
+        let section = snapshot
+            .sections
+            .iter_mut()
+            .find(|section| section.id == id)
+            .ok_or_else(|| "Section not found".to_string())?;
+        section.defaults = defaults;
         self.storage.save(&snapshot).map_err(|e| e.to_string())
     }
 
+    /// Defaults a new session in `section_id` gets, merged down from the top-level
+    /// section. Unknown sections have no defaults.
+    pub fn section_defaults(&self, section_id: &str) -> SectionDefaults {
+        let sections = self.list_sections();
+        let mut chain = Vec::new();
+        let mut current = sections.iter().find(|section| section.id == section_id);
+        while let Some(section) = current {
+            // Guards against a cycle in a hand-edited snapshot.
+            if chain.len() > sections.len() {
+                break;
+            }
+            chain.push(section);
+            current = section
+                .parent_id
+                .as_ref()
+                .and_then(|parent| sections.iter().find(|s| &s.id == parent));
+        }
+
+        let mut resolved = SectionDefaults::default();
+        for section in chain.iter().rev() {
+            let defaults = &section.defaults;
+            if defaults.tool.is_some() {
+                resolved.tool = defaults.tool.clone();
+            }
+            if defaults.command.is_some() {
+                resolved.command = defaults.command.clone();
+            }
+            resolved.env.extend(defaults.env.clone());
+            for name in &defaults.mcp_names {
+                if !resolved.mcp_names.contains(name) {
+                    resolved.mcp_names.push(name.clone());
+                }
+            }
+        }
+        if let Some(section) = chain.first() {
+            resolved.auto_start = section.defaults.auto_start.clone();
+        }
+        resolved
+    }
+
+    /// Attach the MCPs a session's section asks for to the session's project.
+    pub fn attach_section_mcps(&self, mcp_manager: &McpManager, record: &SessionRecord) {
+        let names = self.section_defaults(&record.section_id).mcp_names;
+        if names.is_empty() || record.project_path.is_empty() {
+            return;
+        }
+        let project_path = Some(record.project_path.as_str());
+        let attached = tauri::async_runtime::block_on(
+            mcp_manager.get_attached_mcps(McpScope::Local, project_path),
+        )
+        .unwrap_or_default();
+        for name in names.iter().filter(|name| !attached.contains(name)) {
+            if let Err(err) = tauri::async_runtime::block_on(mcp_manager.attach_mcp(
+                McpScope::Local,
+                project_path,
+                name,
+            )) {
+                diagnostics::log(format!(
+                    "section_mcp_attach_failed session_id={} name={} error={}",
+                    record.id, name, err
+                ));
+            }
+        }
+    }
+
+    /// Start every session listed in a section's auto-start list.
+    pub fn auto_start_sessions(&self, app: &AppHandle) {
+        let ids: Vec<String> = self
+            .list_sections()
+            .into_iter()
+            .flat_map(|section| section.defaults.auto_start)
+            .collect();
+        for id in ids {
+            if let Err(err) = self.start_session(app, &id, None, None) {
+                diagnostics::log(format!("auto_start_failed id={} error={}", id, err));
+            }
+        }
+    }
+
     pub fn start_session(
         &self,
         app: &AppHandle,
@@ -314,7 +752,7 @@ impl SessionManager {
         rows: Option<u16>,
         cols: Option<u16>,
     ) -> Result<(), String> {
-        let record = self.get_session(id)?;
+        let mut record = self.get_session(id)?;
         diagnostics::log(format!(
             "start_session id={} tool={:?} cmd={} rows={:?} cols={:?} project_path={}",
             id, record.tool, record.command, rows, cols, redact_path(&record.project_path)
@@ -333,7 +771,19 @@ impl SessionManager {
             }
         }
 
-        let cmd_spec = build_command(&record)?;
+        let config = app
+            .state::<McpManager>()
+            .load_config_blocking()
+            .unwrap_or_default();
+        // Passed like the session's own env so it reaches sandboxes and containers.
+        let caller_token = Uuid::new_v4().to_string();
+        record
+            .env
+            .insert(CALLER_TOKEN_ENV.to_string(), caller_token.clone());
+        let cmd_spec = build_command(&record, &config)?;
+        if let Some(session_container) = &record.container {
+            container::remove_stale(session_container, id);
+        }
         diagnostics::log(format!(
             "start_session id={} command_spec program={} args={:?} env_keys={:?}",
             id,
@@ -458,11 +908,16 @@ impl SessionManager {
                             status: new_status,
                         },
                     );
+                    app.state::<SessionManager>()
+                        .on_status_changed(app, sid, new_status);
                 }
             };
 
+            // Stopped through the runtime: signalled, or the runtime is gone.
+            let mut stopped = false;
             loop {
-                if shutdown_rx.try_recv().is_ok() {
+                if !matches!(shutdown_rx.try_recv(), Err(TryRecvError::Empty)) {
+                    stopped = true;
                     break;
                 }
                 match reader.read(&mut buf) {
@@ -485,6 +940,9 @@ impl SessionManager {
                     }
                     Ok(n) => {
                         pending.extend_from_slice(&buf[..n]);
+                        app_clone
+                            .state::<SessionManager>()
+                            .record_screen_output(&session_id, &buf[..n]);
                         output_events += 1;
                         if output_events <= 3 {
                             diagnostics::log(format!(
@@ -553,9 +1011,18 @@ impl SessionManager {
                     }
                 }
             }
+
+            // The process went away on its own rather than through stop_session.
+            if !stopped && matches!(shutdown_rx.try_recv(), Err(TryRecvError::Empty)) {
+                let app = app_clone.clone();
+                thread::spawn(move || {
+                    app.state::<SessionManager>()
+                        .on_session_ended(&app, &session_id);
+                });
+            }
         });
 
-        let runtime = SessionRuntime::new(
+        let mut runtime = SessionRuntime::new(
             pair.master,
             writer,
             child,
@@ -563,8 +1030,16 @@ impl SessionManager {
             shutdown_tx,
             id.to_string(),
         );
+        if let Some(session_container) = &record.container {
+            runtime.set_stop_command(container::stop_command(session_container, id));
+        }
 
         self.runtimes.lock().insert(id.to_string(), runtime);
+        self.caller_tokens.lock().insert(id.to_string(), caller_token);
+        self.live_statuses
+            .lock()
+            .insert(id.to_string(), SessionStatus::Running);
+        self.last_activity.lock().insert(id.to_string(), Instant::now());
         self.update_session_status(id, SessionStatus::Running)?;
         Ok(())
     }
@@ -596,6 +1071,10 @@ impl SessionManager {
             ));
         }
 
+        self.live_statuses.lock().remove(id);
+        self.prompts_in_flight.lock().remove(id);
+        self.caller_tokens.lock().remove(id);
+        self.last_activity.lock().remove(id);
         let status_result = self.update_session_status(id, SessionStatus::Idle);
         if let Err(ref err) = status_result {
             diagnostics::log(format!(
@@ -685,6 +1164,14 @@ impl SessionManager {
     }
 
     pub fn write_session_input(&self, id: &str, data: &[u8]) -> Result<(), String> {
+        self.write_runtime(id, data)?;
+        self.touch_activity(id);
+        Ok(())
+    }
+
+    /// Write to the session's PTY without counting it as activity, for input
+    /// the app types on its own such as queued prompts.
+    fn write_runtime(&self, id: &str, data: &[u8]) -> Result<(), String> {
         if data.len() > 4096 {
             diagnostics::log(format!(
                 "write_session_input large payload id={} bytes={} (consider base64)",
@@ -696,7 +1183,170 @@ impl SessionManager {
         let runtime = runtimes
             .get_mut(id)
             .ok_or_else(|| "session not running".to_string())?;
-        runtime.write(data)
+        runtime.write(data)?;
+        drop(runtimes);
+        if data.contains(&b'\r') || data.contains(&b'\n') {
+            self.arm_checkpoint(id);
+        }
+        Ok(())
+    }
+
+    fn record_screen_output(&self, id: &str, data: &[u8]) {
+        self.screens
+            .lock()
+            .entry(id.to_string())
+            .or_default()
+            .push(data);
+    }
+
+    /// Last `lines` lines of a session's output with escape sequences removed.
+    pub fn read_output(&self, id: &str, lines: usize) -> Result<Vec<String>, String> {
+        self.get_session(id)?;
+        Ok(self
+            .screens
+            .lock()
+            .get(id)
+            .map(|screen| screen.tail(lines))
+            .unwrap_or_default())
+    }
+
+    /// The running session that was handed `token`.
+    pub fn session_for_caller_token(&self, token: &str) -> Option<String> {
+        self.caller_tokens
+            .lock()
+            .iter()
+            .find(|(_, session_token)| session_token.as_str() == token)
+            .map(|(id, _)| id.clone())
+    }
+
+    /// The running session whose process tree `pid` belongs to, found by walking
+    /// up its parents to a session's top-level process.
+    pub fn session_for_pid(&self, pid: u32) -> Option<String> {
+        let roots: HashMap<u32, String> = self
+            .runtimes
+            .lock()
+            .iter()
+            .filter_map(|(id, runtime)| runtime.pid().map(|root| (root, id.clone())))
+            .collect();
+        let mut current = pid;
+        // Bounded in case the process table changes under the walk.
+        for _ in 0..MAX_PROCESS_DEPTH {
+            if let Some(id) = roots.get(&current) {
+                return Some(id.clone());
+            }
+            current = monitor::parent_pid(current).filter(|parent| *parent > 1)?;
+        }
+        None
+    }
+
+    /// The session and project of a process, as the MCP pool should see them.
+    pub fn client_info_for_pid(&self, pid: u32) -> Option<ClientInfo> {
+        let id = self.session_for_pid(pid)?;
+        let project_path = self.get_session(&id).ok()?.project_path;
+        Some(ClientInfo {
+            project_path: tools::reported_project_path(&project_path),
+            session_id: Some(id),
+        })
+    }
+
+    /// Detected status of a running session, as last reported by its reader thread.
+    pub fn live_status(&self, id: &str) -> Option<SessionStatus> {
+        self.live_statuses.lock().get(id).copied()
+    }
+
+    pub fn is_session_running(&self, id: &str) -> bool {
+        self.runtimes.lock().contains_key(id)
+    }
+
+    /// When the session last showed activity, if it is running.
+    pub fn last_activity(&self, id: &str) -> Option<Instant> {
+        self.last_activity.lock().get(id).copied()
+    }
+
+    /// Send the same input to several sessions, addressed either by id or by section.
+    /// With `dry_run` nothing is written and the results list the resolved targets.
+    pub fn broadcast_session_input(
+        &self,
+        session_ids: Option<Vec<String>>,
+        section_id: Option<String>,
+        data: &[u8],
+        dry_run: bool,
+        only_waiting: bool,
+    ) -> Result<Vec<BroadcastResult>, String> {
+        let targets: Vec<(String, Option<String>)> = {
+            let snapshot = self.snapshot.lock();
+            match (session_ids, section_id) {
+                (Some(ids), None) => ids
+                    .into_iter()
+                    .map(|id| {
+                        let title = snapshot
+                            .sessions
+                            .iter()
+                            .find(|session| session.id == id)
+                            .map(|session| session.title.clone());
+                        (id, title)
+                    })
+                    .collect(),
+                (None, Some(section_id)) => {
+                    let mut sessions: Vec<&SessionRecord> = snapshot
+                        .sessions
+                        .iter()
+                        .filter(|session| session.section_id == section_id)
+                        .collect();
+                    sessions.sort_by_key(|session| session.tab_order.unwrap_or(u32::MAX));
+                    sessions
+                        .into_iter()
+                        .map(|session| (session.id.clone(), Some(session.title.clone())))
+                        .collect()
+                }
+                _ => {
+                    return Err("Provide either session ids or a section id".to_string());
+                }
+            }
+        };
+
+        diagnostics::log(format!(
+            "broadcast_session_input targets={} bytes={} dry_run={} only_waiting={}",
+            targets.len(),
+            data.len(),
+            dry_run,
+            only_waiting
+        ));
+
+        let results = targets
+            .into_iter()
+            .map(|(id, title)| {
+                let status = self.live_status(&id);
+                let mut result = BroadcastResult {
+                    session_id: id.clone(),
+                    title: title.clone().unwrap_or_default(),
+                    status,
+                    delivered: false,
+                    skipped: false,
+                    error: None,
+                };
+                let skip_reason = if title.is_none() {
+                    Some("Session not found")
+                } else if !self.runtimes.lock().contains_key(&id) {
+                    Some("session not running")
+                } else if only_waiting && status != Some(SessionStatus::Waiting) {
+                    Some("session not waiting")
+                } else {
+                    None
+                };
+                if let Some(reason) = skip_reason {
+                    result.skipped = true;
+                    result.error = Some(reason.to_string());
+                } else if !dry_run {
+                    match self.write_session_input(&id, data) {
+                        Ok(()) => result.delivered = true,
+                        Err(err) => result.error = Some(err),
+                    }
+                }
+                result
+            })
+            .collect();
+        Ok(results)
     }
 
     pub fn resize_session(&self, id: &str, rows: u16, cols: u16) -> Result<(), String> {
@@ -728,54 +1378,1278 @@ impl SessionManager {
         self.storage.save(&snapshot).map_err(|e| e.to_string())
     }
 
-    fn update_session_status(&self, id: &str, status: SessionStatus) -> Result<(), String> {
-        let mut snapshot = self.snapshot.lock();
-        if let Some(session) = snapshot.sessions.iter_mut().find(|s| s.id == id) {
-            session.status = status;
-            session.last_accessed_at = Some(chrono_now());
+    /// Store a detected status. Returns whether the session is ready for its
+    /// next queued prompt: a delivered prompt counts as handled only once the
+    /// session has gone from Running back to Waiting.
+    fn record_live_status(&self, id: &str, status: SessionStatus) -> bool {
+        let previous = self.live_statuses.lock().insert(id.to_string(), status);
+        if status != SessionStatus::Waiting {
+            return false;
         }
-        self.storage.save(&snapshot).map_err(|e| e.to_string())
-    }
-
-    fn next_tab_order(&self) -> u32 {
-        let snapshot = self.snapshot.lock();
-        snapshot
-            .sessions
-            .iter()
-            .filter_map(|session| session.tab_order)
-            .max()
-            .unwrap_or(0)
-            .saturating_add(1)
+        let mut in_flight = self.prompts_in_flight.lock();
+        if previous == Some(SessionStatus::Running) {
+            in_flight.remove(id);
+            *self.finished_turns.lock().entry(id.to_string()).or_default() += 1;
+        }
+        !in_flight.contains(id)
     }
 
-    fn next_section_order(&self) -> u32 {
-        let snapshot = self.snapshot.lock();
-        snapshot
-            .sections
-            .iter()
-            .map(|section| section.order)
-            .max()
-            .unwrap_or(0)
-            .saturating_add(1)
+    /// How many times the session has gone from working back to its prompt.
+    pub fn finished_turns(&self, id: &str) -> u64 {
+        self.finished_turns.lock().get(id).copied().unwrap_or(0)
     }
 
-    fn is_ai_tool(tool: &model::SessionTool) -> bool {
-        matches!(
-            tool,
-            model::SessionTool::Claude
-                | model::SessionTool::Gemini
-                | model::SessionTool::Codex
-                | model::SessionTool::OpenCode
-        )
+    /// Whether the session was sent a queued prompt it hasn't finished working on.
+    pub fn prompt_in_flight(&self, id: &str) -> bool {
+        self.prompts_in_flight.lock().contains(id)
     }
 
-    fn get_running_session_ids(&self) -> Vec<String> {
-        let runtimes = self.runtimes.lock();
-        runtimes.keys().cloned().collect()
+    /// Whether a queued prompt may be typed into the session now.
+    fn ready_for_prompt(&self, id: &str) -> bool {
+        self.live_status(id) == Some(SessionStatus::Waiting)
+            && !self.prompts_in_flight.lock().contains(id)
     }
 
-    pub fn find_running_ai_sessions(&self, project_path: Option<&str>) -> Vec<String> {
-        let snapshot = self.snapshot.lock();
+    /// Called from the reader thread whenever the detected status changes.
+    fn on_status_changed(&self, app: &AppHandle, id: &str, status: SessionStatus) {
+        let ready = self.record_live_status(id, status);
+        if status == SessionStatus::Running {
+            self.touch_activity(id);
+        }
+        if ready && self.has_queued_prompts(id) {
+            let app = app.clone();
+            let session_id = id.to_string();
+            thread::spawn(move || {
+                let manager = app.state::<SessionManager>();
+                match manager.deliver_next_prompt(&session_id) {
+                    Ok(Some(prompt)) => {
+                        let _ = app.emit(
+                            "session-prompt-delivered",
+                            PromptDeliveredEvent {
+                                session_id: session_id.clone(),
+                                prompt,
+                            },
+                        );
+                    }
+                    Ok(None) => {}
+                    Err(err) => diagnostics::log(format!(
+                        "prompt_queue_deliver_failed id={} error={}",
+                        session_id, err
+                    )),
+                }
+            });
+        }
+        if status == SessionStatus::Running && self.checkpoint_armed.lock().remove(id) {
+            let app = app.clone();
+            let session_id = id.to_string();
+            thread::spawn(move || {
+                let manager = app.state::<SessionManager>();
+                match manager.create_checkpoint(&session_id, "input") {
+                    Ok(checkpoint) => {
+                        let _ = app.emit(
+                            "session-checkpoint",
+                            CheckpointEvent {
+                                session_id: session_id.clone(),
+                                checkpoint,
+                            },
+                        );
+                    }
+                    Err(err) => diagnostics::log(format!(
+                        "checkpoint_auto_skipped id={} reason={}",
+                        session_id, err
+                    )),
+                }
+            });
+        }
+    }
+
+    pub fn enqueue_prompt(&self, id: &str, text: String) -> Result<QueuedPrompt, String> {
+        if text.trim().is_empty() {
+            return Err("Prompt is empty".to_string());
+        }
+        let prompt = QueuedPrompt {
+            id: Uuid::new_v4().to_string(),
+            text,
+            created_at: chrono_now(),
+        };
+        {
+            let mut snapshot = self.snapshot.lock();
+            if !snapshot.sessions.iter().any(|session| session.id == id) {
+                return Err("Session not found".to_string());
+            }
+            snapshot
+                .prompt_queues
+                .entry(id.to_string())
+                .or_default()
+                .push(prompt.clone());
+            self.storage.save(&snapshot).map_err(|e| e.to_string())?;
+        }
+        // A session already sitting at its prompt will not transition again on its own.
+        // The prompt is queued either way, so a failed delivery is not the caller's error.
+        if self.ready_for_prompt(id) {
+            if let Err(err) = self.deliver_next_prompt(id) {
+                diagnostics::log(format!(
+                    "prompt_queue_deliver_failed id={} error={}",
+                    id, err
+                ));
+            }
+        }
+        Ok(prompt)
+    }
+
+    pub fn list_prompt_queue(&self, id: &str) -> Vec<QueuedPrompt> {
+        let snapshot = self.snapshot.lock();
+        snapshot.prompt_queues.get(id).cloned().unwrap_or_default()
+    }
+
+    pub fn cancel_queued_prompt(&self, id: &str, prompt_id: &str) -> Result<(), String> {
+        let mut snapshot = self.snapshot.lock();
+        let queue = snapshot
+            .prompt_queues
+            .get_mut(id)
+            .ok_or_else(|| "Prompt not found".to_string())?;
+        let before = queue.len();
+        queue.retain(|prompt| prompt.id != prompt_id);
+        if queue.len() == before {
+            return Err("Prompt not found".to_string());
+        }
+        if queue.is_empty() {
+            snapshot.prompt_queues.remove(id);
+        }
+        self.storage.save(&snapshot).map_err(|e| e.to_string())
+    }
+
+    /// Reorder a session's queue. `prompt_ids` must name every queued prompt exactly once.
+    pub fn reorder_prompt_queue(&self, id: &str, prompt_ids: &[String]) -> Result<(), String> {
+        let mut snapshot = self.snapshot.lock();
+        let queue = snapshot.prompt_queues.get_mut(id).ok_or_else(|| {
+            "Prompt queue is empty".to_string()
+        })?;
+        let unique: HashSet<&String> = prompt_ids.iter().collect();
+        if unique.len() != prompt_ids.len() || prompt_ids.len() != queue.len() {
+            return Err("Reorder must list every queued prompt exactly once".to_string());
+        }
+        let mut reordered = Vec::with_capacity(queue.len());
+        for prompt_id in prompt_ids {
+            let prompt = queue
+                .iter()
+                .find(|prompt| &prompt.id == prompt_id)
+                .cloned()
+                .ok_or_else(|| format!("Prompt not found: {}", prompt_id))?;
+            reordered.push(prompt);
+        }
+        *queue = reordered;
+        self.storage.save(&snapshot).map_err(|e| e.to_string())
+    }
+
+    fn has_queued_prompts(&self, id: &str) -> bool {
+        let snapshot = self.snapshot.lock();
+        snapshot
+            .prompt_queues
+            .get(id)
+            .is_some_and(|queue| !queue.is_empty())
+    }
+
+    /// Pop the next queued prompt and type it into the session. The prompt goes
+    /// back to the front of the queue if the write fails. Returns `None` when
+    /// the queue is empty or another prompt is already in flight.
+    pub fn deliver_next_prompt(&self, id: &str) -> Result<Option<QueuedPrompt>, String> {
+        // Claimed before popping so two callers that both saw the session
+        // ready cannot type two prompts at once. The status still reads
+        // Waiting until the session reacts; the claim holds the next one back.
+        if !self.prompts_in_flight.lock().insert(id.to_string()) {
+            return Ok(None);
+        }
+        let popped = self.pop_queued_prompt(id);
+        let prompt = match popped {
+            Ok(Some(prompt)) => prompt,
+            other => {
+                self.prompts_in_flight.lock().remove(id);
+                return other;
+            }
+        };
+
+        // Send the submit key separately so TUIs don't treat it as part of a paste.
+        // Typing it isn't activity; the work it starts is.
+        let result = self.write_runtime(id, prompt.text.as_bytes()).and_then(|_| {
+            thread::sleep(std::time::Duration::from_millis(150));
+            self.write_runtime(id, b"\r")
+        });
+        if let Err(err) = result {
+            self.prompts_in_flight.lock().remove(id);
+            let mut snapshot = self.snapshot.lock();
+            snapshot
+                .prompt_queues
+                .entry(id.to_string())
+                .or_default()
+                .insert(0, prompt);
+            let _ = self.storage.save(&snapshot);
+            return Err(err);
+        }
+        diagnostics::log(format!(
+            "prompt_queue_delivered id={} prompt_id={} bytes={}",
+            id,
+            prompt.id,
+            prompt.text.len()
+        ));
+        Ok(Some(prompt))
+    }
+
+    fn pop_queued_prompt(&self, id: &str) -> Result<Option<QueuedPrompt>, String> {
+        if !self.runtimes.lock().contains_key(id) {
+            return Err("session not running".to_string());
+        }
+        let mut snapshot = self.snapshot.lock();
+        let Some(queue) = snapshot.prompt_queues.get_mut(id) else {
+            return Ok(None);
+        };
+        if queue.is_empty() {
+            return Ok(None);
+        }
+        let prompt = queue.remove(0);
+        if queue.is_empty() {
+            snapshot.prompt_queues.remove(id);
+        }
+        self.storage.save(&snapshot).map_err(|e| e.to_string())?;
+        Ok(Some(prompt))
+    }
+
+    pub fn create_schedule(&self, input: NewScheduleInput) -> Result<ScheduleRecord, String> {
+        if input.name.trim().is_empty() {
+            return Err("Schedule name is empty".to_string());
+        }
+        let next_run_at = scheduler::next_run_at(&input.cron, OffsetDateTime::now_utc())?;
+        match &input.target {
+            ScheduleTarget::Session { session_id } => {
+                self.get_session(session_id)?;
+            }
+            ScheduleTarget::Template { input } => {
+                validate_path(&input.project_path)?;
+            }
+        }
+        let schedule = ScheduleRecord {
+            id: Uuid::new_v4().to_string(),
+            name: input.name,
+            cron: input.cron,
+            target: input.target,
+            initial_prompt: input
+                .initial_prompt
+                .filter(|prompt| !prompt.trim().is_empty()),
+            max_runtime_minutes: input.max_runtime_minutes,
+            enabled: true,
+            created_at: chrono_now(),
+            next_run_at,
+            runs: Vec::new(),
+        };
+        diagnostics::log(format!(
+            "schedule_created id={} cron={} next_run_at={:?}",
+            schedule.id, schedule.cron, schedule.next_run_at
+        ));
+        let mut snapshot = self.snapshot.lock();
+        snapshot.schedules.push(schedule.clone());
+        self.storage.save(&snapshot).map_err(|e| e.to_string())?;
+        Ok(schedule)
+    }
+
+    pub fn list_schedules(&self) -> Vec<ScheduleRecord> {
+        let snapshot = self.snapshot.lock();
+        snapshot.schedules.clone()
+    }
+
+    pub fn set_schedule_enabled(&self, id: &str, enabled: bool) -> Result<(), String> {
+        let mut snapshot = self.snapshot.lock();
+        let schedule = snapshot
+            .schedules
+            .iter_mut()
+            .find(|schedule| schedule.id == id)
+            .ok_or_else(|| "Schedule not found".to_string())?;
+        schedule.enabled = enabled;
+        if enabled {
+            // Fires missed while disabled are not caught up.
+            schedule.next_run_at =
+                scheduler::next_run_at(&schedule.cron, OffsetDateTime::now_utc())?;
+        }
+        self.storage.save(&snapshot).map_err(|e| e.to_string())
+    }
+
+    pub fn delete_schedule(&self, id: &str) -> Result<(), String> {
+        let mut snapshot = self.snapshot.lock();
+        let before = snapshot.schedules.len();
+        snapshot.schedules.retain(|schedule| schedule.id != id);
+        if snapshot.schedules.len() == before {
+            return Err("Schedule not found".to_string());
+        }
+        self.storage.save(&snapshot).map_err(|e| e.to_string())
+    }
+
+    /// Settle finished runs, then launch every enabled schedule that is due at `now`.
+    pub fn tick_schedules(&self, app: &AppHandle, now: OffsetDateTime) {
+        self.settle_schedule_runs(now);
+        let due: Vec<String> = {
+            let snapshot = self.snapshot.lock();
+            snapshot
+                .schedules
+                .iter()
+                .filter(|schedule| {
+                    schedule.enabled
+                        && schedule
+                            .next_run_at
+                            .as_deref()
+                            .and_then(scheduler::parse_timestamp)
+                            .is_some_and(|next| next <= now)
+                })
+                .map(|schedule| schedule.id.clone())
+                .collect()
+        };
+        for id in due {
+            if let Err(err) = self.run_schedule(app, &id, now) {
+                diagnostics::log(format!("schedule_run_failed id={} error={}", id, err));
+            }
+        }
+    }
+
+    /// Launch a schedule immediately. A schedule with a run still active is skipped.
+    pub fn run_schedule(
+        &self,
+        app: &AppHandle,
+        id: &str,
+        now: OffsetDateTime,
+    ) -> Result<ScheduleRun, String> {
+        let schedule = {
+            let mut snapshot = self.snapshot.lock();
+            let schedule = snapshot
+                .schedules
+                .iter_mut()
+                .find(|schedule| schedule.id == id)
+                .ok_or_else(|| "Schedule not found".to_string())?;
+            // Advance before launching so a failing launch is not retried every tick.
+            schedule.next_run_at = scheduler::next_run_at(&schedule.cron, now)?;
+            schedule.clone()
+        };
+
+        let started_at = scheduler::format_timestamp(now);
+        let active = schedule
+            .runs
+            .iter()
+            .any(|run| run.outcome == ScheduleOutcome::Running);
+        let run = if active {
+            ScheduleRun {
+                session_id: None,
+                started_at: started_at.clone(),
+                finished_at: Some(started_at),
+                outcome: ScheduleOutcome::Skipped,
+                exit_code: None,
+                error: Some("previous run still active".to_string()),
+            }
+        } else {
+            match self.launch_schedule(app, &schedule) {
+                Ok(session_id) => ScheduleRun {
+                    session_id: Some(session_id),
+                    started_at,
+                    finished_at: None,
+                    outcome: ScheduleOutcome::Running,
+                    exit_code: None,
+                    error: None,
+                },
+                Err(err) => ScheduleRun {
+                    session_id: None,
+                    started_at: started_at.clone(),
+                    finished_at: Some(started_at),
+                    outcome: ScheduleOutcome::Failed,
+                    exit_code: None,
+                    error: Some(err),
+                },
+            }
+        };
+        diagnostics::log(format!(
+            "schedule_run id={} outcome={:?} session_id={:?}",
+            id, run.outcome, run.session_id
+        ));
+
+        let mut snapshot = self.snapshot.lock();
+        if let Some(schedule) = snapshot.schedules.iter_mut().find(|s| s.id == id) {
+            schedule.runs.push(run.clone());
+            let excess = schedule.runs.len().saturating_sub(scheduler::MAX_RUNS_KEPT);
+            schedule.runs.drain(..excess);
+        }
+        self.storage.save(&snapshot).map_err(|e| e.to_string())?;
+        Ok(run)
+    }
+
+    fn launch_schedule(&self, app: &AppHandle, schedule: &ScheduleRecord) -> Result<String, String> {
+        let session_id = match &schedule.target {
+            ScheduleTarget::Session { session_id } => {
+                if self.runtimes.lock().contains_key(session_id) {
+                    return Err("session already running".to_string());
+                }
+                session_id.clone()
+            }
+            ScheduleTarget::Template { input } => {
+                self.prune_template_sessions(schedule);
+                self.create_session(input.clone())?.id
+            }
+        };
+        self.start_session(app, &session_id, None, None)?;
+        // Queued so it is typed once the tool reaches its prompt.
+        if let Some(prompt) = &schedule.initial_prompt {
+            self.enqueue_prompt(&session_id, prompt.clone())?;
+        }
+        Ok(session_id)
+    }
+
+    /// Delete sessions left by earlier runs of a template schedule, keeping
+    /// the most recent ones so their output can still be read.
+    fn prune_template_sessions(&self, schedule: &ScheduleRecord) {
+        let finished: Vec<&str> = schedule
+            .runs
+            .iter()
+            .filter(|run| run.outcome != ScheduleOutcome::Running)
+            .filter_map(|run| run.session_id.as_deref())
+            .filter(|id| self.get_session(id).is_ok() && !self.is_session_running(id))
+            .collect();
+        let excess = finished
+            .len()
+            .saturating_sub(scheduler::TEMPLATE_SESSIONS_KEPT.saturating_sub(1));
+        for id in &finished[..excess] {
+            if let Err(err) = self.delete_session(id) {
+                diagnostics::log(format!(
+                    "schedule_prune_failed id={} session_id={} error={}",
+                    schedule.id, id, err
+                ));
+            }
+        }
+    }
+
+    /// Record the outcome of active runs whose process exited, was stopped or ran too long.
+    fn settle_schedule_runs(&self, now: OffsetDateTime) {
+        let active: Vec<(String, String, String, Option<u32>)> = {
+            let snapshot = self.snapshot.lock();
+            snapshot
+                .schedules
+                .iter()
+                .filter_map(|schedule| {
+                    let run = schedule
+                        .runs
+                        .iter()
+                        .find(|run| run.outcome == ScheduleOutcome::Running)?;
+                    Some((
+                        schedule.id.clone(),
+                        run.session_id.clone()?,
+                        run.started_at.clone(),
+                        schedule.max_runtime_minutes,
+                    ))
+                })
+                .collect()
+        };
+
+        for (schedule_id, session_id, started_at, max_runtime) in active {
+            let timed_out = max_runtime.is_some_and(|minutes| {
+                scheduler::parse_timestamp(&started_at).is_some_and(|started| {
+                    now - started >= time::Duration::minutes(i64::from(minutes))
+                })
+            });
+            let (outcome, exit_code) = if !self.runtimes.lock().contains_key(&session_id) {
+                (ScheduleOutcome::Stopped, None)
+            } else if let Some(code) = self.session_exit_code(&session_id) {
+                // Free the PTY and reader thread left behind by the exited process.
+                let _ = self.stop_session(&session_id);
+                let outcome = if code == 0 {
+                    ScheduleOutcome::Completed
+                } else {
+                    ScheduleOutcome::Failed
+                };
+                (outcome, Some(code))
+            } else if timed_out {
+                let _ = self.stop_session(&session_id);
+                (ScheduleOutcome::TimedOut, None)
+            } else {
+                continue;
+            };
+
+            let mut snapshot = self.snapshot.lock();
+            let run = snapshot
+                .schedules
+                .iter_mut()
+                .find(|schedule| schedule.id == schedule_id)
+                .and_then(|schedule| {
+                    schedule.runs.iter_mut().find(|run| {
+                        run.outcome == ScheduleOutcome::Running
+                            && run.session_id.as_deref() == Some(session_id.as_str())
+                    })
+                });
+            if let Some(run) = run {
+                run.outcome = outcome;
+                run.exit_code = exit_code;
+                run.finished_at = Some(scheduler::format_timestamp(now));
+            }
+            let _ = self.storage.save(&snapshot);
+            diagnostics::log(format!(
+                "schedule_run_finished id={} session_id={} outcome={:?} exit_code={:?}",
+                schedule_id, session_id, outcome, exit_code
+            ));
+        }
+    }
+
+    /// Exit code of a session whose process has exited but was not stopped yet.
+    pub fn session_exit_code(&self, id: &str) -> Option<u32> {
+        let mut runtimes = self.runtimes.lock();
+        runtimes.get_mut(id).and_then(|runtime| runtime.exit_code())
+    }
+
+    /// Called off the reader thread when a session's process exits by itself.
+    /// Remote sessions whose ssh connection dropped are restarted with backoff.
+    fn on_session_ended(&self, app: &AppHandle, id: &str) {
+        let Ok(record) = self.get_session(id) else {
+            return;
+        };
+        if !record.remote.as_ref().is_some_and(|remote| remote.reconnect) {
+            return;
+        }
+
+        // The reader can see EOF slightly before the child is reaped.
+        let mut exit_code = None;
+        for _ in 0..20 {
+            exit_code = self.session_exit_code(id);
+            if exit_code.is_some() || !self.runtimes.lock().contains_key(id) {
+                break;
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+        if exit_code != Some(remote::SSH_CONNECTION_LOST) {
+            return;
+        }
+
+        let (uptime, size) = match self.runtimes.lock().get(id) {
+            Some(runtime) => (runtime.uptime(), runtime.size()),
+            None => return,
+        };
+        let attempt = {
+            let mut attempts = self.reconnect_attempts.lock();
+            let count = attempts.entry(id.to_string()).or_insert(0);
+            if uptime >= remote::STABLE_CONNECTION {
+                *count = 0;
+            }
+            *count += 1;
+            *count
+        };
+        if attempt > remote::MAX_RECONNECT_ATTEMPTS {
+            self.reconnect_attempts.lock().remove(id);
+            diagnostics::log(format!("remote_reconnect_gave_up id={}", id));
+            let _ = app.emit(
+                "session-reconnecting",
+                RemoteReconnectEvent {
+                    session_id: id.to_string(),
+                    attempt: attempt - 1,
+                    gave_up: true,
+                },
+            );
+            return;
+        }
+
+        thread::sleep(remote::reconnect_delay(attempt));
+        // Skip if the user stopped or restarted the session while we waited.
+        if self.session_exit_code(id) != Some(remote::SSH_CONNECTION_LOST) {
+            return;
+        }
+        diagnostics::log(format!("remote_reconnect id={} attempt={}", id, attempt));
+        let _ = app.emit(
+            "session-reconnecting",
+            RemoteReconnectEvent {
+                session_id: id.to_string(),
+                attempt,
+                gave_up: false,
+            },
+        );
+        let (rows, cols) = match size {
+            Some((rows, cols)) => (Some(rows), Some(cols)),
+            None => (None, None),
+        };
+        if let Err(err) = self.restart_session(app, id, rows, cols) {
+            diagnostics::log(format!("remote_reconnect_failed id={} error={}", id, err));
+        }
+    }
+
+    fn touch_activity(&self, id: &str) {
+        if let Some(last) = self.last_activity.lock().get_mut(id) {
+            *last = Instant::now();
+        }
+    }
+
+    /// Stop running sessions that have been quiet for longer than their idle policy
+    /// allows. Resume ids are left in place so the sessions can be picked up again.
+    fn reap_idle_sessions(&self, settings: &IdleSettings, now: Instant) -> Vec<SessionReapedEvent> {
+        let activity: Vec<(String, Instant)> = self
+            .last_activity
+            .lock()
+            .iter()
+            .map(|(id, last)| (id.clone(), *last))
+            .collect();
+
+        let mut reaped = Vec::new();
+        for (id, last) in activity {
+            if self.live_status(&id) == Some(SessionStatus::Running) {
+                continue;
+            }
+            let Ok(record) = self.get_session(&id) else {
+                continue;
+            };
+            let Some(policy) = settings.policy_for(record.tool.name(), &record.section_id) else {
+                continue;
+            };
+            let Some(limit) = policy.limit() else {
+                continue;
+            };
+            let idle = now.saturating_duration_since(last);
+            if idle < limit {
+                continue;
+            }
+
+            if let Err(err) = self.stop_session(&id) {
+                diagnostics::log(format!("idle_reap_failed id={} error={}", id, err));
+                continue;
+            }
+            let idle_minutes = idle.as_secs() / 60;
+            diagnostics::log(format!(
+                "idle_reaped id={} tool={} idle_minutes={} limit_hours={}",
+                id,
+                record.tool.name(),
+                idle_minutes,
+                policy.idle_hours
+            ));
+            reaped.push(SessionReapedEvent {
+                session_id: id,
+                title: record.title,
+                idle_minutes,
+                reason: format!(
+                    "No input or new work for {:.1} hours (idle limit {} hours)",
+                    idle.as_secs_f64() / 3600.0,
+                    policy.idle_hours
+                ),
+            });
+        }
+        reaped
+    }
+
+    /// CPU, memory and process list for a running session's process tree.
+    pub fn session_usage(&self, id: &str) -> Result<SessionUsage, String> {
+        let pid = self.session_pid(id)?;
+        self.monitor
+            .sample(&[(id.to_string(), pid)])
+            .pop()
+            .ok_or_else(|| "session not running".to_string())
+    }
+
+    fn sample_session_usage(&self) -> Vec<SessionUsage> {
+        let roots: Vec<(String, u32)> = {
+            let runtimes = self.runtimes.lock();
+            runtimes
+                .iter()
+                .filter_map(|(id, runtime)| runtime.pid().map(|pid| (id.clone(), pid)))
+                .collect()
+        };
+        self.monitor.sample(&roots)
+    }
+
+    /// Signal a single process spawned by a session, e.g. a runaway build. The
+    /// session's own top-level process is refused; use `stop_session` for that.
+    pub fn kill_session_process(&self, id: &str, pid: u32, force: bool) -> Result<(), String> {
+        let root = self.session_pid(id)?;
+        if !ProcessMonitor::is_descendant(root, pid) {
+            return Err(format!("process {} does not belong to session", pid));
+        }
+        diagnostics::log(format!(
+            "kill_session_process id={} pid={} force={}",
+            id, pid, force
+        ));
+        monitor::kill_process(pid, force)
+    }
+
+    fn session_pid(&self, id: &str) -> Result<u32, String> {
+        let runtimes = self.runtimes.lock();
+        runtimes
+            .get(id)
+            .ok_or_else(|| "session not running".to_string())?
+            .pid()
+            .ok_or_else(|| "session process id unavailable".to_string())
+    }
+
+    /// Submitted input on an agent session triggers a checkpoint once it starts working.
+    fn arm_checkpoint(&self, id: &str) {
+        let is_agent = {
+            let snapshot = self.snapshot.lock();
+            snapshot
+                .sessions
+                .iter()
+                .any(|session| session.id == id && Self::is_ai_tool(&session.tool))
+        };
+        if is_agent {
+            self.checkpoint_armed.lock().insert(id.to_string());
+        }
+    }
+
+    pub fn create_checkpoint(&self, id: &str, label: &str) -> Result<CheckpointRecord, String> {
+        let record = self.get_session(id)?;
+        let root = checkpoint::repo_root(&record.project_path)
+            .ok_or_else(|| "Project is not a git repository".to_string())?;
+        // Only the session's project is snapshotted, not the whole repository.
+        let scope = checkpoint::project_scope(&record.project_path)?;
+        let checkpoint_id = Uuid::new_v4().to_string();
+        let git_ref = checkpoint::checkpoint_ref(id, &checkpoint_id);
+        let created_at = chrono_now();
+        let message = format!("agent-term checkpoint {} ({}) {}", record.title, label, created_at);
+        let commit = checkpoint::snapshot(&root, &scope, &git_ref, &message)?;
+
+        let checkpoint = CheckpointRecord {
+            id: checkpoint_id,
+            session_id: id.to_string(),
+            label: label.to_string(),
+            repo_path: root.display().to_string(),
+            scope,
+            commit,
+            git_ref,
+            created_at,
+        };
+
+        let mut snapshot = self.snapshot.lock();
+        snapshot.checkpoints.push(checkpoint.clone());
+        let count = snapshot
+            .checkpoints
+            .iter()
+            .filter(|c| c.session_id == id)
+            .count();
+        let mut pruned = Vec::new();
+        if count > checkpoint::MAX_CHECKPOINTS_PER_SESSION {
+            let mut excess = count - checkpoint::MAX_CHECKPOINTS_PER_SESSION;
+            snapshot.checkpoints.retain(|c| {
+                if excess > 0 && c.session_id == id {
+                    excess -= 1;
+                    pruned.push(c.clone());
+                    return false;
+                }
+                true
+            });
+        }
+        let saved = self.storage.save(&snapshot).map_err(|e| e.to_string());
+        drop(snapshot);
+        delete_checkpoint_refs(&pruned);
+        saved?;
+        diagnostics::log(format!(
+            "checkpoint_created id={} checkpoint_id={} label={}",
+            id, checkpoint.id, label
+        ));
+        Ok(checkpoint)
+    }
+
+    /// Checkpoints for a session, newest first.
+    pub fn list_checkpoints(&self, id: &str) -> Vec<CheckpointRecord> {
+        let snapshot = self.snapshot.lock();
+        snapshot
+            .checkpoints
+            .iter()
+            .rev()
+            .filter(|checkpoint| checkpoint.session_id == id)
+            .cloned()
+            .collect()
+    }
+
+    /// Roll the project tree back to a checkpoint. The current tree is
+    /// checkpointed first and returned so the restore can itself be undone.
+    pub fn restore_checkpoint(
+        &self,
+        id: &str,
+        checkpoint_id: &str,
+    ) -> Result<CheckpointRecord, String> {
+        let target = {
+            let snapshot = self.snapshot.lock();
+            snapshot
+                .checkpoints
+                .iter()
+                .find(|c| c.session_id == id && c.id == checkpoint_id)
+                .cloned()
+                .ok_or_else(|| "Checkpoint not found".to_string())?
+        };
+        let safety = self.create_checkpoint(id, "before-restore")?;
+        checkpoint::restore(
+            std::path::Path::new(&target.repo_path),
+            &target.scope,
+            &target.commit,
+        )?;
+        diagnostics::log(format!(
+            "checkpoint_restored id={} checkpoint_id={} safety_id={}",
+            id, target.id, safety.id
+        ));
+        Ok(safety)
+    }
+
+    fn update_session_status(&self, id: &str, status: SessionStatus) -> Result<(), String> {
+        let mut snapshot = self.snapshot.lock();
+        if let Some(session) = snapshot.sessions.iter_mut().find(|s| s.id == id) {
+            session.status = status;
+            session.last_accessed_at = Some(chrono_now());
+        }
+        self.storage.save(&snapshot).map_err(|e| e.to_string())
+    }
+
+    fn next_tab_order(&self) -> u32 {
+        let snapshot = self.snapshot.lock();
+        snapshot
+            .sessions
+            .iter()
+            .filter_map(|session| session.tab_order)
+            .max()
+            .unwrap_or(0)
+            .saturating_add(1)
+    }
+
+    fn next_section_order(&self, parent_id: Option<&str>) -> u32 {
+        let snapshot = self.snapshot.lock();
+        snapshot
+            .sections
+            .iter()
+            .filter(|section| section.parent_id.as_deref() == parent_id)
+            .map(|section| section.order)
+            .max()
+            .unwrap_or(0)
+            .saturating_add(1)
+    }
+
+    fn is_ai_tool(tool: &model::SessionTool) -> bool {
+        matches!(
+            tool,
+            model::SessionTool::Claude
+                | model::SessionTool::Gemini
+                | model::SessionTool::Codex
+                | model::SessionTool::OpenCode
+        )
+    }
+
+    fn get_running_session_ids(&self) -> Vec<String> {
+        let runtimes = self.runtimes.lock();
+        runtimes.keys().cloned().collect()
+    }
+
+    /// Export a section's sessions, or the given sessions, to a bundle file with
+    /// the definitions of the MCPs they use.
+    pub fn export_bundle(
+        &self,
+        mcp_manager: &McpManager,
+        input: &ExportBundleInput,
+    ) -> Result<SessionBundle, String> {
+        let mut bundle = self.bundle_records(input, |record| {
+            let mut names = record.loaded_mcp_names.clone();
+            if !record.project_path.is_empty() {
+                let attached = tauri::async_runtime::block_on(
+                    mcp_manager.get_attached_mcps(McpScope::Local, Some(&record.project_path)),
+                )
+                .unwrap_or_default();
+                names.extend(attached);
+            }
+            names.sort();
+            names.dedup();
+            names
+        })?;
+
+        let config = mcp_manager.load_config_blocking().map_err(|e| e.to_string())?;
+        let mut redacted_names = Vec::new();
+        for name in bundle.sessions.iter().flat_map(|session| &session.mcp_names) {
+            if let Some(def) = config.mcps.get(name) {
+                let (def, redacted) = bundle::redact_mcp(def);
+                if redacted {
+                    redacted_names.push(name.clone());
+                }
+                bundle.mcps.insert(name.clone(), def);
+            }
+        }
+        bundle::write_bundle(Path::new(&input.path), &bundle)?;
+        diagnostics::log(format!(
+            "bundle_exported sections={} sessions={} mcps={} redacted={}",
+            bundle.sections.len(),
+            bundle.sessions.len(),
+            bundle.mcps.len(),
+            redacted_names.len()
+        ));
+        Ok(bundle)
+    }
+
+    fn bundle_records(
+        &self,
+        input: &ExportBundleInput,
+        mcp_names: impl Fn(&SessionRecord) -> Vec<String>,
+    ) -> Result<SessionBundle, String> {
+        let all_sections = self.list_sections();
+        // A section is exported with everything nested in it.
+        let mut exported_sections: Vec<String> = Vec::new();
+        if let Some(section_id) = &input.section_id {
+            if !all_sections.iter().any(|section| &section.id == section_id) {
+                return Err("Section not found".to_string());
+            }
+            exported_sections.push(section_id.clone());
+            let mut index = 0;
+            while index < exported_sections.len() {
+                let parent = exported_sections[index].clone();
+                exported_sections.extend(
+                    all_sections
+                        .iter()
+                        .filter(|section| section.parent_id.as_deref() == Some(parent.as_str()))
+                        .map(|section| section.id.clone()),
+                );
+                index += 1;
+            }
+        }
+        let records: Vec<SessionRecord> = match &input.section_id {
+            Some(_) => self
+                .list_sessions()
+                .into_iter()
+                .filter(|session| exported_sections.contains(&session.section_id))
+                .collect(),
+            None => input
+                .session_ids
+                .iter()
+                .map(|id| self.get_session(id))
+                .collect::<Result<_, _>>()?,
+        };
+        if records.is_empty() && input.section_id.is_none() {
+            return Err("No sessions to export".to_string());
+        }
+
+        let home = dirs::home_dir();
+        let transcripts = bundle::claude_projects_root();
+        let sections = all_sections
+            .into_iter()
+            .filter(|section| {
+                exported_sections.contains(&section.id)
+                    || records.iter().any(|record| record.section_id == section.id)
+            })
+            .map(|mut section| {
+                section.path = bundle::portable_path(&section.path, home.as_deref());
+                bundle::redact_env(section.defaults.env.iter_mut());
+                // Auto-start lists refer to this machine's session ids.
+                section.defaults.auto_start.clear();
+                section
+            })
+            .collect();
+
+        let mut sessions = Vec::new();
+        for record in records {
+            let scrollback = if input.include_scrollback {
+                self.screens
+                    .lock()
+                    .get(&record.id)
+                    .map(|screen| screen.tail(usize::MAX))
+            } else {
+                None
+            };
+            let transcript = match (&transcripts, &record.claude_session_id) {
+                (Some(root), Some(claude_id)) if input.include_transcripts => {
+                    bundle::find_claude_transcript(root, claude_id)
+                        .and_then(|path| std::fs::read_to_string(path).ok())
+                        .map(|content| bundle::export_transcript(&content, &record.project_path))
+                }
+                _ => None,
+            };
+            let mcp_names = mcp_names(&record);
+            let mut exported = record;
+            exported.project_path = bundle::portable_path(&exported.project_path, home.as_deref());
+            bundle::redact_env(exported.env.iter_mut());
+            exported.status = SessionStatus::Idle;
+            exported.last_accessed_at = None;
+            exported.loaded_mcp_names = Vec::new();
+            exported.tab_order = None;
+            // A resume id is only useful together with its transcript.
+            if transcript.is_none() {
+                exported.claude_session_id = None;
+            }
+            exported.gemini_session_id = None;
+            sessions.push(BundledSession {
+                record: exported,
+                mcp_names,
+                scrollback,
+                transcript,
+            });
+        }
+
+        Ok(SessionBundle {
+            version: bundle::BUNDLE_VERSION,
+            exported_at: chrono_now(),
+            sections,
+            sessions,
+            mcps: Default::default(),
+        })
+    }
+
+    /// The commands, env var names and MCP definitions an import would bring in.
+    pub fn preview_bundle(
+        &self,
+        mcp_manager: &McpManager,
+        input: &ImportBundleInput,
+    ) -> Result<BundlePreview, String> {
+        let (bundle, digest) = bundle::read_bundle(Path::new(&input.path))?;
+        let config = mcp_manager.load_config_blocking().map_err(|e| e.to_string())?;
+        let home = dirs::home_dir();
+        let remap = |path: &str| bundle::remap_path(path, &input.path_mappings, home.as_deref());
+        // Importing into an existing section skips the bundle's sections.
+        let sections = match input.section_id {
+            Some(_) => Vec::new(),
+            None => bundle
+                .sections
+                .iter()
+                .map(|section| PreviewSection {
+                    name: section.name.clone(),
+                    path: remap(&section.path),
+                    tool: section.defaults.tool.clone(),
+                    command: section.defaults.command.clone(),
+                    mcp_names: section.defaults.mcp_names.clone(),
+                    env_names: section.defaults.env.keys().cloned().collect(),
+                })
+                .collect(),
+        };
+        let sessions = bundle
+            .sessions
+            .iter()
+            .map(|session| PreviewSession {
+                title: session.record.title.clone(),
+                tool: session.record.tool.clone(),
+                command: session.record.command.clone(),
+                project_path: remap(&session.record.project_path),
+                env_names: session.record.env.keys().cloned().collect(),
+                container: session.record.container.clone(),
+                remote: session.record.remote.clone(),
+                sandbox: session.record.sandbox.clone(),
+            })
+            .collect();
+        let (existing, new): (Vec<_>, Vec<_>) = bundle
+            .mcps
+            .into_iter()
+            .partition(|(name, _)| config.mcps.contains_key(name));
+        Ok(BundlePreview {
+            sections,
+            sessions,
+            new_mcps: new.into_iter().collect(),
+            existing_mcps: existing.into_iter().map(|(name, _)| name).collect(),
+            digest,
+        })
+    }
+
+    /// Recreate a bundle's sections and sessions with new IDs and remapped paths.
+    /// MCPs the bundle defines are added to config.toml unless a local definition
+    /// with the same name exists, and attached to the imported projects. The
+    /// bundle must have been previewed and approved first.
+    pub fn import_bundle(
+        &self,
+        mcp_manager: &McpManager,
+        input: &ImportBundleInput,
+    ) -> Result<ImportBundleResult, String> {
+        let (bundle, digest) = bundle::read_bundle(Path::new(&input.path))?;
+        bundle::check_approved(input, &digest)?;
+        let (sections, sessions, env_needing_secrets) = self.create_bundle_records(&bundle, input)?;
+
+        let mut config = mcp_manager.load_config_blocking().map_err(|e| e.to_string())?;
+        let mut added_mcps = Vec::new();
+        let mut existing_mcps = Vec::new();
+        let mut mcps_needing_secrets = Vec::new();
+        for (name, def) in &bundle.mcps {
+            if config.mcps.contains_key(name) {
+                existing_mcps.push(name.clone());
+                continue;
+            }
+            let needs_secrets = def.env.values().any(|value| value == bundle::REDACTED)
+                || def.args.iter().any(|arg| arg.contains(bundle::REDACTED))
+                || def.url.contains(bundle::REDACTED);
+            if needs_secrets {
+                mcps_needing_secrets.push(name.clone());
+            }
+            config.mcps.insert(name.clone(), def.clone());
+            added_mcps.push(name.clone());
+        }
+        if !added_mcps.is_empty() {
+            tauri::async_runtime::block_on(mcp_manager.write_config(&config))
+                .map_err(|e| e.to_string())?;
+        }
+
+        for (bundled, record) in bundle.sessions.iter().zip(&sessions) {
+            if record.project_path.is_empty() || bundled.mcp_names.is_empty() {
+                continue;
+            }
+            let attached = tauri::async_runtime::block_on(
+                mcp_manager.get_attached_mcps(McpScope::Local, Some(&record.project_path)),
+            )
+            .unwrap_or_default();
+            for name in bundled
+                .mcp_names
+                .iter()
+                .filter(|name| config.mcps.contains_key(*name) && !attached.contains(name))
+            {
+                if let Err(err) = tauri::async_runtime::block_on(mcp_manager.attach_mcp(
+                    McpScope::Local,
+                    Some(&record.project_path),
+                    name,
+                )) {
+                    diagnostics::log(format!(
+                        "bundle_mcp_attach_failed name={} error={}",
+                        name, err
+                    ));
+                }
+            }
+        }
+
+        diagnostics::log(format!(
+            "bundle_imported sections={} sessions={} added_mcps={} existing_mcps={}",
+            sections.len(),
+            sessions.len(),
+            added_mcps.len(),
+            existing_mcps.len()
+        ));
+        Ok(ImportBundleResult {
+            sections,
+            sessions,
+            added_mcps,
+            existing_mcps,
+            mcps_needing_secrets,
+            env_needing_secrets,
+        })
+    }
+
+    /// Create the bundle's sections and sessions. Redacted env vars are left
+    /// out and returned, so no session starts with a placeholder for a secret.
+    fn create_bundle_records(
+        &self,
+        bundle: &SessionBundle,
+        input: &ImportBundleInput,
+    ) -> Result<ImportedRecords, String> {
+        let home = dirs::home_dir();
+        let remap = |path: &str| {
+            if path.is_empty() {
+                String::new()
+            } else {
+                bundle::remap_path(path, &input.path_mappings, home.as_deref())
+            }
+        };
+        if let Some(section_id) = &input.section_id {
+            if !self.list_sections().iter().any(|section| &section.id == section_id) {
+                return Err("Section not found".to_string());
+            }
+        }
+        // Check every path first so a bad mapping doesn't leave a partial import.
+        for session in &bundle.sessions {
+            let path = remap(&session.record.project_path);
+            validate_path(&path).map_err(|e| {
+                format!("{} (session '{}'; add a path mapping)", e, session.record.title)
+            })?;
+        }
+
+        let mut sections = Vec::new();
+        let mut section_ids = HashMap::new();
+        let mut redacted = Vec::new();
+        if input.section_id.is_none() {
+            for section in &bundle.sections {
+                let created =
+                    self.create_section(section.name.clone(), remap(&section.path), None)?;
+                if section.icon.is_some() {
+                    self.set_section_icon(&created.id, section.icon.clone())?;
+                }
+                let mut defaults = section.defaults.clone();
+                redacted.extend(bundle::take_redacted_env(&mut defaults.env).into_iter().map(
+                    |name| RedactedEnvVar {
+                        owner: section.name.clone(),
+                        name,
+                    },
+                ));
+                self.set_section_defaults(&created.id, defaults)?;
+                section_ids.insert(section.id.clone(), created.id.clone());
+                sections.push(created);
+            }
+            // Nest sections once they all exist; parents outside the bundle are dropped.
+            for (source, created) in bundle.sections.iter().zip(sections.iter_mut()) {
+                let parent = source
+                    .parent_id
+                    .as_ref()
+                    .and_then(|parent| section_ids.get(parent))
+                    .cloned();
+                if parent.is_some() {
+                    self.move_section(&created.id, parent)?;
+                }
+            }
+            let current = self.list_sections();
+            for created in sections.iter_mut() {
+                if let Some(section) = current.iter().find(|section| section.id == created.id) {
+                    *created = section.clone();
+                }
+            }
+        }
+
+        let transcripts = bundle::claude_projects_root();
+        let mut session_ids = HashMap::new();
+        let mut sessions = Vec::new();
+        let mut envs = Vec::new();
+        for bundled in &bundle.sessions {
+            let source = &bundled.record;
+            // The session's own env, not the new section's defaults.
+            let mut env = source.env.clone();
+            redacted.extend(bundle::take_redacted_env(&mut env).into_iter().map(|name| {
+                RedactedEnvVar {
+                    owner: source.title.clone(),
+                    name,
+                }
+            }));
+            envs.push(env);
+            let section_id = input
+                .section_id
+                .clone()
+                .or_else(|| section_ids.get(&source.section_id).cloned())
+                .unwrap_or_else(|| source.section_id.clone());
+            let record = self.create_session(NewSessionInput {
+                title: source.title.clone(),
+                project_path: remap(&source.project_path),
+                section_id,
+                tool: source.tool.clone(),
+                command: source.command.clone(),
+                icon: source.icon.clone(),
+            })?;
+            if source.sandbox.is_some() {
+                self.set_session_sandbox(&record.id, source.sandbox.clone())?;
+            }
+            if source.container.is_some() {
+                self.set_session_container(&record.id, source.container.clone())?;
+            }
+            if source.remote.is_some() {
+                self.set_session_remote(&record.id, source.remote.clone())?;
+            }
+            if let (Some(root), Some(claude_id), Some(transcript)) =
+                (&transcripts, &source.claude_session_id, &bundled.transcript)
+            {
+                bundle::import_transcript(root, claude_id, transcript, &record.project_path)?;
+                self.set_tool_session_id(&record.id, "claude", claude_id.clone())?;
+            }
+            if let Some(lines) = &bundled.scrollback {
+                let mut text = lines.join("\r\n");
+                text.push_str("\r\n");
+                self.record_screen_output(&record.id, text.as_bytes());
+            }
+            session_ids.insert(source.id.clone(), record.id.clone());
+            sessions.push(record);
+        }
+
+        // Helpers keep pointing at the session that created them, if it came along.
+        let mut snapshot = self.snapshot.lock();
+        for ((bundled, record), env) in bundle.sessions.iter().zip(sessions.iter_mut()).zip(envs) {
+            let created_by = bundled
+                .record
+                .created_by
+                .as_ref()
+                .and_then(|id| session_ids.get(id))
+                .cloned();
+            if let Some(session) = snapshot.sessions.iter_mut().find(|s| s.id == record.id) {
+                session.created_by = created_by;
+                session.env = env;
+                *record = session.clone();
+            }
+        }
+        self.storage.save(&snapshot).map_err(|e| e.to_string())?;
+        Ok((sections, sessions, redacted))
+    }
+
+    pub fn find_running_ai_sessions(&self, project_path: Option<&str>) -> Vec<String> {
+        let snapshot = self.snapshot.lock();
         let running_ids = self.get_running_session_ids();
 
         snapshot
@@ -809,9 +2683,12 @@ pub fn get_session(state: State<'_, SessionManager>, id: String) -> Result<Sessi
 #[tauri::command(rename_all = "camelCase")]
 pub fn create_session(
     state: State<'_, SessionManager>,
+    mcp_state: State<'_, crate::mcp::McpManager>,
     input: NewSessionInput,
 ) -> Result<SessionRecord, String> {
-    state.create_session(input)
+    let record = state.create_session(input)?;
+    state.attach_section_mcps(&mcp_state, &record);
+    Ok(record)
 }
 
 #[tauri::command(rename_all = "camelCase")]
@@ -841,6 +2718,33 @@ pub fn set_session_icon(
     state.set_session_icon(&id, icon)
 }
 
+#[tauri::command(rename_all = "camelCase")]
+pub fn set_session_sandbox(
+    state: State<'_, SessionManager>,
+    id: String,
+    sandbox: Option<SessionSandbox>,
+) -> Result<(), String> {
+    state.set_session_sandbox(&id, sandbox)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn set_session_container(
+    state: State<'_, SessionManager>,
+    id: String,
+    container: Option<SessionContainer>,
+) -> Result<(), String> {
+    state.set_session_container(&id, container)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn set_session_remote(
+    state: State<'_, SessionManager>,
+    id: String,
+    remote: Option<SessionRemote>,
+) -> Result<(), String> {
+    state.set_session_remote(&id, remote)
+}
+
 #[tauri::command(rename_all = "camelCase")]
 pub fn delete_session(state: State<'_, SessionManager>, id: String) -> Result<(), String> {
     state.delete_session(&id)
@@ -868,8 +2772,9 @@ pub fn create_section(
     state: State<'_, SessionManager>,
     name: String,
     path: String,
+    parent_id: Option<String>,
 ) -> Result<SectionRecord, String> {
-    state.create_section(name, path)
+    state.create_section(name, path, parent_id)
 }
 
 #[tauri::command(rename_all = "camelCase")]
@@ -899,6 +2804,50 @@ pub fn set_section_icon(
     state.set_section_icon(&id, icon)
 }
 
+#[tauri::command(rename_all = "camelCase")]
+pub fn move_section(
+    state: State<'_, SessionManager>,
+    id: String,
+    parent_id: Option<String>,
+) -> Result<(), String> {
+    state.move_section(&id, parent_id)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn reorder_sections(
+    state: State<'_, SessionManager>,
+    parent_id: Option<String>,
+    section_ids: Vec<String>,
+) -> Result<(), String> {
+    state.reorder_sections(parent_id, &section_ids)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn reorder_sessions(
+    state: State<'_, SessionManager>,
+    session_ids: Vec<String>,
+) -> Result<(), String> {
+    state.reorder_sessions(&session_ids)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn set_section_defaults(
+    state: State<'_, SessionManager>,
+    id: String,
+    defaults: SectionDefaults,
+) -> Result<(), String> {
+    state.set_section_defaults(&id, defaults)
+}
+
+/// Defaults a new session in the section inherits, merged with its parents'.
+#[tauri::command(rename_all = "camelCase")]
+pub fn get_section_defaults(
+    state: State<'_, SessionManager>,
+    id: String,
+) -> Result<SectionDefaults, String> {
+    Ok(state.section_defaults(&id))
+}
+
 #[tauri::command(rename_all = "camelCase")]
 pub fn delete_section(state: State<'_, SessionManager>, id: String) -> Result<(), String> {
     state.delete_section(&id)
@@ -942,28 +2891,206 @@ pub fn write_session_input(
 }
 
 #[tauri::command(rename_all = "camelCase")]
-pub fn resize_session(
+pub fn broadcast_session_input(
+    state: State<'_, SessionManager>,
+    session_ids: Option<Vec<String>>,
+    section_id: Option<String>,
+    data: String,
+    dry_run: Option<bool>,
+    only_waiting: Option<bool>,
+) -> Result<Vec<BroadcastResult>, String> {
+    state.broadcast_session_input(
+        session_ids,
+        section_id,
+        data.as_bytes(),
+        dry_run.unwrap_or(false),
+        only_waiting.unwrap_or(false),
+    )
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn resize_session(
+    state: State<'_, SessionManager>,
+    id: String,
+    rows: u16,
+    cols: u16,
+) -> Result<(), String> {
+    state.resize_session(&id, rows, cols)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn acknowledge_session(state: State<'_, SessionManager>, id: String) -> Result<(), String> {
+    state.acknowledge_session(&id)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn set_tool_session_id(
+    state: State<'_, SessionManager>,
+    id: String,
+    tool: String,
+    tool_session_id: String,
+) -> Result<(), String> {
+    state.set_tool_session_id(&id, &tool, tool_session_id)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn enqueue_prompt(
+    state: State<'_, SessionManager>,
+    session_id: String,
+    text: String,
+) -> Result<QueuedPrompt, String> {
+    state.enqueue_prompt(&session_id, text)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn list_prompt_queue(
+    state: State<'_, SessionManager>,
+    session_id: String,
+) -> Result<Vec<QueuedPrompt>, String> {
+    Ok(state.list_prompt_queue(&session_id))
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn cancel_queued_prompt(
+    state: State<'_, SessionManager>,
+    session_id: String,
+    prompt_id: String,
+) -> Result<(), String> {
+    state.cancel_queued_prompt(&session_id, &prompt_id)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn reorder_prompt_queue(
+    state: State<'_, SessionManager>,
+    session_id: String,
+    prompt_ids: Vec<String>,
+) -> Result<(), String> {
+    state.reorder_prompt_queue(&session_id, &prompt_ids)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn list_checkpoints(
+    state: State<'_, SessionManager>,
+    session_id: String,
+) -> Result<Vec<CheckpointRecord>, String> {
+    Ok(state.list_checkpoints(&session_id))
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn create_checkpoint(
+    state: State<'_, SessionManager>,
+    session_id: String,
+) -> Result<CheckpointRecord, String> {
+    state.create_checkpoint(&session_id, "manual")
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn restore_checkpoint(
+    state: State<'_, SessionManager>,
+    session_id: String,
+    checkpoint_id: String,
+) -> Result<CheckpointRecord, String> {
+    state.restore_checkpoint(&session_id, &checkpoint_id)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn list_schedules(state: State<'_, SessionManager>) -> Result<Vec<ScheduleRecord>, String> {
+    Ok(state.list_schedules())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn create_schedule(
+    state: State<'_, SessionManager>,
+    input: NewScheduleInput,
+) -> Result<ScheduleRecord, String> {
+    state.create_schedule(input)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn set_schedule_enabled(
+    state: State<'_, SessionManager>,
+    id: String,
+    enabled: bool,
+) -> Result<(), String> {
+    state.set_schedule_enabled(&id, enabled)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn delete_schedule(state: State<'_, SessionManager>, id: String) -> Result<(), String> {
+    state.delete_schedule(&id)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn run_schedule_now(
+    app: AppHandle,
+    state: State<'_, SessionManager>,
+    id: String,
+) -> Result<ScheduleRun, String> {
+    state.run_schedule(&app, &id, OffsetDateTime::now_utc())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn export_session_bundle(
+    state: State<'_, SessionManager>,
+    mcp_state: State<'_, crate::mcp::McpManager>,
+    input: ExportBundleInput,
+) -> Result<SessionBundle, String> {
+    state.export_bundle(&mcp_state, &input)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn preview_session_bundle(
+    state: State<'_, SessionManager>,
+    mcp_state: State<'_, crate::mcp::McpManager>,
+    input: ImportBundleInput,
+) -> Result<BundlePreview, String> {
+    state.preview_bundle(&mcp_state, &input)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn import_session_bundle(
     state: State<'_, SessionManager>,
-    id: String,
-    rows: u16,
-    cols: u16,
-) -> Result<(), String> {
-    state.resize_session(&id, rows, cols)
+    mcp_state: State<'_, crate::mcp::McpManager>,
+    input: ImportBundleInput,
+) -> Result<ImportBundleResult, String> {
+    state.import_bundle(&mcp_state, &input)
 }
 
 #[tauri::command(rename_all = "camelCase")]
-pub fn acknowledge_session(state: State<'_, SessionManager>, id: String) -> Result<(), String> {
-    state.acknowledge_session(&id)
+pub fn get_session_usage(
+    state: State<'_, SessionManager>,
+    session_id: String,
+) -> Result<SessionUsage, String> {
+    state.session_usage(&session_id)
 }
 
 #[tauri::command(rename_all = "camelCase")]
-pub fn set_tool_session_id(
+pub fn kill_session_process(
     state: State<'_, SessionManager>,
-    id: String,
-    tool: String,
-    tool_session_id: String,
+    session_id: String,
+    pid: u32,
+    force: Option<bool>,
 ) -> Result<(), String> {
-    state.set_tool_session_id(&id, &tool, tool_session_id)
+    state.kill_session_process(&session_id, pid, force.unwrap_or(false))
+}
+
+/// Command a new session of `tool` runs when none is given: the configured shell
+/// for shell sessions, otherwise the tool's own name.
+pub fn default_tool_command(app: &AppHandle, tool: &SessionTool) -> String {
+    match tool {
+        SessionTool::Shell => {
+            let config = app
+                .state::<McpManager>()
+                .load_config_blocking()
+                .unwrap_or_default();
+            if config.shell.default_shell.is_empty() {
+                crate::detect_default_shell()
+            } else {
+                config.shell.default_shell
+            }
+        }
+        tool => tool.name().to_string(),
+    }
 }
 
 fn chrono_now() -> String {
@@ -986,6 +3113,15 @@ mod tests {
             storage: debounced,
             snapshot: Mutex::new(snapshot),
             runtimes: Mutex::new(HashMap::new()),
+            checkpoint_armed: Mutex::new(HashSet::new()),
+            live_statuses: Mutex::new(HashMap::new()),
+            prompts_in_flight: Mutex::new(HashSet::new()),
+            finished_turns: Mutex::new(HashMap::new()),
+        caller_tokens: Mutex::new(HashMap::new()),
+            last_activity: Mutex::new(HashMap::new()),
+            monitor: ProcessMonitor::default(),
+            reconnect_attempts: Mutex::new(HashMap::new()),
+            screens: Mutex::new(HashMap::new()),
         };
         (temp, manager)
     }
@@ -1056,7 +3192,7 @@ mod tests {
     fn test_create_and_list_sections() {
         let (_temp, manager) = test_manager();
 
-        let section = manager.create_section("Project A".to_string(), "/home/user/project-a".to_string()).unwrap();
+        let section = manager.create_section("Project A".to_string(), "/home/user/project-a".to_string(), None).unwrap();
         assert_eq!(section.name, "Project A");
         assert_eq!(section.path, "/home/user/project-a");
         assert!(section.icon.is_none());
@@ -1066,11 +3202,143 @@ mod tests {
         assert_eq!(sections[0].id, section.id);
     }
 
+    #[test]
+    fn test_nested_section_defaults_are_inherited() {
+        let (_temp, manager) = test_manager();
+
+        let parent = manager.create_section("Work".to_string(), "".to_string(), None).unwrap();
+        let child = manager
+            .create_section("API".to_string(), "".to_string(), Some(parent.id.clone()))
+            .unwrap();
+        assert_eq!(child.order, 1);
+
+        let mut parent_defaults = SectionDefaults {
+            tool: Some(model::SessionTool::Claude),
+            command: Some("claude --verbose".to_string()),
+            mcp_names: vec!["github".to_string()],
+            ..SectionDefaults::default()
+        };
+        parent_defaults.env.insert("RUST_LOG".to_string(), "info".to_string());
+        manager.set_section_defaults(&parent.id, parent_defaults).unwrap();
+        let mut child_defaults = SectionDefaults {
+            mcp_names: vec!["postgres".to_string()],
+            ..SectionDefaults::default()
+        };
+        child_defaults.env.insert("RUST_LOG".to_string(), "debug".to_string());
+        manager.set_section_defaults(&child.id, child_defaults).unwrap();
+
+        let resolved = manager.section_defaults(&child.id);
+        assert_eq!(resolved.tool, Some(model::SessionTool::Claude));
+        assert_eq!(resolved.env.get("RUST_LOG").map(String::as_str), Some("debug"));
+        assert_eq!(resolved.mcp_names, vec!["github", "postgres"]);
+
+        let session = manager
+            .create_session(NewSessionInput {
+                title: "Inherits".to_string(),
+                project_path: "".to_string(),
+                section_id: child.id.clone(),
+                tool: model::SessionTool::Claude,
+                command: String::new(),
+                icon: None,
+            })
+            .unwrap();
+        assert_eq!(session.command, "claude --verbose");
+        assert_eq!(session.env.get("RUST_LOG").map(String::as_str), Some("debug"));
+
+        // The default command belongs to the default tool.
+        let shell = manager
+            .create_session(NewSessionInput {
+                title: "Shell".to_string(),
+                project_path: "".to_string(),
+                section_id: child.id.clone(),
+                tool: model::SessionTool::Shell,
+                command: String::new(),
+                icon: None,
+            })
+            .unwrap();
+        assert_eq!(shell.command, "");
+
+        manager.delete_section(&child.id).unwrap();
+        assert_eq!(manager.get_session(&session.id).unwrap().section_id, parent.id);
+    }
+
+    #[test]
+    fn test_move_section_rejects_cycles() {
+        let (_temp, manager) = test_manager();
+
+        let top = manager.create_section("Top".to_string(), "".to_string(), None).unwrap();
+        let mid = manager
+            .create_section("Mid".to_string(), "".to_string(), Some(top.id.clone()))
+            .unwrap();
+        let leaf = manager
+            .create_section("Leaf".to_string(), "".to_string(), Some(mid.id.clone()))
+            .unwrap();
+
+        assert!(manager.move_section(&top.id, Some(leaf.id.clone())).is_err());
+        assert!(manager.move_section(&top.id, Some(top.id.clone())).is_err());
+        assert!(manager.move_section(&leaf.id, Some("missing".to_string())).is_err());
+
+        manager.move_section(&leaf.id, None).unwrap();
+        let sections = manager.list_sections();
+        let moved = sections.iter().find(|section| section.id == leaf.id).unwrap();
+        assert!(moved.parent_id.is_none());
+        assert_eq!(moved.order, 2);
+    }
+
+    #[test]
+    fn test_reorder_sections_and_sessions() {
+        let (_temp, manager) = test_manager();
+
+        let a = manager.create_section("A".to_string(), "".to_string(), None).unwrap();
+        let b = manager.create_section("B".to_string(), "".to_string(), None).unwrap();
+        let c = manager.create_section("C".to_string(), "".to_string(), None).unwrap();
+
+        manager.reorder_sections(None, &[c.id.clone(), a.id.clone()]).unwrap();
+        let order = |id: &str| {
+            manager
+                .list_sections()
+                .into_iter()
+                .find(|section| section.id == id)
+                .unwrap()
+                .order
+        };
+        assert_eq!((order(&c.id), order(&a.id), order(&b.id)), (0, 1, 2));
+
+        // A bad id leaves every section untouched.
+        assert!(manager
+            .reorder_sections(None, &[b.id.clone(), "missing".to_string()])
+            .is_err());
+        assert_eq!(order(&b.id), 2);
+
+        let ids: Vec<String> = (0..3)
+            .map(|i| {
+                manager
+                    .create_session(NewSessionInput {
+                        title: format!("S{}", i),
+                        project_path: "".to_string(),
+                        section_id: a.id.clone(),
+                        tool: model::SessionTool::Shell,
+                        command: "/bin/bash".to_string(),
+                        icon: None,
+                    })
+                    .unwrap()
+                    .id
+            })
+            .collect();
+        manager.reorder_sessions(&[ids[2].clone()]).unwrap();
+        let tabs: Vec<Option<u32>> = ids
+            .iter()
+            .map(|id| manager.get_session(id).unwrap().tab_order)
+            .collect();
+        assert_eq!(tabs, vec![Some(1), Some(2), Some(0)]);
+        assert!(manager.reorder_sessions(&["missing".to_string()]).is_err());
+    }
+
     #[test]
     fn test_move_session_to_section() {
         let (_temp, manager) = test_manager();
 
-        let section = manager.create_section("New Section".to_string(), "".to_string()).unwrap();
+        let section = manager.create_section("New Section".to_string(), "".to_string(), None).unwrap();
 
         let input = NewSessionInput {
             title: "Movable".to_string(),
@@ -1170,6 +3438,569 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_checkpoint_create_list_restore() {
+        let (temp, manager) = test_manager();
+        let project = temp.path().join("project");
+        std::fs::create_dir_all(&project).unwrap();
+        std::process::Command::new("git")
+            .arg("-C")
+            .arg(&project)
+            .args(["init", "-q"])
+            .status()
+            .unwrap();
+        std::fs::write(project.join("plan.md"), "original").unwrap();
+
+        let session = manager
+            .create_session(NewSessionInput {
+                title: "Agent".to_string(),
+                project_path: project.display().to_string(),
+                section_id: "default".to_string(),
+                tool: model::SessionTool::Claude,
+                command: "claude".to_string(),
+                icon: None,
+            })
+            .unwrap();
+
+        let first = manager.create_checkpoint(&session.id, "manual").unwrap();
+        std::fs::write(project.join("plan.md"), "rewritten by agent").unwrap();
+
+        let safety = manager.restore_checkpoint(&session.id, &first.id).unwrap();
+        assert_eq!(std::fs::read_to_string(project.join("plan.md")).unwrap(), "original");
+
+        let listed = manager.list_checkpoints(&session.id);
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[0].id, safety.id);
+        assert_eq!(listed[1].id, first.id);
+
+        manager.restore_checkpoint(&session.id, &safety.id).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(project.join("plan.md")).unwrap(),
+            "rewritten by agent"
+        );
+    }
+
+    #[test]
+    fn test_checkpoint_requires_git_repo() {
+        let (temp, manager) = test_manager();
+        let session = manager
+            .create_session(NewSessionInput {
+                title: "Agent".to_string(),
+                project_path: temp.path().display().to_string(),
+                section_id: "default".to_string(),
+                tool: model::SessionTool::Claude,
+                command: "claude".to_string(),
+                icon: None,
+            })
+            .unwrap();
+
+        assert!(manager.create_checkpoint(&session.id, "manual").is_err());
+        assert!(manager.list_checkpoints(&session.id).is_empty());
+    }
+
+    #[test]
+    fn test_broadcast_dry_run_resolves_section_targets() {
+        let (_temp, manager) = test_manager();
+        let section = manager.create_section("Agents".to_string(), "".to_string(), None).unwrap();
+        let mut ids = Vec::new();
+        for (i, section_id) in [section.id.clone(), section.id.clone(), "default".to_string()]
+            .into_iter()
+            .enumerate()
+        {
+            let session = manager
+                .create_session(NewSessionInput {
+                    title: format!("Agent {}", i),
+                    project_path: "".to_string(),
+                    section_id,
+                    tool: model::SessionTool::Claude,
+                    command: "claude".to_string(),
+                    icon: None,
+                })
+                .unwrap();
+            ids.push(session.id);
+        }
+
+        let results = manager
+            .broadcast_session_input(None, Some(section.id.clone()), b"/compact\r", true, false)
+            .unwrap();
+        let targeted: Vec<&str> = results.iter().map(|r| r.session_id.as_str()).collect();
+        assert_eq!(targeted, vec![ids[0].as_str(), ids[1].as_str()]);
+        assert!(results.iter().all(|r| !r.delivered && r.skipped));
+        assert_eq!(results[0].error.as_deref(), Some("session not running"));
+    }
+
+    #[test]
+    fn test_broadcast_reports_unknown_sessions_and_requires_one_target() {
+        let (_temp, manager) = test_manager();
+
+        let results = manager
+            .broadcast_session_input(Some(vec!["missing".to_string()]), None, b"\x03", false, true)
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].skipped);
+        assert_eq!(results[0].error.as_deref(), Some("Session not found"));
+
+        assert!(manager
+            .broadcast_session_input(None, None, b"\x03", false, false)
+            .is_err());
+        assert!(manager
+            .broadcast_session_input(
+                Some(vec![]),
+                Some("default".to_string()),
+                b"\x03",
+                false,
+                false
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_prompt_queue_enqueue_reorder_cancel() {
+        let (_temp, manager) = test_manager();
+        let session = manager
+            .create_session(NewSessionInput {
+                title: "Agent".to_string(),
+                project_path: "".to_string(),
+                section_id: "default".to_string(),
+                tool: model::SessionTool::Claude,
+                command: "claude".to_string(),
+                icon: None,
+            })
+            .unwrap();
+
+        let first = manager.enqueue_prompt(&session.id, "write the plan".to_string()).unwrap();
+        let second = manager.enqueue_prompt(&session.id, "implement it".to_string()).unwrap();
+        let third = manager.enqueue_prompt(&session.id, "add tests".to_string()).unwrap();
+        assert!(manager.enqueue_prompt(&session.id, "   ".to_string()).is_err());
+        assert!(manager.enqueue_prompt("missing", "hi".to_string()).is_err());
+
+        manager
+            .reorder_prompt_queue(
+                &session.id,
+                &[third.id.clone(), first.id.clone(), second.id.clone()],
+            )
+            .unwrap();
+        assert!(manager
+            .reorder_prompt_queue(&session.id, &[third.id.clone(), third.id.clone(), first.id.clone()])
+            .is_err());
+
+        manager.cancel_queued_prompt(&session.id, &first.id).unwrap();
+        assert!(manager.cancel_queued_prompt(&session.id, &first.id).is_err());
+
+        let queue: Vec<String> = manager
+            .list_prompt_queue(&session.id)
+            .into_iter()
+            .map(|prompt| prompt.text)
+            .collect();
+        assert_eq!(queue, vec!["add tests".to_string(), "implement it".to_string()]);
+    }
+
+    #[test]
+    fn test_delivered_prompt_holds_the_queue_until_the_session_works() {
+        let (_temp, manager) = test_manager();
+        let id = "agent";
+        assert!(manager.record_live_status(id, SessionStatus::Waiting));
+        assert!(manager.ready_for_prompt(id));
+
+        // Delivered, but the session has not reacted yet.
+        manager.prompts_in_flight.lock().insert(id.to_string());
+        assert!(!manager.ready_for_prompt(id));
+        assert!(!manager.record_live_status(id, SessionStatus::Waiting));
+        assert_eq!(manager.finished_turns(id), 0);
+
+        assert!(!manager.record_live_status(id, SessionStatus::Running));
+        assert!(manager.record_live_status(id, SessionStatus::Waiting));
+        assert!(manager.ready_for_prompt(id));
+        assert!(!manager.prompt_in_flight(id));
+        assert_eq!(manager.finished_turns(id), 1);
+    }
+
+    #[test]
+    fn test_deliver_next_prompt_requires_running_session() {
+        let (_temp, manager) = test_manager();
+        let session = manager
+            .create_session(NewSessionInput {
+                title: "Agent".to_string(),
+                project_path: "".to_string(),
+                section_id: "default".to_string(),
+                tool: model::SessionTool::Claude,
+                command: "claude".to_string(),
+                icon: None,
+            })
+            .unwrap();
+        manager.enqueue_prompt(&session.id, "next step".to_string()).unwrap();
+
+        assert!(manager.deliver_next_prompt(&session.id).is_err());
+        assert_eq!(manager.list_prompt_queue(&session.id).len(), 1);
+        assert!(!manager.prompt_in_flight(&session.id));
+
+        // Queued even when delivering right away fails.
+        manager.record_live_status(&session.id, SessionStatus::Waiting);
+        assert!(manager.enqueue_prompt(&session.id, "then this".to_string()).is_ok());
+        assert_eq!(manager.list_prompt_queue(&session.id).len(), 2);
+
+        // Only one caller holds the slot; the other leaves the queue alone.
+        manager.prompts_in_flight.lock().insert(session.id.clone());
+        assert!(matches!(manager.deliver_next_prompt(&session.id), Ok(None)));
+        assert_eq!(manager.list_prompt_queue(&session.id).len(), 2);
+        manager.prompts_in_flight.lock().remove(&session.id);
+
+        manager.delete_session(&session.id).unwrap();
+        assert!(manager.list_prompt_queue(&session.id).is_empty());
+    }
+
+    #[test]
+    fn test_schedule_create_toggle_delete() {
+        let (_temp, manager) = test_manager();
+        let session = manager
+            .create_session(NewSessionInput {
+                title: "Audit".to_string(),
+                project_path: "".to_string(),
+                section_id: "default".to_string(),
+                tool: model::SessionTool::Claude,
+                command: "claude".to_string(),
+                icon: None,
+            })
+            .unwrap();
+
+        let bad_cron = manager.create_schedule(NewScheduleInput {
+            name: "Nightly".to_string(),
+            cron: "not a cron".to_string(),
+            target: ScheduleTarget::Session {
+                session_id: session.id.clone(),
+            },
+            initial_prompt: None,
+            max_runtime_minutes: None,
+        });
+        assert!(bad_cron.is_err());
+        let missing_session = manager.create_schedule(NewScheduleInput {
+            name: "Nightly".to_string(),
+            cron: "30 2 * * *".to_string(),
+            target: ScheduleTarget::Session {
+                session_id: "missing".to_string(),
+            },
+            initial_prompt: None,
+            max_runtime_minutes: None,
+        });
+        assert!(missing_session.is_err());
+
+        let schedule = manager
+            .create_schedule(NewScheduleInput {
+                name: "Nightly".to_string(),
+                cron: "30 2 * * *".to_string(),
+                target: ScheduleTarget::Session {
+                    session_id: session.id.clone(),
+                },
+                initial_prompt: Some("Audit dependencies".to_string()),
+                max_runtime_minutes: Some(60),
+            })
+            .unwrap();
+        assert!(schedule.enabled);
+        assert!(schedule.next_run_at.is_some());
+
+        manager.set_schedule_enabled(&schedule.id, false).unwrap();
+        assert!(!manager.list_schedules()[0].enabled);
+
+        manager.delete_schedule(&schedule.id).unwrap();
+        assert!(manager.list_schedules().is_empty());
+        assert!(manager.delete_schedule(&schedule.id).is_err());
+    }
+
+    #[test]
+    fn test_deleting_a_session_deletes_its_schedules() {
+        let (_temp, manager) = test_manager();
+        let session = manager
+            .create_session(NewSessionInput {
+                title: "Audit".to_string(),
+                project_path: "".to_string(),
+                section_id: "default".to_string(),
+                tool: model::SessionTool::Claude,
+                command: "claude".to_string(),
+                icon: None,
+            })
+            .unwrap();
+        manager
+            .create_schedule(NewScheduleInput {
+                name: "Nightly".to_string(),
+                cron: "30 2 * * *".to_string(),
+                target: ScheduleTarget::Session {
+                    session_id: session.id.clone(),
+                },
+                initial_prompt: None,
+                max_runtime_minutes: None,
+            })
+            .unwrap();
+
+        manager.delete_session(&session.id).unwrap();
+        assert!(manager.list_schedules().is_empty());
+    }
+
+    #[test]
+    fn test_template_runs_keep_only_recent_sessions() {
+        let (_temp, manager) = test_manager();
+        let input = NewSessionInput {
+            title: "Triage".to_string(),
+            project_path: "".to_string(),
+            section_id: "default".to_string(),
+            tool: model::SessionTool::Shell,
+            command: "".to_string(),
+            icon: None,
+        };
+        let runs: Vec<ScheduleRun> = (0..5)
+            .map(|_| ScheduleRun {
+                session_id: Some(manager.create_session(input.clone()).unwrap().id),
+                started_at: "2026-01-01T00:00:00Z".to_string(),
+                finished_at: Some("2026-01-01T00:05:00Z".to_string()),
+                outcome: ScheduleOutcome::Completed,
+                exit_code: Some(0),
+                error: None,
+            })
+            .collect();
+        let latest = runs[4].session_id.clone().unwrap();
+        let schedule = ScheduleRecord {
+            id: "schedule-1".to_string(),
+            name: "Triage".to_string(),
+            cron: "@hourly".to_string(),
+            target: ScheduleTarget::Template { input },
+            initial_prompt: None,
+            max_runtime_minutes: None,
+            enabled: true,
+            created_at: "2026-01-01T00:00:00Z".to_string(),
+            next_run_at: None,
+            runs,
+        };
+
+        manager.prune_template_sessions(&schedule);
+        let left = manager.list_sessions();
+        assert_eq!(left.len(), scheduler::TEMPLATE_SESSIONS_KEPT - 1);
+        assert!(left.iter().any(|session| session.id == latest));
+    }
+
+    #[test]
+    fn test_settle_marks_runs_without_runtime_as_stopped() {
+        let (_temp, manager) = test_manager();
+        let now = OffsetDateTime::now_utc();
+        {
+            let mut snapshot = manager.snapshot.lock();
+            snapshot.schedules.push(ScheduleRecord {
+                id: "schedule-1".to_string(),
+                name: "Hourly".to_string(),
+                cron: "@hourly".to_string(),
+                target: ScheduleTarget::Session {
+                    session_id: "session-1".to_string(),
+                },
+                initial_prompt: None,
+                max_runtime_minutes: Some(5),
+                enabled: true,
+                created_at: chrono_now(),
+                next_run_at: None,
+                runs: vec![ScheduleRun {
+                    session_id: Some("session-1".to_string()),
+                    started_at: scheduler::format_timestamp(now),
+                    finished_at: None,
+                    outcome: ScheduleOutcome::Running,
+                    exit_code: None,
+                    error: None,
+                }],
+            });
+        }
+
+        manager.settle_schedule_runs(now);
+
+        let run = manager.list_schedules()[0].runs[0].clone();
+        assert_eq!(run.outcome, ScheduleOutcome::Stopped);
+        assert!(run.finished_at.is_some());
+    }
+
+    #[test]
+    fn test_reap_idle_sessions_stops_quiet_sessions_and_keeps_resume_ids() {
+        let (_temp, manager) = test_manager();
+        let new_session = |title: &str, tool: model::SessionTool| NewSessionInput {
+            title: title.to_string(),
+            project_path: "".to_string(),
+            section_id: "default".to_string(),
+            tool,
+            command: "claude".to_string(),
+            icon: None,
+        };
+        let quiet = manager
+            .create_session(new_session("Quiet", model::SessionTool::Claude))
+            .unwrap();
+        let busy = manager
+            .create_session(new_session("Busy", model::SessionTool::Claude))
+            .unwrap();
+        let shell = manager
+            .create_session(new_session("Shell", model::SessionTool::Shell))
+            .unwrap();
+        manager
+            .set_tool_session_id(&quiet.id, "claude", "abc-123".to_string())
+            .unwrap();
+
+        let started = Instant::now();
+        for (session, status) in [
+            (&quiet, SessionStatus::Waiting),
+            (&busy, SessionStatus::Running),
+            (&shell, SessionStatus::Idle),
+        ] {
+            manager.live_statuses.lock().insert(session.id.clone(), status);
+            manager.last_activity.lock().insert(session.id.clone(), started);
+        }
+
+        let settings: IdleSettings = toml::from_str(
+            r#"
+            enabled = true
+            [[policies]]
+            tool = "claude"
+            idle_hours = 2.0
+            "#,
+        )
+        .unwrap();
+
+        let later = started + Duration::from_secs(3 * 3600);
+        let reaped = manager.reap_idle_sessions(&settings, later);
+        assert_eq!(reaped.len(), 1);
+        assert_eq!(reaped[0].session_id, quiet.id);
+        assert_eq!(reaped[0].idle_minutes, 180);
+
+        assert!(manager.live_status(&quiet.id).is_none());
+        assert!(manager.last_activity.lock().get(&quiet.id).is_none());
+        assert_eq!(
+            manager.get_session(&quiet.id).unwrap().claude_session_id.as_deref(),
+            Some("abc-123")
+        );
+        assert!(manager.reap_idle_sessions(&settings, later).is_empty());
+    }
+
+    #[test]
+    fn test_bundle_round_trip_remaps_ids_and_paths() {
+        let (_temp, source) = test_manager();
+        let project = TempDir::new().unwrap();
+        let project_path = project.path().display().to_string();
+        let section = source
+            .create_section("Web".to_string(), project_path.clone(), None)
+            .unwrap();
+        let mut defaults = SectionDefaults::default();
+        defaults.env.insert("RUST_LOG".to_string(), "debug".to_string());
+        defaults.env.insert("GITHUB_TOKEN".to_string(), "ghp_secret".to_string());
+        source.set_section_defaults(&section.id, defaults).unwrap();
+        let lead = source
+            .create_session(NewSessionInput {
+                title: "Lead".to_string(),
+                project_path: project_path.clone(),
+                section_id: section.id.clone(),
+                tool: model::SessionTool::Claude,
+                command: "claude".to_string(),
+                icon: None,
+            })
+            .unwrap();
+        let helper = source
+            .create_agent_session(
+                NewSessionInput {
+                    title: "Helper".to_string(),
+                    project_path: project_path.clone(),
+                    section_id: section.id.clone(),
+                    tool: model::SessionTool::Shell,
+                    command: "/bin/bash".to_string(),
+                    icon: None,
+                },
+                &lead.id,
+            )
+            .unwrap();
+        source.record_screen_output(&helper.id, b"$ make\r\nok\r\n");
+
+        let export = ExportBundleInput {
+            path: String::new(),
+            section_id: Some(section.id.clone()),
+            session_ids: Vec::new(),
+            include_scrollback: true,
+            include_transcripts: false,
+        };
+        let bundle = source
+            .bundle_records(&export, |_| vec!["github".to_string()])
+            .unwrap();
+        assert_eq!(bundle.sections.len(), 1);
+        assert_eq!(bundle.sessions.len(), 2);
+        let env = &bundle.sessions[0].record.env;
+        assert_eq!(env["GITHUB_TOKEN"], bundle::REDACTED);
+        assert_eq!(env["RUST_LOG"], "debug");
+        assert_eq!(bundle.sections[0].defaults.env["GITHUB_TOKEN"], bundle::REDACTED);
+
+        let (_temp, target) = test_manager();
+        let moved = TempDir::new().unwrap();
+        let import = ImportBundleInput {
+            path: String::new(),
+            path_mappings: vec![bundle::PathMapping {
+                from: bundle.sections[0].path.clone(),
+                to: moved.path().display().to_string(),
+            }],
+            section_id: None,
+            approved_digest: None,
+        };
+        let (sections, sessions, redacted) = target.create_bundle_records(&bundle, &import).unwrap();
+        assert_eq!(sections.len(), 1);
+        assert_ne!(sections[0].id, section.id);
+        assert_eq!(sections[0].path, moved.path().display().to_string());
+
+        let imported_lead = &sessions[0];
+        let imported_helper = &sessions[1];
+        assert_ne!(imported_lead.id, lead.id);
+        assert_eq!(imported_helper.section_id, sections[0].id);
+        assert_eq!(imported_helper.project_path, moved.path().display().to_string());
+        assert_eq!(imported_helper.created_by.as_deref(), Some(imported_lead.id.as_str()));
+        assert_eq!(
+            target.read_output(&imported_helper.id, 10).unwrap(),
+            vec!["$ make", "ok"]
+        );
+
+        // Redacted secrets are reported instead of being imported as placeholders.
+        assert_eq!(imported_lead.env.get("RUST_LOG").map(String::as_str), Some("debug"));
+        assert!(!imported_lead.env.contains_key("GITHUB_TOKEN"));
+        assert!(!target.section_defaults(&sections[0].id).env.contains_key("GITHUB_TOKEN"));
+        let owners: Vec<&str> = redacted.iter().map(|var| var.owner.as_str()).collect();
+        assert_eq!(owners, vec!["Web", "Lead", "Helper"]);
+        assert!(redacted.iter().all(|var| var.name == "GITHUB_TOKEN"));
+    }
+
+    #[test]
+    fn test_bundle_import_rejects_missing_paths_before_creating_anything() {
+        let (_temp, manager) = test_manager();
+        let bundle = SessionBundle {
+            version: bundle::BUNDLE_VERSION,
+            exported_at: String::new(),
+            sections: Vec::new(),
+            sessions: vec![BundledSession {
+                record: manager
+                    .create_session(NewSessionInput {
+                        title: "Gone".to_string(),
+                        project_path: "/tmp".to_string(),
+                        section_id: "default".to_string(),
+                        tool: model::SessionTool::Shell,
+                        command: "/bin/bash".to_string(),
+                        icon: None,
+                    })
+                    .map(|mut record| {
+                        record.project_path = "/nonexistent/agent-term-bundle".to_string();
+                        record
+                    })
+                    .unwrap(),
+                mcp_names: Vec::new(),
+                scrollback: None,
+                transcript: None,
+            }],
+            mcps: Default::default(),
+        };
+        let before = manager.list_sessions().len();
+        let import = ImportBundleInput {
+            path: String::new(),
+            path_mappings: Vec::new(),
+            section_id: None,
+            approved_digest: None,
+        };
+        let err = manager.create_bundle_records(&bundle, &import).unwrap_err();
+        assert!(err.contains("add a path mapping"));
+        assert_eq!(manager.list_sessions().len(), before);
+    }
+
     #[test]
     fn test_is_ai_tool_returns_true_for_ai_tools() {
         assert!(SessionManager::is_ai_tool(&model::SessionTool::Claude));
@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
 /// Persistent metadata for a session record.
@@ -19,6 +21,11 @@ use serde::{Deserialize, Serialize};
 ///     loaded_mcp_names: vec![],
 ///     is_open: true,
 ///     tab_order: Some(0),
+///     sandbox: None,
+///     container: None,
+///     remote: None,
+///     created_by: None,
+///     env: BTreeMap::new(),
 /// };
 /// ```
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -40,6 +47,147 @@ pub struct SessionRecord {
     pub loaded_mcp_names: Vec<String>,
     pub is_open: bool,
     pub tab_order: Option<u32>,
+    /// Overrides the tool's sandbox settings when set.
+    #[serde(default)]
+    pub sandbox: Option<SessionSandbox>,
+    /// Run the tool inside a local container instead of on the host.
+    #[serde(default)]
+    pub container: Option<SessionContainer>,
+    /// Run the tool on another machine over ssh.
+    #[serde(default)]
+    pub remote: Option<SessionRemote>,
+    /// Session whose agent created this one through the agent-term MCP server.
+    #[serde(default)]
+    pub created_by: Option<String>,
+    /// Extra environment for the session's process, seeded from its section's defaults.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+}
+
+/// Remote host a session's tool runs on.
+///
+/// Example:
+/// ```rust,ignore
+/// let remote = SessionRemote {
+///     host: "devbox".to_string(),
+///     remote_path: "~/src/api".to_string(),
+///     command: "claude".to_string(),
+///     ssh_args: vec![],
+///     reconnect: true,
+/// };
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionRemote {
+    /// Host or alias from `~/.ssh/config`.
+    pub host: String,
+    #[serde(default)]
+    pub remote_path: String,
+    /// Tool command on the remote host; empty starts a login shell.
+    #[serde(default)]
+    pub command: String,
+    /// Extra ssh options placed before the host.
+    #[serde(default)]
+    pub ssh_args: Vec<String>,
+    /// Restart the session when the connection drops.
+    #[serde(default = "default_remote_reconnect")]
+    pub reconnect: bool,
+}
+
+fn default_remote_reconnect() -> bool {
+    true
+}
+
+/// Container a session's tool runs in.
+///
+/// Example:
+/// ```rust,ignore
+/// let container = SessionContainer {
+///     engine: ContainerEngine::Podman,
+///     image: "ghcr.io/acme/agent:latest".to_string(),
+///     run_args: vec!["--network".to_string(), "none".to_string()],
+/// };
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionContainer {
+    #[serde(default)]
+    pub engine: ContainerEngine,
+    pub image: String,
+    /// Extra `run` arguments placed before the image name.
+    #[serde(default)]
+    pub run_args: Vec<String>,
+}
+
+/// Container CLI used to run a session.
+///
+/// Example:
+/// ```rust,ignore
+/// let engine = ContainerEngine::Docker;
+/// ```
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ContainerEngine {
+    #[default]
+    Docker,
+    Podman,
+}
+
+impl ContainerEngine {
+    pub fn program(&self) -> &'static str {
+        match self {
+            ContainerEngine::Docker => "docker",
+            ContainerEngine::Podman => "podman",
+        }
+    }
+}
+
+/// Isolation applied to a session's process.
+///
+/// Example:
+/// ```rust,ignore
+/// let sandbox = SessionSandbox {
+///     enabled: true,
+///     mode: SandboxMode::Bubblewrap,
+///     wrapper: vec![],
+///     writable_paths: vec!["~/.cache/cargo".to_string()],
+///     network: false,
+/// };
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSandbox {
+    pub enabled: bool,
+    #[serde(default)]
+    pub mode: SandboxMode,
+    /// Wrapper command and leading args for `SandboxMode::Wrapper`.
+    #[serde(default)]
+    pub wrapper: Vec<String>,
+    /// Writable paths in addition to the project directory.
+    #[serde(default)]
+    pub writable_paths: Vec<String>,
+    #[serde(default = "default_sandbox_network")]
+    pub network: bool,
+}
+
+fn default_sandbox_network() -> bool {
+    true
+}
+
+/// How a sandbox is enforced.
+///
+/// Example:
+/// ```rust,ignore
+/// let mode = SandboxMode::Wrapper;
+/// ```
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SandboxMode {
+    /// Linux namespaces via `bwrap`: read-only root, writable project.
+    #[default]
+    Bubblewrap,
+    /// A user-supplied command that runs the tool, e.g. `firejail` or `sandbox-exec`.
+    Wrapper,
 }
 
 /// Section metadata for organizing sessions.
@@ -53,6 +201,8 @@ pub struct SessionRecord {
 ///     icon: None,
 ///     collapsed: false,
 ///     order: 0,
+///     parent_id: None,
+///     defaults: SectionDefaults::default(),
 /// };
 /// ```
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -64,7 +214,45 @@ pub struct SectionRecord {
     #[serde(default)]
     pub icon: Option<String>,
     pub collapsed: bool,
+    /// Position among sections with the same parent.
     pub order: u32,
+    /// Section this one is nested in; `None` for top-level sections.
+    #[serde(default)]
+    pub parent_id: Option<String>,
+    #[serde(default)]
+    pub defaults: SectionDefaults,
+}
+
+/// Settings new sessions in a section inherit. Nested sections inherit their
+/// parent's defaults and override or extend them.
+///
+/// Example:
+/// ```rust,ignore
+/// let defaults = SectionDefaults {
+///     tool: Some(SessionTool::Claude),
+///     command: Some("claude".to_string()),
+///     env: BTreeMap::from([("RUST_LOG".to_string(), "debug".to_string())]),
+///     mcp_names: vec!["github".to_string()],
+///     auto_start: Vec::new(),
+/// };
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SectionDefaults {
+    #[serde(default)]
+    pub tool: Option<SessionTool>,
+    /// Used when a session is created with an empty command.
+    #[serde(default)]
+    pub command: Option<String>,
+    /// Merged over the parent's env.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Attached to a new session's project, along with the parent's.
+    #[serde(default)]
+    pub mcp_names: Vec<String>,
+    /// Sessions started when the app launches. Not inherited.
+    #[serde(default)]
+    pub auto_start: Vec<String>,
 }
 
 /// Session state used for UI status indicators.
@@ -100,6 +288,32 @@ pub enum SessionTool {
     Custom(String),
 }
 
+impl SessionTool {
+    /// Lowercase name used to refer to the tool in config.toml.
+    pub fn name(&self) -> &str {
+        match self {
+            SessionTool::Shell => "shell",
+            SessionTool::Claude => "claude",
+            SessionTool::Gemini => "gemini",
+            SessionTool::Codex => "codex",
+            SessionTool::OpenCode => "opencode",
+            SessionTool::Custom(name) => name,
+        }
+    }
+
+    /// Inverse of [`SessionTool::name`]; unknown names become custom tools.
+    pub fn from_name(name: &str) -> SessionTool {
+        match name.trim().to_lowercase().as_str() {
+            "shell" => SessionTool::Shell,
+            "claude" => SessionTool::Claude,
+            "gemini" => SessionTool::Gemini,
+            "codex" => SessionTool::Codex,
+            "opencode" => SessionTool::OpenCode,
+            _ => SessionTool::Custom(name.trim().to_string()),
+        }
+    }
+}
+
 /// Input payload for creating a new session.
 ///
 /// Example:
@@ -123,3 +337,162 @@ pub struct NewSessionInput {
     #[serde(default)]
     pub icon: Option<String>,
 }
+
+/// Per-session outcome of a broadcast.
+///
+/// Example:
+/// ```rust,ignore
+/// let result = BroadcastResult {
+///     session_id: "session-1".to_string(),
+///     title: "Claude 1".to_string(),
+///     status: Some(SessionStatus::Waiting),
+///     delivered: true,
+///     skipped: false,
+///     error: None,
+/// };
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BroadcastResult {
+    pub session_id: String,
+    pub title: String,
+    pub status: Option<SessionStatus>,
+    pub delivered: bool,
+    pub skipped: bool,
+    pub error: Option<String>,
+}
+
+/// A follow-up prompt waiting to be delivered to a session.
+///
+/// Example:
+/// ```rust,ignore
+/// let prompt = QueuedPrompt {
+///     id: "prompt-1".to_string(),
+///     text: "Now add tests".to_string(),
+///     created_at: "2025-01-01T00:00:00Z".to_string(),
+/// };
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueuedPrompt {
+    pub id: String,
+    pub text: String,
+    pub created_at: String,
+}
+
+/// What a schedule launches on each run.
+///
+/// Example:
+/// ```rust,ignore
+/// let target = ScheduleTarget::Session { session_id: "session-1".to_string() };
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ScheduleTarget {
+    /// Start an existing session.
+    #[serde(rename_all = "camelCase")]
+    Session { session_id: String },
+    /// Create a fresh session from a template on every run.
+    #[serde(rename_all = "camelCase")]
+    Template { input: NewSessionInput },
+}
+
+/// Result of a finished (or still active) scheduled run.
+///
+/// Example:
+/// ```rust,ignore
+/// let outcome = ScheduleOutcome::TimedOut;
+/// ```
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ScheduleOutcome {
+    Running,
+    Completed,
+    Failed,
+    TimedOut,
+    Stopped,
+    Skipped,
+}
+
+/// A single execution of a schedule.
+///
+/// Example:
+/// ```rust,ignore
+/// let run = ScheduleRun {
+///     session_id: Some("session-1".to_string()),
+///     started_at: "2025-01-01T02:30:00Z".to_string(),
+///     finished_at: None,
+///     outcome: ScheduleOutcome::Running,
+///     exit_code: None,
+///     error: None,
+/// };
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleRun {
+    pub session_id: Option<String>,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+    pub outcome: ScheduleOutcome,
+    pub exit_code: Option<u32>,
+    pub error: Option<String>,
+}
+
+/// A cron-driven job that starts a session and optionally sends it a prompt.
+///
+/// Example:
+/// ```rust,ignore
+/// let schedule = ScheduleRecord {
+///     id: "schedule-1".to_string(),
+///     name: "Nightly audit".to_string(),
+///     cron: "30 2 * * *".to_string(),
+///     target: ScheduleTarget::Session { session_id: "session-1".to_string() },
+///     initial_prompt: Some("Audit dependencies".to_string()),
+///     max_runtime_minutes: Some(60),
+///     enabled: true,
+///     created_at: "2025-01-01T00:00:00Z".to_string(),
+///     next_run_at: Some("2025-01-01T02:30:00Z".to_string()),
+///     runs: vec![],
+/// };
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleRecord {
+    pub id: String,
+    pub name: String,
+    pub cron: String,
+    pub target: ScheduleTarget,
+    #[serde(default)]
+    pub initial_prompt: Option<String>,
+    #[serde(default)]
+    pub max_runtime_minutes: Option<u32>,
+    pub enabled: bool,
+    pub created_at: String,
+    pub next_run_at: Option<String>,
+    #[serde(default)]
+    pub runs: Vec<ScheduleRun>,
+}
+
+/// Input payload for creating a schedule.
+///
+/// Example:
+/// ```rust,ignore
+/// let input = NewScheduleInput {
+///     name: "Hourly triage".to_string(),
+///     cron: "@hourly".to_string(),
+///     target: ScheduleTarget::Session { session_id: "session-1".to_string() },
+///     initial_prompt: None,
+///     max_runtime_minutes: Some(15),
+/// };
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewScheduleInput {
+    pub name: String,
+    pub cron: String,
+    pub target: ScheduleTarget,
+    #[serde(default)]
+    pub initial_prompt: Option<String>,
+    #[serde(default)]
+    pub max_runtime_minutes: Option<u32>,
+}
@@ -0,0 +1,492 @@
+// ABOUTME: Samples the process tree under each session's child through /proc on Linux.
+// ABOUTME: Reports CPU, memory and descendants per session and raises threshold alerts.
+
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::mcp::config::MonitorSettings;
+
+/// A single process in a session's tree.
+///
+/// Example:
+/// ```rust,ignore
+/// let process = ProcessInfo {
+///     pid: 4242,
+///     ppid: 4200,
+///     name: "cargo".to_string(),
+///     cmdline: "cargo build --release".to_string(),
+///     cpu_percent: 97.5,
+///     rss_kb: 812_000,
+/// };
+/// ```
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub ppid: u32,
+    pub name: String,
+    pub cmdline: String,
+    pub cpu_percent: f64,
+    pub rss_kb: u64,
+}
+
+/// Aggregate resource usage of a session's process tree.
+///
+/// Example:
+/// ```rust,ignore
+/// let usage = manager.session_usage("session-1")?;
+/// println!("{} processes, {} kB", usage.processes.len(), usage.rss_kb);
+/// ```
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionUsage {
+    pub session_id: String,
+    pub root_pid: u32,
+    pub cpu_percent: f64,
+    pub rss_kb: u64,
+    pub processes: Vec<ProcessInfo>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum ResourceKind {
+    Cpu,
+    Memory,
+    Processes,
+}
+
+/// Emitted when a session's usage rises above a configured threshold.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceAlert {
+    pub session_id: String,
+    pub kind: ResourceKind,
+    pub value: f64,
+    pub threshold: f64,
+}
+
+#[derive(Debug, Clone)]
+struct ProcStat {
+    ppid: u32,
+    name: String,
+    cpu_ticks: u64,
+    rss_pages: u64,
+}
+
+/// Keeps the previous CPU tick sample per pid so usage can be reported as a rate.
+#[derive(Default)]
+pub struct ProcessMonitor {
+    last_ticks: Mutex<HashMap<u32, (u64, Instant)>>,
+    alerts: Mutex<HashSet<(String, ResourceKind)>>,
+}
+
+impl ProcessMonitor {
+    /// Sample every `(session_id, root_pid)` pair, reading only the processes
+    /// below those roots. CPU is reported relative to the previous sample, so
+    /// the first one reads 0.
+    pub fn sample(&self, roots: &[(String, u32)]) -> Vec<SessionUsage> {
+        let pids: Vec<u32> = roots.iter().map(|(_, root)| *root).collect();
+        let table = read_process_tree(&pids);
+        let now = Instant::now();
+        let (clock_ticks, page_kb) = (clock_ticks_per_second(), page_size_kb());
+        let mut last = self.last_ticks.lock();
+
+        let usages = roots
+            .iter()
+            .map(|(session_id, root)| {
+                let processes: Vec<ProcessInfo> = descendants(&table, *root)
+                    .into_iter()
+                    .filter_map(|pid| table.get(&pid).map(|stat| (pid, stat)))
+                    .map(|(pid, stat)| {
+                        let cpu_percent = match last.get(&pid) {
+                            Some((ticks, at)) => cpu_percent(
+                                stat.cpu_ticks.saturating_sub(*ticks),
+                                now.saturating_duration_since(*at),
+                                clock_ticks,
+                            ),
+                            None => 0.0,
+                        };
+                        last.insert(pid, (stat.cpu_ticks, now));
+                        ProcessInfo {
+                            pid,
+                            ppid: stat.ppid,
+                            name: stat.name.clone(),
+                            cmdline: read_cmdline(pid),
+                            cpu_percent,
+                            rss_kb: stat.rss_pages * page_kb,
+                        }
+                    })
+                    .collect();
+                SessionUsage {
+                    session_id: session_id.clone(),
+                    root_pid: *root,
+                    cpu_percent: processes.iter().map(|p| p.cpu_percent).sum(),
+                    rss_kb: processes.iter().map(|p| p.rss_kb).sum(),
+                    processes,
+                }
+            })
+            .collect();
+
+        // Forget samples for processes that have exited.
+        last.retain(|pid, _| table.contains_key(pid));
+        usages
+    }
+
+    /// Alerts for thresholds `usage` has newly crossed. An alert fires once and
+    /// re-arms after the value drops back under its threshold.
+    pub fn check_thresholds(
+        &self,
+        usage: &SessionUsage,
+        settings: &MonitorSettings,
+    ) -> Vec<ResourceAlert> {
+        let checks = [
+            (ResourceKind::Cpu, usage.cpu_percent, settings.cpu_percent),
+            (
+                ResourceKind::Memory,
+                usage.rss_kb as f64 / 1024.0,
+                settings.memory_mb as f64,
+            ),
+            (
+                ResourceKind::Processes,
+                usage.processes.len() as f64,
+                settings.max_processes as f64,
+            ),
+        ];
+
+        let mut alerts = self.alerts.lock();
+        let mut fired = Vec::new();
+        for (kind, value, threshold) in checks {
+            let key = (usage.session_id.clone(), kind);
+            if threshold <= 0.0 || value <= threshold {
+                alerts.remove(&key);
+            } else if alerts.insert(key) {
+                fired.push(ResourceAlert {
+                    session_id: usage.session_id.clone(),
+                    kind,
+                    value,
+                    threshold,
+                });
+            }
+        }
+        fired
+    }
+
+    /// Drop alert state for sessions that are no longer running.
+    pub fn retain_sessions(&self, session_ids: &HashSet<&str>) {
+        self.alerts
+            .lock()
+            .retain(|(session_id, _)| session_ids.contains(session_id.as_str()));
+    }
+
+    /// Whether `pid` is a strict descendant of `root`.
+    pub fn is_descendant(root: u32, pid: u32) -> bool {
+        descendant_pids(root).contains(&pid)
+    }
+}
+
+/// Every process below `pid`, excluding `pid` itself.
+pub fn descendant_pids(pid: u32) -> Vec<u32> {
+    descendants(&read_process_tree(&[pid]), pid)
+        .into_iter()
+        .filter(|found| *found != pid)
+        .collect()
+}
+
+/// Parent of `pid`, or `None` when it has exited or can't be read.
+#[cfg(target_os = "linux")]
+pub fn parent_pid(pid: u32) -> Option<u32> {
+    std::fs::read_to_string(format!("/proc/{}/stat", pid))
+        .ok()
+        .and_then(|content| parse_stat(&content))
+        .map(|stat| stat.ppid)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn parent_pid(_pid: u32) -> Option<u32> {
+    None
+}
+
+/// Start the background sampler. Settings are cached and re-read only when
+/// config.toml changes. Sampling relies on /proc, so this is Linux only.
+#[cfg(target_os = "linux")]
+pub fn spawn_process_monitor(app: AppHandle) {
+    use std::time::SystemTime;
+
+    use tauri::{Emitter, Manager};
+
+    use super::SessionManager;
+    use crate::mcp::config::get_config_path;
+    use crate::mcp::McpManager;
+
+    std::thread::spawn(move || {
+        let mut settings = MonitorSettings::default();
+        let mut loaded_at: Option<Option<SystemTime>> = None;
+        loop {
+            let modified = get_config_path()
+                .ok()
+                .and_then(|path| std::fs::metadata(path).ok())
+                .and_then(|meta| meta.modified().ok());
+            if loaded_at != Some(modified) {
+                settings = app
+                    .state::<McpManager>()
+                    .load_config_blocking()
+                    .map(|config| config.monitor)
+                    .unwrap_or_default();
+                loaded_at = Some(modified);
+            }
+            if settings.enabled {
+                let manager = app.state::<SessionManager>();
+                let usages = manager.sample_session_usage();
+                for usage in &usages {
+                    for alert in manager.monitor.check_thresholds(usage, &settings) {
+                        let _ = app.emit("session-resource-alert", alert);
+                    }
+                }
+                let running: HashSet<&str> =
+                    usages.iter().map(|u| u.session_id.as_str()).collect();
+                manager.monitor.retain_sessions(&running);
+            }
+            std::thread::sleep(Duration::from_secs(settings.interval_seconds.max(1)));
+        }
+    });
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn spawn_process_monitor(_app: AppHandle) {}
+
+/// `root` and every process below it.
+fn descendants(table: &HashMap<u32, ProcStat>, root: u32) -> Vec<u32> {
+    let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+    for (pid, stat) in table {
+        children.entry(stat.ppid).or_default().push(*pid);
+    }
+    let mut found = Vec::new();
+    if !table.contains_key(&root) {
+        return found;
+    }
+    let mut queue = vec![root];
+    while let Some(pid) = queue.pop() {
+        found.push(pid);
+        if let Some(kids) = children.get(&pid) {
+            queue.extend(kids.iter().copied().filter(|kid| *kid != pid));
+        }
+    }
+    found
+}
+
+fn cpu_percent(ticks: u64, elapsed: Duration, clock_ticks: u64) -> f64 {
+    let seconds = elapsed.as_secs_f64();
+    if seconds <= 0.0 || clock_ticks == 0 {
+        return 0.0;
+    }
+    ticks as f64 / clock_ticks as f64 / seconds * 100.0
+}
+
+/// Parse `/proc/<pid>/stat`. The command name is wrapped in parentheses and may
+/// itself contain spaces or parentheses, so fields are split after the last `)`.
+fn parse_stat(content: &str) -> Option<ProcStat> {
+    let open = content.find('(')?;
+    let close = content.rfind(')')?;
+    let name = content.get(open + 1..close)?.to_string();
+    let fields: Vec<&str> = content.get(close + 1..)?.split_whitespace().collect();
+    // Offsets are relative to field 3 (state) of proc(5).
+    let ppid = fields.get(1)?.parse().ok()?;
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    let rss_pages = fields.get(21)?.parse::<i64>().ok()?.max(0) as u64;
+    Some(ProcStat {
+        ppid,
+        name,
+        cpu_ticks: utime + stime,
+        rss_pages,
+    })
+}
+
+/// Read `roots` and everything below them. Children are found through
+/// `/proc/<pid>/task/<tid>/children`, so unrelated processes are never read.
+#[cfg(target_os = "linux")]
+fn read_process_tree(roots: &[u32]) -> HashMap<u32, ProcStat> {
+    let mut table = HashMap::new();
+    let mut queue = roots.to_vec();
+    while let Some(pid) = queue.pop() {
+        if table.contains_key(&pid) {
+            continue;
+        }
+        // Processes can exit between listing and reading.
+        let Some(stat) = std::fs::read_to_string(format!("/proc/{}/stat", pid))
+            .ok()
+            .and_then(|content| parse_stat(&content))
+        else {
+            continue;
+        };
+        table.insert(pid, stat);
+        let Ok(tasks) = std::fs::read_dir(format!("/proc/{}/task", pid)) else {
+            continue;
+        };
+        for task in tasks.flatten() {
+            if let Ok(children) = std::fs::read_to_string(task.path().join("children")) {
+                queue.extend(children.split_whitespace().filter_map(|kid| kid.parse::<u32>().ok()));
+            }
+        }
+    }
+    table
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_process_tree(_roots: &[u32]) -> HashMap<u32, ProcStat> {
+    HashMap::new()
+}
+
+fn read_cmdline(pid: u32) -> String {
+    std::fs::read(format!("/proc/{}/cmdline", pid))
+        .map(|raw| {
+            String::from_utf8_lossy(&raw)
+                .split('\0')
+                .filter(|part| !part.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(unix)]
+fn clock_ticks_per_second() -> u64 {
+    // SAFETY: sysconf has no preconditions.
+    let ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+    if ticks > 0 {
+        ticks as u64
+    } else {
+        100
+    }
+}
+
+#[cfg(not(unix))]
+fn clock_ticks_per_second() -> u64 {
+    100
+}
+
+#[cfg(unix)]
+fn page_size_kb() -> u64 {
+    // SAFETY: sysconf has no preconditions.
+    let size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    if size > 0 {
+        size as u64 / 1024
+    } else {
+        4
+    }
+}
+
+#[cfg(not(unix))]
+fn page_size_kb() -> u64 {
+    4
+}
+
+/// Send SIGTERM (or SIGKILL when `force`) to a single process.
+#[cfg(unix)]
+pub fn kill_process(pid: u32, force: bool) -> Result<(), String> {
+    let signal = if force { libc::SIGKILL } else { libc::SIGTERM };
+    let pid = i32::try_from(pid).map_err(|_| format!("invalid pid: {}", pid))?;
+    // SAFETY: kill has no memory-safety preconditions; pid was checked to be positive.
+    if unsafe { libc::kill(pid, signal) } != 0 {
+        return Err(format!(
+            "failed to signal process {}: {}",
+            pid,
+            std::io::Error::last_os_error()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn kill_process(_pid: u32, _force: bool) -> Result<(), String> {
+    Err("killing individual processes is not supported on this platform".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stat(ppid: u32) -> ProcStat {
+        ProcStat {
+            ppid,
+            name: "proc".to_string(),
+            cpu_ticks: 0,
+            rss_pages: 0,
+        }
+    }
+
+    #[test]
+    fn test_parse_stat_handles_parens_in_name() {
+        let line = "4242 (tmux: (server)) S 4200 4242 4242 0 -1 4194560 100 0 0 0 \
+                    150 50 0 0 20 0 1 0 12345 1000000 256 18446744073709551615";
+        let stat = parse_stat(line).unwrap();
+        assert_eq!(stat.name, "tmux: (server)");
+        assert_eq!(stat.ppid, 4200);
+        assert_eq!(stat.cpu_ticks, 200);
+        assert_eq!(stat.rss_pages, 256);
+    }
+
+    #[test]
+    fn test_descendants_walks_whole_tree() {
+        let table: HashMap<u32, ProcStat> = [
+            (1, stat(0)),
+            (10, stat(1)),
+            (11, stat(10)),
+            (12, stat(11)),
+            (13, stat(10)),
+            (20, stat(1)),
+        ]
+        .into_iter()
+        .collect();
+        let mut found = descendants(&table, 10);
+        found.sort();
+        assert_eq!(found, vec![10, 11, 12, 13]);
+        assert!(descendants(&table, 99).is_empty());
+    }
+
+    #[test]
+    fn test_thresholds_fire_once_until_cleared() {
+        let monitor = ProcessMonitor::default();
+        let settings = MonitorSettings {
+            cpu_percent: 80.0,
+            ..MonitorSettings::default()
+        };
+        let mut usage = SessionUsage {
+            session_id: "session-1".to_string(),
+            root_pid: 1,
+            cpu_percent: 150.0,
+            rss_kb: 0,
+            processes: Vec::new(),
+        };
+        let alerts = monitor.check_thresholds(&usage, &settings);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].kind, ResourceKind::Cpu);
+        assert!(monitor.check_thresholds(&usage, &settings).is_empty());
+
+        usage.cpu_percent = 10.0;
+        assert!(monitor.check_thresholds(&usage, &settings).is_empty());
+        usage.cpu_percent = 90.0;
+        assert_eq!(monitor.check_thresholds(&usage, &settings).len(), 1);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_sample_and_kill_child_process() {
+        let mut child = std::process::Command::new("sleep").arg("30").spawn().unwrap();
+        let root = std::process::id();
+        let monitor = ProcessMonitor::default();
+
+        let usage = monitor.sample(&[("session-1".to_string(), root)]).remove(0);
+        assert!(usage.rss_kb > 0);
+        assert!(usage.processes.iter().any(|p| p.pid == child.id() && p.name == "sleep"));
+        assert!(ProcessMonitor::is_descendant(root, child.id()));
+        assert!(!ProcessMonitor::is_descendant(root, root));
+
+        kill_process(child.id(), true).unwrap();
+        assert!(!child.wait().unwrap().success());
+    }
+}
@@ -0,0 +1,29 @@
+// ABOUTME: Stops sessions that have sat idle longer than their configured policy allows.
+// ABOUTME: Runs as a background thread and emits an event for every session it stops.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::SessionManager;
+use crate::mcp::McpManager;
+
+/// Start the idle reaper thread. Settings are re-read from config.toml on every pass
+/// so policy edits apply without a restart.
+pub fn spawn_idle_reaper(app: AppHandle) {
+    thread::spawn(move || loop {
+        let settings = app
+            .state::<McpManager>()
+            .load_config_blocking()
+            .map(|config| config.idle)
+            .unwrap_or_default();
+        if settings.enabled {
+            let manager = app.state::<SessionManager>();
+            for event in manager.reap_idle_sessions(&settings, Instant::now()) {
+                let _ = app.emit("session-reaped", event);
+            }
+        }
+        thread::sleep(Duration::from_secs(settings.check_interval_minutes.max(1) * 60));
+    });
+}
@@ -0,0 +1,215 @@
+// ABOUTME: Builds `ssh -t` commands for sessions whose tool runs on a remote host.
+// ABOUTME: Also defines the reconnect backoff used when an ssh connection drops.
+
+use std::time::Duration;
+
+use super::model::{SessionRecord, SessionRemote, SessionTool};
+use super::tools::CommandSpec;
+
+/// ssh exits with 255 when the connection itself fails or drops.
+pub const SSH_CONNECTION_LOST: u32 = 255;
+
+/// Reconnect attempts before a dropped remote session is left stopped.
+pub const MAX_RECONNECT_ATTEMPTS: u32 = 5;
+
+/// A connection that lasted this long counts as healthy and resets the attempt count.
+pub const STABLE_CONNECTION: Duration = Duration::from_secs(60);
+
+pub fn validate(remote: &SessionRemote) -> Result<(), String> {
+    if remote.host.trim().is_empty() {
+        return Err("Remote host is empty".to_string());
+    }
+    // Anything starting with '-' would be parsed by ssh as an option.
+    if remote.host.starts_with('-') {
+        return Err(format!("Invalid remote host: {}", remote.host));
+    }
+    Ok(())
+}
+
+/// Delay before reconnect attempt `attempt` (1-based): 2s, 4s, 8s, ... capped at 30s.
+pub fn reconnect_delay(attempt: u32) -> Duration {
+    let secs = 2u64.saturating_pow(attempt.clamp(1, 5));
+    Duration::from_secs(secs.min(30))
+}
+
+/// `ssh -t <host> "cd <remote_path> && exec <command> <tool args>"`. Tool args
+/// carry resume ids, which refer to sessions stored on the remote host.
+pub fn build_remote_command(
+    record: &SessionRecord,
+    remote: &SessionRemote,
+) -> Result<CommandSpec, String> {
+    validate(remote)?;
+    let command = if remote.command.trim().is_empty() {
+        // No tool: the remote login shell.
+        "exec \"${SHELL:-/bin/sh}\" -l".to_string()
+    } else {
+        let mut parts = vec![remote.command.trim().to_string()];
+        parts.extend(remote_tool_args(record).iter().map(|arg| shell_quote(arg)));
+        format!("exec {}", parts.join(" "))
+    };
+    let script = if remote.remote_path.trim().is_empty() {
+        command
+    } else {
+        format!("cd {} && {}", quote_remote_path(remote.remote_path.trim()), command)
+    };
+
+    let mut args = vec![
+        "-t".to_string(),
+        // Detect dead connections so the session can reconnect.
+        "-o".to_string(),
+        "ServerAliveInterval=15".to_string(),
+        "-o".to_string(),
+        "ServerAliveCountMax=3".to_string(),
+    ];
+    args.extend(remote.ssh_args.iter().cloned());
+    args.push(remote.host.trim().to_string());
+    args.push(script);
+
+    Ok(CommandSpec {
+        program: "ssh".to_string(),
+        args,
+        env: Vec::new(),
+    })
+}
+
+fn remote_tool_args(record: &SessionRecord) -> Vec<String> {
+    let resume = match record.tool {
+        SessionTool::Claude => record.claude_session_id.as_ref(),
+        SessionTool::Gemini => record.gemini_session_id.as_ref(),
+        _ => None,
+    };
+    match resume {
+        Some(id) => vec!["--resume".to_string(), id.clone()],
+        None => Vec::new(),
+    }
+}
+
+/// Like `shell_quote`, but keeps a leading `~/` unquoted so the remote shell expands it.
+fn quote_remote_path(path: &str) -> String {
+    match path.strip_prefix("~/") {
+        Some(rest) if !rest.is_empty() => format!("~/{}", shell_quote(rest)),
+        _ if path == "~" || path == "~/" => "~".to_string(),
+        _ => shell_quote(path),
+    }
+}
+
+/// Quote `value` for a POSIX shell. The remote command is a single string that
+/// the remote login shell parses.
+fn shell_quote(value: &str) -> String {
+    if !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=:@%+,".contains(c))
+    {
+        return value.to_string();
+    }
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::model::SessionStatus;
+
+    fn remote_record(tool: SessionTool, remote: SessionRemote) -> SessionRecord {
+        SessionRecord {
+            id: "session-1".to_string(),
+            title: "Remote".to_string(),
+            project_path: String::new(),
+            section_id: "default".to_string(),
+            tool,
+            command: "claude".to_string(),
+            icon: None,
+            status: SessionStatus::Idle,
+            created_at: String::new(),
+            last_accessed_at: None,
+            claude_session_id: Some("abc-123".to_string()),
+            gemini_session_id: None,
+            loaded_mcp_names: Vec::new(),
+            is_open: true,
+            tab_order: None,
+            sandbox: None,
+            container: None,
+            remote: Some(remote),
+            created_by: None,
+            env: Default::default(),
+        }
+    }
+
+    fn remote(command: &str) -> SessionRemote {
+        SessionRemote {
+            host: "devbox".to_string(),
+            remote_path: "~/work/my repo".to_string(),
+            command: command.to_string(),
+            ssh_args: vec!["-p".to_string(), "2222".to_string()],
+            reconnect: true,
+        }
+    }
+
+    #[test]
+    fn test_build_remote_command_resumes_remote_session() {
+        let record = remote_record(SessionTool::Claude, remote("claude"));
+        let spec = build_remote_command(&record, record.remote.as_ref().unwrap()).unwrap();
+        assert_eq!(spec.program, "ssh");
+        assert_eq!(spec.args[0], "-t");
+        assert_eq!(&spec.args[5..8], ["-p", "2222", "devbox"]);
+        assert_eq!(
+            spec.args[8],
+            "cd ~/'work/my repo' && exec claude --resume abc-123"
+        );
+    }
+
+    #[test]
+    fn test_build_remote_command_defaults_to_login_shell() {
+        let mut shell = remote("");
+        shell.remote_path = String::new();
+        let record = remote_record(SessionTool::Shell, shell);
+        let spec = build_remote_command(&record, record.remote.as_ref().unwrap()).unwrap();
+        assert_eq!(spec.args.last().unwrap(), "exec \"${SHELL:-/bin/sh}\" -l");
+    }
+
+    #[test]
+    fn test_validate_rejects_option_like_hosts() {
+        let mut bad = remote("claude");
+        bad.host = "-oProxyCommand=evil".to_string();
+        assert!(validate(&bad).is_err());
+        bad.host = "  ".to_string();
+        assert!(validate(&bad).is_err());
+    }
+
+    #[test]
+    fn test_shell_quote_and_backoff() {
+        assert_eq!(shell_quote("abc-123"), "abc-123");
+        assert_eq!(shell_quote("it's"), "'it'\\''s'");
+        assert_eq!(reconnect_delay(1), Duration::from_secs(2));
+        assert_eq!(reconnect_delay(3), Duration::from_secs(8));
+        assert_eq!(reconnect_delay(9), Duration::from_secs(30));
+    }
+
+    /// Runs against a real sshd. Point `AGENT_TERM_TEST_SSH_HOST` at a host alias
+    /// that accepts key auth without prompting, e.g. a local sshd on localhost:
+    /// `AGENT_TERM_TEST_SSH_HOST=localhost cargo test remote -- --ignored`
+    #[test]
+    #[ignore]
+    fn test_remote_command_against_sshd() {
+        let host = std::env::var("AGENT_TERM_TEST_SSH_HOST")
+            .expect("AGENT_TERM_TEST_SSH_HOST must be set");
+        let remote = SessionRemote {
+            host,
+            remote_path: "/tmp".to_string(),
+            command: "echo agent-term-$(pwd)".to_string(),
+            ssh_args: vec!["-o".to_string(), "BatchMode=yes".to_string()],
+            reconnect: false,
+        };
+        let mut record = remote_record(SessionTool::Custom("echo".to_string()), remote);
+        record.claude_session_id = None;
+        let spec = build_remote_command(&record, record.remote.as_ref().unwrap()).unwrap();
+
+        let output = std::process::Command::new(&spec.program)
+            .args(&spec.args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        assert!(String::from_utf8_lossy(&output.stdout).contains("agent-term-/tmp"));
+    }
+}
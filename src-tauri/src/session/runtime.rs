@@ -29,6 +29,8 @@ pub struct SessionRuntime {
     reader_thread: Option<JoinHandle<()>>,
     shutdown_tx: Sender<()>,
     shutdown_called: bool,
+    stop_command: Option<Vec<String>>,
+    started_at: Instant,
 }
 
 impl SessionRuntime {
@@ -48,9 +50,17 @@ impl SessionRuntime {
             reader_thread: Some(reader_thread),
             shutdown_tx,
             shutdown_called: false,
+            stop_command: None,
+            started_at: Instant::now(),
         }
     }
 
+    /// Command run at shutdown before the process is signalled, e.g. to stop a
+    /// container the session's CLI is attached to.
+    pub fn set_stop_command(&mut self, command: Vec<String>) {
+        self.stop_command = Some(command);
+    }
+
     pub fn write(&mut self, data: &[u8]) -> Result<(), String> {
         let writer = self
             .writer
@@ -78,6 +88,30 @@ impl SessionRuntime {
             .map_err(|e| format!("failed to resize: {}", e))
     }
 
+    /// Current PTY size as (rows, cols).
+    pub fn size(&self) -> Option<(u16, u16)> {
+        let size = self.master.as_ref()?.get_size().ok()?;
+        Some((size.rows, size.cols))
+    }
+
+    /// Time since the session's process was spawned.
+    pub fn uptime(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+
+    /// OS process id of the session's top-level child.
+    pub fn pid(&self) -> Option<u32> {
+        self.child.process_id()
+    }
+
+    /// Exit code of the child if it has already exited.
+    pub fn exit_code(&mut self) -> Option<u32> {
+        match self.child.try_wait() {
+            Ok(Some(status)) => Some(status.exit_code()),
+            _ => None,
+        }
+    }
+
     pub fn shutdown(&mut self) {
         if self.shutdown_called {
             diagnostics::log(format!(
@@ -111,9 +145,18 @@ impl SessionRuntime {
             ));
         }
         let _ = self.shutdown_tx.send(());
+        self.run_stop_command();
+
+        #[cfg(unix)]
+        let group_terminated = self.terminate_process_group();
+        #[cfg(not(unix))]
+        let group_terminated = false;
 
         // Retry kill with exponential backoff
         for attempt in 0..3 {
+            if group_terminated {
+                break;
+            }
             match self.child.kill() {
                 Ok(_) => {
                     diagnostics::log(format!(
@@ -184,8 +227,179 @@ impl SessionRuntime {
     }
 }
 
+impl SessionRuntime {
+    fn run_stop_command(&mut self) {
+        let Some(command) = self.stop_command.take() else {
+            return;
+        };
+        let Some((program, args)) = command.split_first() else {
+            return;
+        };
+        let result = std::process::Command::new(program)
+            .args(args)
+            .stdin(std::process::Stdio::null())
+            .output();
+        match result {
+            Ok(output) => diagnostics::log(format!(
+                "session_runtime_shutdown id={} stop_command={} status={} os={}",
+                self.id,
+                program,
+                output.status,
+                std::env::consts::OS
+            )),
+            Err(e) => diagnostics::log(format!(
+                "session_runtime_shutdown id={} stop_command={} error={} os={}",
+                self.id,
+                program,
+                e,
+                std::env::consts::OS
+            )),
+        }
+    }
+}
+
+#[cfg(unix)]
+impl SessionRuntime {
+    /// portable-pty starts the child with `setsid`, so it leads its own process
+    /// group. Signal the whole group: SIGTERM, a grace period, then SIGKILL, so
+    /// dev servers and MCP subprocesses don't outlive the session. Returns false
+    /// if the child's pid is unknown.
+    fn terminate_process_group(&mut self) -> bool {
+        let Some(pid) = self.child.process_id() else {
+            return false;
+        };
+        let Ok(pgid) = i32::try_from(pid) else {
+            return false;
+        };
+        // Descendants that moved to their own group are found now, before the
+        // leader exits and they get reparented out of the tree.
+        let strays: Vec<i32> = super::monitor::descendant_pids(pid)
+            .into_iter()
+            .filter_map(|p| i32::try_from(p).ok())
+            // SAFETY: getpgid has no memory-safety preconditions.
+            .filter(|p| unsafe { libc::getpgid(*p) } != pgid)
+            .collect();
+
+        signal(-pgid, libc::SIGTERM);
+        for stray in &strays {
+            signal(*stray, libc::SIGTERM);
+        }
+
+        // Wait without reaping: while the leader is a zombie its pid, and so the
+        // group id, can't be handed to an unrelated process.
+        let deadline = Instant::now() + PROCESS_GROUP_GRACE;
+        while Instant::now() < deadline {
+            if leader_exited(pgid) {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+
+        // Anything that ignored SIGTERM, including the leader itself. If the
+        // leader was reaped before shutdown, only signal a group that still exists.
+        let killed = signal(-pgid, 0) && signal(-pgid, libc::SIGKILL);
+        for stray in &strays {
+            signal(*stray, libc::SIGKILL);
+        }
+        diagnostics::log(format!(
+            "session_runtime_shutdown id={} pgid={} strays={} sigkill_needed={} os={}",
+            self.id,
+            pgid,
+            strays.len(),
+            killed,
+            std::env::consts::OS
+        ));
+        true
+    }
+}
+
+/// How long a session's process group gets to exit after SIGTERM.
+#[cfg(unix)]
+const PROCESS_GROUP_GRACE: Duration = Duration::from_secs(3);
+
+/// Send `sig` to a pid (or, when negative, a process group). Returns whether
+/// anything received it.
+#[cfg(unix)]
+fn signal(target: i32, sig: i32) -> bool {
+    // SAFETY: kill has no memory-safety preconditions.
+    unsafe { libc::kill(target, sig) == 0 }
+}
+
+/// Whether our child `pid` has exited (or was already reaped), leaving it
+/// unreaped so the caller can still signal its process group safely.
+#[cfg(unix)]
+fn leader_exited(pid: i32) -> bool {
+    // SAFETY: siginfo_t is plain data; zeroed is a valid initial value.
+    let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+    // SAFETY: info is a valid, writable siginfo_t for waitid to fill in.
+    let result = unsafe {
+        libc::waitid(
+            libc::P_PID,
+            pid as libc::id_t,
+            &mut info,
+            libc::WEXITED | libc::WNOHANG | libc::WNOWAIT,
+        )
+    };
+    // With WNOHANG, si_pid stays 0 while the child is still running.
+    // SAFETY: waitid filled in info, or left it zeroed.
+    result != 0 || unsafe { info.si_pid() } != 0
+}
+
 impl Drop for SessionRuntime {
     fn drop(&mut self) {
         self.shutdown();
     }
 }
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use portable_pty::{CommandBuilder, NativePtySystem, PtySystem};
+    use std::sync::mpsc;
+
+    #[test]
+    fn test_shutdown_kills_whole_process_group() {
+        let pair = NativePtySystem::default()
+            .openpty(PtySize {
+                rows: 24,
+                cols: 80,
+                pixel_width: 0,
+                pixel_height: 0,
+            })
+            .unwrap();
+        let mut cmd = CommandBuilder::new("/bin/sh");
+        // Both processes ignore SIGHUP and SIGTERM, so only SIGKILL to the group stops them.
+        cmd.args(["-c", "trap '' HUP TERM; sleep 300 & wait"]);
+        let child = pair.slave.spawn_command(cmd).unwrap();
+        let root = child.process_id().unwrap();
+        let writer = pair.master.take_writer().unwrap();
+        let (shutdown_tx, _shutdown_rx) = mpsc::channel();
+        let reader_thread = std::thread::spawn(|| {});
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut grandchildren = Vec::new();
+        while grandchildren.is_empty() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(50));
+            grandchildren = super::super::monitor::descendant_pids(root);
+        }
+        assert!(!grandchildren.is_empty());
+
+        let mut runtime = SessionRuntime::new(
+            pair.master,
+            writer,
+            child,
+            reader_thread,
+            shutdown_tx,
+            "session-1".to_string(),
+        );
+        runtime.shutdown();
+
+        std::thread::sleep(Duration::from_millis(100));
+        for pid in grandchildren {
+            let alive = std::fs::read_to_string(format!("/proc/{}/stat", pid))
+                .map(|stat| !stat.contains(") Z "))
+                .unwrap_or(false);
+            assert!(!alive, "process {} survived shutdown", pid);
+        }
+    }
+}
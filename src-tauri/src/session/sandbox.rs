@@ -0,0 +1,251 @@
+// ABOUTME: Wraps a session's command in a sandbox, either bubblewrap namespaces or a user wrapper.
+// ABOUTME: Only the project directory and configured paths stay writable; network access is optional.
+
+use std::path::{Path, PathBuf};
+
+use super::model::{SandboxMode, SessionSandbox};
+use super::tools::CommandSpec;
+use agentterm_shared::socket_path::{get_agent_term_mcp_run_dir, get_agent_term_run_dir};
+use crate::diagnostics;
+use crate::mcp::config::{expand_tilde, SandboxSettings};
+
+/// Convert a tool's `[tools.<name>.sandbox]` table from config.toml.
+pub fn from_settings(settings: &SandboxSettings) -> Result<SessionSandbox, String> {
+    let mode = match settings.mode.trim().to_lowercase().as_str() {
+        "" | "bubblewrap" | "bwrap" => SandboxMode::Bubblewrap,
+        "wrapper" => SandboxMode::Wrapper,
+        other => return Err(format!("Unknown sandbox mode: {}", other)),
+    };
+    Ok(SessionSandbox {
+        enabled: settings.enabled,
+        mode,
+        wrapper: settings.wrapper.clone(),
+        writable_paths: settings.writable_paths.clone(),
+        network: settings.network,
+    })
+}
+
+pub fn validate(sandbox: &SessionSandbox) -> Result<(), String> {
+    if sandbox.enabled && sandbox.mode == SandboxMode::Wrapper && sandbox.wrapper.is_empty() {
+        return Err("Wrapper sandbox needs a wrapper command".to_string());
+    }
+    Ok(())
+}
+
+/// Rewrite `spec` so it runs inside `sandbox`. `tool_paths` are extra writable
+/// paths the tool itself needs, such as its config directory.
+pub fn wrap(
+    spec: CommandSpec,
+    sandbox: &SessionSandbox,
+    project_path: &str,
+    tool_paths: &[PathBuf],
+) -> Result<CommandSpec, String> {
+    validate(sandbox)?;
+    if project_path.trim().is_empty() {
+        return Err("Sandboxed sessions need a project path".to_string());
+    }
+    let project = std::fs::canonicalize(project_path)
+        .map_err(|e| format!("Invalid path '{}': {}", project_path, e))?;
+
+    match sandbox.mode {
+        SandboxMode::Bubblewrap => {
+            if !cfg!(target_os = "linux") {
+                return Err("Bubblewrap sandboxes are only available on Linux".to_string());
+            }
+            let writable: Vec<PathBuf> = sandbox
+                .writable_paths
+                .iter()
+                .map(|path| expand_tilde(path))
+                .chain(tool_paths.iter().cloned())
+                .collect();
+            Ok(bubblewrap(spec, &project, &writable, sandbox.network))
+        }
+        SandboxMode::Wrapper => Ok(wrapper(spec, &sandbox.wrapper, &project)),
+    }
+}
+
+/// Read-only view of the host with a minimal /dev, its own /proc, private /tmp,
+/// writable binds for the project and `writable`, and optionally no network.
+/// Host device nodes such as block devices are not reachable.
+fn bubblewrap(spec: CommandSpec, project: &Path, writable: &[PathBuf], network: bool) -> CommandSpec {
+    let mut args: Vec<String> = [
+        "--ro-bind", "/", "/",
+        "--dev", "/dev",
+        // A fresh procfs needs its own pid namespace.
+        "--unshare-pid",
+        "--proc", "/proc",
+        "--tmpfs", "/tmp",
+        "--die-with-parent",
+    ]
+    .iter()
+    .map(|arg| arg.to_string())
+    .collect();
+
+    if let (Ok(run_dir), Ok(mcp_run_dir)) = (get_agent_term_run_dir(), get_agent_term_mcp_run_dir()) {
+        args.extend(socket_dir_args(&run_dir, &mcp_run_dir));
+    }
+
+    // Binds come after the /tmp tmpfs so a project under /tmp stays visible.
+    for path in std::iter::once(project).chain(writable.iter().map(PathBuf::as_path)) {
+        if !path.exists() {
+            diagnostics::log(format!("sandbox_writable_missing path={}", path.display()));
+            continue;
+        }
+        let path = path.display().to_string();
+        args.extend(["--bind".to_string(), path.clone(), path]);
+    }
+    if !network {
+        args.push("--unshare-net".to_string());
+    }
+    args.extend(["--chdir".to_string(), project.display().to_string()]);
+    args.push("--".to_string());
+    args.push(spec.program);
+    args.extend(spec.args);
+
+    CommandSpec {
+        program: "bwrap".to_string(),
+        args,
+        env: spec.env,
+    }
+}
+
+/// Hide the app's socket directory, control socket included, behind a tmpfs
+/// and put back only the MCP pool's sockets. Connecting to a socket works on a
+/// read-only bind.
+fn socket_dir_args(run_dir: &Path, mcp_run_dir: &Path) -> Vec<String> {
+    let mut args = Vec::new();
+    if !run_dir.exists() {
+        return args;
+    }
+    args.extend(["--tmpfs".to_string(), run_dir.display().to_string()]);
+    if mcp_run_dir.exists() {
+        let path = mcp_run_dir.display().to_string();
+        args.extend(["--ro-bind".to_string(), path.clone(), path]);
+    }
+    args
+}
+
+/// `wrapper[0] wrapper[1..] program args`, with `{project}` substituted.
+/// Filesystem and network rules are left to the wrapper.
+fn wrapper(spec: CommandSpec, wrapper: &[String], project: &Path) -> CommandSpec {
+    let project = project.display().to_string();
+    let mut parts = wrapper.iter().map(|part| part.replace("{project}", &project));
+    let program = parts.next().unwrap_or_default();
+    let mut args: Vec<String> = parts.collect();
+    args.push(spec.program);
+    args.extend(spec.args);
+    CommandSpec {
+        program,
+        args,
+        env: spec.env,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn spec() -> CommandSpec {
+        CommandSpec {
+            program: "claude".to_string(),
+            args: vec!["--resume".to_string(), "abc".to_string()],
+            env: vec![("PATH".to_string(), "/usr/bin".to_string())],
+        }
+    }
+
+    #[test]
+    fn test_bubblewrap_binds_project_and_disables_network() {
+        let project = TempDir::new().unwrap();
+        let cache = TempDir::new().unwrap();
+        let project_path = project.path().canonicalize().unwrap();
+        let cache_path = cache.path().to_path_buf();
+
+        let wrapped = bubblewrap(
+            spec(),
+            &project_path,
+            &[cache_path.clone(), PathBuf::from("/does/not/exist")],
+            false,
+        );
+        assert_eq!(wrapped.program, "bwrap");
+        let args = wrapped.args.join(" ");
+        let project_str = project_path.display().to_string();
+        let cache_str = cache_path.display().to_string();
+        assert!(args.starts_with("--ro-bind / / --dev /dev --unshare-pid --proc /proc --tmpfs /tmp"));
+        assert!(!args.contains("--dev-bind"));
+        assert!(args.contains(&format!("--bind {} {}", project_str, project_str)));
+        assert!(args.contains(&format!("--bind {} {}", cache_str, cache_str)));
+        assert!(!args.contains("/does/not/exist"));
+        assert!(args.contains("--unshare-net"));
+        assert!(args.ends_with(&format!("--chdir {} -- claude --resume abc", project_str)));
+        assert_eq!(wrapped.env, spec().env);
+    }
+
+    #[test]
+    fn test_socket_dir_is_hidden_except_mcp_sockets() {
+        let run = TempDir::new().unwrap();
+        let mcp = run.path().join("mcp");
+        std::fs::create_dir(&mcp).unwrap();
+
+        let args = socket_dir_args(run.path(), &mcp).join(" ");
+        let run_str = run.path().display().to_string();
+        let mcp_str = mcp.display().to_string();
+        assert_eq!(args, format!("--tmpfs {} --ro-bind {} {}", run_str, mcp_str, mcp_str));
+        assert!(socket_dir_args(&run.path().join("missing"), &mcp).is_empty());
+    }
+
+    #[test]
+    fn test_wrapper_substitutes_project() {
+        let project = TempDir::new().unwrap();
+        let sandbox = SessionSandbox {
+            enabled: true,
+            mode: SandboxMode::Wrapper,
+            wrapper: vec![
+                "firejail".to_string(),
+                "--whitelist={project}".to_string(),
+            ],
+            writable_paths: Vec::new(),
+            network: true,
+        };
+        let project_str = project.path().display().to_string();
+        let wrapped = wrap(spec(), &sandbox, &project_str, &[]).unwrap();
+        let canonical = project.path().canonicalize().unwrap();
+        assert_eq!(wrapped.program, "firejail");
+        assert_eq!(
+            wrapped.args,
+            vec![
+                format!("--whitelist={}", canonical.display()),
+                "claude".to_string(),
+                "--resume".to_string(),
+                "abc".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_wrap_requires_project_and_wrapper_command() {
+        let mut sandbox = SessionSandbox {
+            enabled: true,
+            mode: SandboxMode::Wrapper,
+            wrapper: Vec::new(),
+            writable_paths: Vec::new(),
+            network: true,
+        };
+        assert!(validate(&sandbox).is_err());
+        sandbox.mode = SandboxMode::Bubblewrap;
+        assert!(wrap(spec(), &sandbox, "", &[]).is_err());
+    }
+
+    #[test]
+    fn test_from_settings_rejects_unknown_mode() {
+        let settings = SandboxSettings {
+            mode: "chroot".to_string(),
+            ..SandboxSettings::default()
+        };
+        assert!(from_settings(&settings).is_err());
+        assert_eq!(
+            from_settings(&SandboxSettings::default()).unwrap().mode,
+            SandboxMode::Bubblewrap
+        );
+    }
+}
@@ -0,0 +1,44 @@
+// ABOUTME: Drives cron-scheduled sessions from a background thread.
+// ABOUTME: Starts due jobs, enforces their max runtime and records each run's outcome.
+
+use std::thread;
+use std::time::Duration;
+
+use tauri::{AppHandle, Manager};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+use super::cron::CronSchedule;
+use super::SessionManager;
+
+/// How often due schedules and active runs are checked.
+const TICK_INTERVAL: Duration = Duration::from_secs(20);
+
+/// Run history kept per schedule.
+pub const MAX_RUNS_KEPT: usize = 20;
+
+/// Sessions of a template schedule kept around, counting the one being started.
+pub const TEMPLATE_SESSIONS_KEPT: usize = 3;
+
+/// Start the scheduler thread. Runs for the lifetime of the app.
+pub fn spawn_scheduler(app: AppHandle) {
+    thread::spawn(move || loop {
+        thread::sleep(TICK_INTERVAL);
+        let manager = app.state::<SessionManager>();
+        manager.tick_schedules(&app, OffsetDateTime::now_utc());
+    });
+}
+
+/// Next fire time of `cron` after `after`, formatted like other record timestamps.
+pub fn next_run_at(cron: &str, after: OffsetDateTime) -> Result<Option<String>, String> {
+    let schedule = CronSchedule::parse(cron)?;
+    Ok(schedule.next_after(after).map(format_timestamp))
+}
+
+pub fn parse_timestamp(value: &str) -> Option<OffsetDateTime> {
+    OffsetDateTime::parse(value, &Rfc3339).ok()
+}
+
+pub fn format_timestamp(value: OffsetDateTime) -> String {
+    value.format(&Rfc3339).unwrap_or_default()
+}
@@ -0,0 +1,148 @@
+// ABOUTME: Keeps a plain-text tail of each session's terminal output for the control API.
+// ABOUTME: Strips ANSI escape sequences incrementally so sequences split across reads are handled.
+
+use std::collections::VecDeque;
+
+/// Lines kept per session.
+const MAX_LINES: usize = 2000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EscapeState {
+    Text,
+    Escape,
+    Csi,
+    Osc,
+    OscEscape,
+}
+
+/// Recent output of a session as plain text lines.
+///
+/// Example:
+/// ```rust,ignore
+/// let mut screen = ScreenBuffer::default();
+/// screen.push(b"\x1b[32mready\x1b[0m\r\n> ");
+/// assert_eq!(screen.tail(2), vec!["ready", "> "]);
+/// ```
+#[derive(Debug)]
+pub struct ScreenBuffer {
+    lines: VecDeque<String>,
+    current: Vec<u8>,
+    state: EscapeState,
+    carriage_return: bool,
+}
+
+impl Default for ScreenBuffer {
+    fn default() -> Self {
+        Self {
+            lines: VecDeque::new(),
+            current: Vec::new(),
+            state: EscapeState::Text,
+            carriage_return: false,
+        }
+    }
+}
+
+impl ScreenBuffer {
+    pub fn push(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.state = match self.state {
+                EscapeState::Text => self.text_byte(byte),
+                EscapeState::Escape => match byte {
+                    b'[' => EscapeState::Csi,
+                    b']' => EscapeState::Osc,
+                    // Two-byte sequences such as ESC 7 or ESC =.
+                    _ => EscapeState::Text,
+                },
+                // Parameters and intermediates until a final byte in @..~.
+                EscapeState::Csi => match byte {
+                    0x40..=0x7e => EscapeState::Text,
+                    _ => EscapeState::Csi,
+                },
+                // Window titles and hyperlinks end with BEL or ESC \.
+                EscapeState::Osc => match byte {
+                    0x07 => EscapeState::Text,
+                    0x1b => EscapeState::OscEscape,
+                    _ => EscapeState::Osc,
+                },
+                EscapeState::OscEscape => match byte {
+                    b'\\' => EscapeState::Text,
+                    _ => EscapeState::Osc,
+                },
+            };
+        }
+    }
+
+    fn text_byte(&mut self, byte: u8) -> EscapeState {
+        match byte {
+            0x1b => return EscapeState::Escape,
+            // A carriage return not followed by a newline redraws the line.
+            b'\r' => {
+                self.carriage_return = true;
+                return EscapeState::Text;
+            }
+            b'\n' => {}
+            _ if self.carriage_return => self.current.clear(),
+            _ => {}
+        }
+        self.carriage_return = false;
+        match byte {
+            b'\n' => {
+                let line = String::from_utf8_lossy(&self.current).trim_end().to_string();
+                self.current.clear();
+                self.lines.push_back(line);
+                if self.lines.len() > MAX_LINES {
+                    self.lines.pop_front();
+                }
+            }
+            0x08 => {
+                self.current.pop();
+            }
+            b'\t' => self.current.push(byte),
+            0x00..=0x1f | 0x7f => {}
+            _ => self.current.push(byte),
+        }
+        EscapeState::Text
+    }
+
+    /// Last `count` lines, including the unterminated line the cursor is on.
+    pub fn tail(&self, count: usize) -> Vec<String> {
+        let mut lines: Vec<String> = self.lines.iter().cloned().collect();
+        if !self.current.is_empty() {
+            lines.push(String::from_utf8_lossy(&self.current).to_string());
+        }
+        let start = lines.len().saturating_sub(count);
+        lines.split_off(start)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_strips_colors_and_titles() {
+        let mut screen = ScreenBuffer::default();
+        screen.push(b"\x1b]0;claude\x07\x1b[1;32mready\x1b[0m\r\n> ");
+        assert_eq!(screen.tail(10), vec!["ready", "> "]);
+    }
+
+    #[test]
+    fn test_push_handles_sequences_split_across_reads() {
+        let mut screen = ScreenBuffer::default();
+        screen.push(b"one\x1b[3");
+        screen.push(b"8;5;12mtwo\n");
+        screen.push(b"progress 10%\rprogress 100%\n");
+        assert_eq!(screen.tail(10), vec!["onetwo", "progress 100%"]);
+    }
+
+    #[test]
+    fn test_tail_keeps_most_recent_lines() {
+        let mut screen = ScreenBuffer::default();
+        for i in 0..(MAX_LINES + 5) {
+            screen.push(format!("line {}\n", i).as_bytes());
+        }
+        let tail = screen.tail(2);
+        assert_eq!(tail, vec![format!("line {}", MAX_LINES + 3), format!("line {}", MAX_LINES + 4)]);
+        assert_eq!(screen.tail(usize::MAX).len(), MAX_LINES);
+    }
+}
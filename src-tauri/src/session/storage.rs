@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::fs;
 use std::io::BufWriter;
 use std::path::{Path, PathBuf};
@@ -9,10 +10,11 @@ use std::time::{Duration, Instant};
 use parking_lot::Mutex as PLMutex;
 use serde::{Deserialize, Serialize};
 
+use super::checkpoint::CheckpointRecord;
 use super::error::{StorageError, StorageResult};
-use super::model::{SectionRecord, SessionRecord};
+use super::model::{QueuedPrompt, ScheduleRecord, SectionRecord, SessionRecord};
 
-pub const SCHEMA_VERSION: u32 = 1;
+pub const SCHEMA_VERSION: u32 = 2;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -22,6 +24,12 @@ pub struct StorageSnapshot {
     pub sessions: Vec<SessionRecord>,
     pub sections: Vec<SectionRecord>,
     pub active_session_id: Option<String>,
+    #[serde(default)]
+    pub checkpoints: Vec<CheckpointRecord>,
+    #[serde(default)]
+    pub prompt_queues: HashMap<String, Vec<QueuedPrompt>>,
+    #[serde(default)]
+    pub schedules: Vec<ScheduleRecord>,
 }
 
 fn default_schema_version() -> u32 {
@@ -47,6 +55,9 @@ impl Storage {
                 sessions: Vec::new(),
                 sections: Vec::new(),
                 active_session_id: None,
+                checkpoints: Vec::new(),
+                prompt_queues: HashMap::new(),
+                schedules: Vec::new(),
             });
         }
         let data = fs::read_to_string(&path).map_err(|e| StorageError::ReadError(e.to_string()))?;
@@ -73,6 +84,9 @@ impl Storage {
     }
 
     fn migrate(&self, mut snapshot: StorageSnapshot) -> StorageResult<StorageSnapshot> {
+        if snapshot.schema_version < 2 {
+            migrate_v1_to_v2(&mut snapshot);
+        }
         if snapshot.schema_version < SCHEMA_VERSION {
             snapshot.schema_version = SCHEMA_VERSION;
         }
@@ -145,6 +159,33 @@ impl Storage {
     }
 }
 
+/// v2 adds nested sections and section defaults (both default to empty) and
+/// makes `order` and `tab_order` dense: v1 could leave gaps, duplicates and
+/// sessions without a tab position.
+fn migrate_v1_to_v2(snapshot: &mut StorageSnapshot) {
+    let mut sections: Vec<(u32, usize)> = snapshot
+        .sections
+        .iter()
+        .enumerate()
+        .map(|(index, section)| (section.order, index))
+        .collect();
+    sections.sort();
+    for (order, (_, index)) in sections.into_iter().enumerate() {
+        snapshot.sections[index].order = order as u32;
+    }
+
+    let mut sessions: Vec<(u32, usize)> = snapshot
+        .sessions
+        .iter()
+        .enumerate()
+        .map(|(index, session)| (session.tab_order.unwrap_or(u32::MAX), index))
+        .collect();
+    sessions.sort();
+    for (order, (_, index)) in sessions.into_iter().enumerate() {
+        snapshot.sessions[index].tab_order = Some(order as u32);
+    }
+}
+
 pub fn default_storage_root() -> PathBuf {
     dirs::home_dir()
         .unwrap_or_else(|| Path::new("/").to_path_buf())
@@ -274,6 +315,9 @@ mod tests {
             sessions: vec![],
             sections: vec![],
             active_session_id: Some("test-id".to_string()),
+            checkpoints: Vec::new(),
+            prompt_queues: HashMap::new(),
+            schedules: Vec::new(),
         };
 
         storage.save(&snapshot).unwrap();
@@ -281,6 +325,50 @@ mod tests {
         assert_eq!(loaded.active_session_id, Some("test-id".to_string()));
     }
 
+    #[test]
+    fn test_load_migrates_v1_snapshot() {
+        let temp = TempDir::new().unwrap();
+        let storage = Storage::new(temp.path().to_path_buf(), "test".to_string());
+        let v1 = r#"{
+            "schemaVersion": 1,
+            "sessions": [
+                {"id": "b", "title": "B", "projectPath": "", "sectionId": "s1", "tool": "shell",
+                 "command": "bash", "status": "idle", "createdAt": "", "lastAccessedAt": null,
+                 "claudeSessionId": null, "geminiSessionId": null, "loadedMcpNames": [],
+                 "isOpen": true, "tabOrder": null},
+                {"id": "a", "title": "A", "projectPath": "", "sectionId": "s1", "tool": "shell",
+                 "command": "bash", "status": "idle", "createdAt": "", "lastAccessedAt": null,
+                 "claudeSessionId": null, "geminiSessionId": null, "loadedMcpNames": [],
+                 "isOpen": true, "tabOrder": 7}
+            ],
+            "sections": [
+                {"id": "s2", "name": "Two", "path": "", "collapsed": false, "order": 9},
+                {"id": "s1", "name": "One", "path": "", "collapsed": false, "order": 4}
+            ],
+            "activeSessionId": null
+        }"#;
+        let path = storage.file_path();
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, v1).unwrap();
+
+        let loaded = storage.load().unwrap();
+        assert_eq!(loaded.schema_version, SCHEMA_VERSION);
+        let order: Vec<(&str, u32)> = loaded
+            .sections
+            .iter()
+            .map(|section| (section.id.as_str(), section.order))
+            .collect();
+        assert_eq!(order, vec![("s2", 1), ("s1", 0)]);
+        assert!(loaded.sections.iter().all(|section| section.parent_id.is_none()));
+        let tabs: Vec<(&str, Option<u32>)> = loaded
+            .sessions
+            .iter()
+            .map(|session| (session.id.as_str(), session.tab_order))
+            .collect();
+        assert_eq!(tabs, vec![("b", Some(1)), ("a", Some(0))]);
+        assert!(loaded.sessions.iter().all(|session| session.env.is_empty()));
+    }
+
     #[test]
     fn test_backup_rotation() {
         let temp = TempDir::new().unwrap();
@@ -292,6 +380,9 @@ mod tests {
                 sessions: vec![],
                 sections: vec![],
                 active_session_id: Some(format!("id-{}", i)),
+                checkpoints: Vec::new(),
+                prompt_queues: HashMap::new(),
+                schedules: Vec::new(),
             };
             storage.save(&snapshot).unwrap();
         }
@@ -313,6 +404,9 @@ mod tests {
             sessions: vec![],
             sections: vec![],
             active_session_id: Some("backup-id".to_string()),
+            checkpoints: Vec::new(),
+            prompt_queues: HashMap::new(),
+            schedules: Vec::new(),
         };
         // First save creates the file
         storage.save(&snapshot).unwrap();
@@ -1,12 +1,49 @@
-use super::model::{SessionRecord, SessionTool};
+use std::path::{Path, PathBuf};
+
+use super::model::{SessionRecord, SessionSandbox, SessionTool};
+use super::{container, remote, sandbox};
+use agentterm_shared::pool::PROJECT_PATH_ENV;
 use crate::diagnostics;
 use crate::mcp::config::{
     get_managed_global_mcp_path,
     get_user_project_mcp_path,
+    expand_tilde,
+    ResourceLimits,
+    ToolDef,
+    UserConfig,
 };
 use crate::mcp::get_claude_config_dir;
 use crate::mcp::proxy::proxy_bin_dir;
 
+/// Lets the agent-term MCP server tell which session is calling it.
+const SESSION_ID_ENV: &str = "AGENT_TERM_SESSION_ID";
+
+/// The project a session's tool reports to the MCP pool. Project tool policies
+/// name the repository, so a worktree of it (such as an orchestrator step's) is
+/// reported as the repository.
+pub(super) fn reported_project_path(project_path: &str) -> Option<String> {
+    if project_path.is_empty() {
+        return None;
+    }
+    let reported = main_checkout(Path::new(project_path))
+        .map(|path| path.display().to_string())
+        .unwrap_or_else(|| project_path.to_string());
+    Some(reported)
+}
+
+/// The main checkout of a linked git worktree, whose `.git` file points at
+/// `<repo>/.git/worktrees/<name>`. `None` for anything else.
+fn main_checkout(project_path: &Path) -> Option<PathBuf> {
+    let git_file = std::fs::read_to_string(project_path.join(".git")).ok()?;
+    let git_dir = project_path.join(git_file.strip_prefix("gitdir:")?.trim());
+    let worktrees = git_dir.parent()?;
+    let common_dir = worktrees.parent()?;
+    if worktrees.file_name()? != "worktrees" || common_dir.file_name()? != ".git" {
+        return None;
+    }
+    common_dir.parent().map(Path::to_path_buf)
+}
+
 /// Validate session ID contains only safe characters
 fn validate_session_id(id: &str) -> bool {
     !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
@@ -29,25 +66,244 @@ pub struct CommandSpec {
     pub env: Vec<(String, String)>,
 }
 
-pub fn build_command(record: &SessionRecord) -> Result<CommandSpec, String> {
-    match &record.tool {
+pub fn build_command(record: &SessionRecord, config: &UserConfig) -> Result<CommandSpec, String> {
+    let sandbox = resolve_sandbox(record, config)?;
+    if let Some(session_remote) = &record.remote {
+        if sandbox.is_some() || record.container.is_some() {
+            return Err("Remote sessions cannot use a sandbox or container".to_string());
+        }
+        return remote::build_remote_command(record, session_remote);
+    }
+    let mut spec = match &record.tool {
         SessionTool::Shell => Ok(CommandSpec {
             program: record.command.clone(),
             args: vec!["-l".to_string(), "-i".to_string()],
             env: append_proxy_path_env(Vec::new()),
         }),
-        SessionTool::Claude => build_claude_command(record),
+        SessionTool::Claude => {
+            build_claude_command(record, config.claude.dangerous_mode, sandbox.is_some())
+        }
         SessionTool::Gemini => build_gemini_command(record),
         SessionTool::Codex | SessionTool::OpenCode | SessionTool::Custom(_) => Ok(CommandSpec {
             program: record.command.clone(),
             args: Vec::new(),
             env: append_proxy_path_env(Vec::new()),
         }),
+    }?;
+    spec.env.extend(
+        record
+            .env
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone())),
+    );
+    spec.env.push((SESSION_ID_ENV.to_string(), record.id.clone()));
+    if let Some(project_path) = reported_project_path(&record.project_path) {
+        spec.env.push((PROJECT_PATH_ENV.to_string(), project_path));
+    }
+
+    let limits = tool_def(config, &record.tool)
+        .map(|def| def.limits.clone())
+        .unwrap_or_default();
+
+    if let Some(session_container) = &record.container {
+        if sandbox.is_some() {
+            return Err("A session cannot use both a container and a sandbox".to_string());
+        }
+        let tool_paths = tool_state_paths(&record.tool, &spec);
+        return container::wrap(
+            spec,
+            session_container,
+            &record.id,
+            &record.project_path,
+            &tool_paths,
+            &limits,
+        );
+    }
+
+    let spec = if limits.is_empty() {
+        spec
+    } else {
+        apply_resource_limits(spec, &limits)?
+    };
+
+    match sandbox {
+        Some(sandbox) => {
+            let tool_paths = tool_state_paths(&record.tool, &spec);
+            sandbox::wrap(spec, &sandbox, &record.project_path, &tool_paths)
+        }
+        None => Ok(spec),
+    }
+}
+
+/// The session's own sandbox when set, otherwise its tool's. `None` when disabled.
+fn resolve_sandbox(
+    record: &SessionRecord,
+    config: &UserConfig,
+) -> Result<Option<SessionSandbox>, String> {
+    let sandbox = match &record.sandbox {
+        Some(sandbox) => sandbox.clone(),
+        None => match tool_def(config, &record.tool) {
+            Some(def) => sandbox::from_settings(&def.sandbox)?,
+            None => return Ok(None),
+        },
+    };
+    Ok(sandbox.enabled.then_some(sandbox))
+}
+
+/// Paths a tool writes its own state to, which must stay writable in a sandbox.
+/// Only directories are returned: tools replace their state files by renaming
+/// a temp file over them, which fails on a single-file bind mount.
+fn tool_state_paths(tool: &SessionTool, spec: &CommandSpec) -> Vec<PathBuf> {
+    match tool {
+        SessionTool::Claude => spec
+            .env
+            .iter()
+            .filter(|(key, _)| key == "CLAUDE_CONFIG_DIR")
+            .map(|(_, value)| {
+                let dir = PathBuf::from(value);
+                seed_claude_state(&dir);
+                dir
+            })
+            .collect(),
+        SessionTool::Gemini => vec![expand_tilde("~/.gemini")],
+        _ => Vec::new(),
+    }
+}
+
+/// With `CLAUDE_CONFIG_DIR` set, Claude keeps `.claude.json` in that directory
+/// instead of the home directory. Copy the home one in the first time so a
+/// sandboxed session starts from the user's existing state.
+fn seed_claude_state(config_dir: &Path) {
+    let seeded = config_dir.join(".claude.json");
+    let home = expand_tilde("~/.claude.json");
+    if seeded.exists() || !home.is_file() {
+        return;
+    }
+    if let Err(err) = std::fs::create_dir_all(config_dir).and_then(|_| std::fs::copy(&home, &seeded)) {
+        diagnostics::log(format!(
+            "claude_state_seed_failed path={} error={}",
+            seeded.display(),
+            err
+        ));
+    }
+}
+
+/// Config entry for a session's tool. Built-in tools can be configured under their
+/// own name (e.g. `[tools.claude]`).
+fn tool_def<'a>(config: &'a UserConfig, tool: &SessionTool) -> Option<&'a ToolDef> {
+    config
+        .tools
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(tool.name()))
+        .map(|(_, def)| def)
+}
+
+/// Apply the tool's resource limits. CPU time and open files are set with
+/// `sh -c 'ulimit ... && exec "$0" "$@"'` so they cover everything the tool
+/// spawns; a limit that cannot be set fails the launch. Memory is capped with
+/// a systemd scope, as `ulimit -v` limits address space rather than memory
+/// and breaks runtimes that reserve large heaps; without `systemd-run` a
+/// memory limit fails the launch too.
+#[cfg(unix)]
+fn apply_resource_limits(spec: CommandSpec, limits: &ResourceLimits) -> Result<CommandSpec, String> {
+    let systemd_run = if cfg!(target_os = "linux") {
+        find_on_path("systemd-run")
+    } else {
+        None
+    };
+    limit_command(spec, limits, systemd_run)
+}
+
+#[cfg(unix)]
+fn limit_command(
+    spec: CommandSpec,
+    limits: &ResourceLimits,
+    systemd_run: Option<PathBuf>,
+) -> Result<CommandSpec, String> {
+    let mut script = String::new();
+    if let Some(cpu_seconds) = limits.cpu_seconds {
+        script.push_str(&format!("ulimit -t {} && ", cpu_seconds));
+    }
+    if let Some(open_files) = limits.open_files {
+        script.push_str(&format!("ulimit -n {} && ", open_files));
     }
+    let spec = if script.is_empty() {
+        spec
+    } else {
+        script.push_str("exec \"$0\" \"$@\"");
+        let mut args = vec!["-c".to_string(), script, spec.program];
+        args.extend(spec.args);
+        CommandSpec {
+            program: "/bin/sh".to_string(),
+            args,
+            env: spec.env,
+        }
+    };
+
+    let Some(memory_mb) = limits.memory_mb else {
+        return Ok(spec);
+    };
+    let Some(systemd_run) = systemd_run else {
+        diagnostics::log(format!(
+            "resource_limits_memory_unsupported program={} memory_mb={}",
+            spec.program, memory_mb
+        ));
+        return Err(format!(
+            "The tool's memory limit ({} MB) needs systemd-run, which was not found; \
+             remove limits.memory_mb or install systemd",
+            memory_mb
+        ));
+    };
+    let mut args = vec![
+        "--user".to_string(),
+        "--scope".to_string(),
+        "--quiet".to_string(),
+        "-p".to_string(),
+        format!("MemoryMax={}M", memory_mb),
+        "--".to_string(),
+        spec.program,
+    ];
+    args.extend(spec.args);
+    Ok(CommandSpec {
+        program: systemd_run.display().to_string(),
+        args,
+        env: spec.env,
+    })
+}
+
+/// First executable named `program` in `PATH`.
+#[cfg(unix)]
+fn find_on_path(program: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .map(|dir| dir.join(program))
+        .find(|candidate| candidate.is_file())
 }
 
-fn build_claude_command(record: &SessionRecord) -> Result<CommandSpec, String> {
+#[cfg(not(unix))]
+fn apply_resource_limits(spec: CommandSpec, _limits: &ResourceLimits) -> Result<CommandSpec, String> {
+    diagnostics::log(format!("resource_limits_unsupported program={}", spec.program));
+    Err("Resource limits are not supported on this platform; remove the tool's limits".to_string())
+}
+
+fn build_claude_command(
+    record: &SessionRecord,
+    dangerous_mode: bool,
+    sandboxed: bool,
+) -> Result<CommandSpec, String> {
     let mut args = build_mcp_config_args(record);
+    if dangerous_mode {
+        if sandboxed || record.container.is_some() {
+            args.push("--dangerously-skip-permissions".to_string());
+        } else {
+            // Dangerous mode is only honoured behind a sandbox or container;
+            // elsewhere the session still starts, with permission prompts.
+            diagnostics::log(format!(
+                "claude_dangerous_mode_ignored id={} reason=unsandboxed",
+                record.id
+            ));
+        }
+    }
     if let Some(session_id) = &record.claude_session_id {
         if !validate_session_id(session_id) {
             return Err(format!("Invalid claude session ID: {}", session_id));
@@ -148,3 +404,185 @@ fn append_proxy_path_env(mut env: Vec<(String, String)>) -> Vec<(String, String)
     env.push(("PATH".to_string(), updated));
     env
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::config::ToolDef;
+    use crate::session::model::SessionStatus;
+
+    fn shell_record() -> SessionRecord {
+        SessionRecord {
+            id: "session-1".to_string(),
+            title: "Shell".to_string(),
+            project_path: String::new(),
+            section_id: "default".to_string(),
+            tool: SessionTool::Shell,
+            command: "/bin/bash".to_string(),
+            icon: None,
+            status: SessionStatus::Idle,
+            created_at: String::new(),
+            last_accessed_at: None,
+            claude_session_id: None,
+            gemini_session_id: None,
+            loaded_mcp_names: Vec::new(),
+            is_open: true,
+            tab_order: None,
+            sandbox: None,
+            container: None,
+            remote: None,
+            created_by: None,
+            env: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_build_command_without_limits_runs_tool_directly() {
+        let mut record = shell_record();
+        record.env.insert("RUST_LOG".to_string(), "debug".to_string());
+        let spec = build_command(&record, &UserConfig::default()).unwrap();
+        assert_eq!(spec.program, "/bin/bash");
+        assert_eq!(spec.args, vec!["-l", "-i"]);
+        assert!(spec
+            .env
+            .contains(&("AGENT_TERM_SESSION_ID".to_string(), "session-1".to_string())));
+        assert!(spec
+            .env
+            .contains(&("RUST_LOG".to_string(), "debug".to_string())));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_build_command_wraps_tool_with_ulimit() {
+        let mut config = UserConfig::default();
+        config.tools.insert(
+            "Shell".to_string(),
+            ToolDef {
+                command: "/bin/bash".to_string(),
+                args: Vec::new(),
+                icon: String::new(),
+                description: String::new(),
+                busy_patterns: Vec::new(),
+                is_shell: true,
+                order: 0,
+                enabled: true,
+                limits: ResourceLimits {
+                    memory_mb: None,
+                    cpu_seconds: None,
+                    open_files: Some(1024),
+                },
+                sandbox: Default::default(),
+            },
+        );
+
+        let spec = build_command(&shell_record(), &config).unwrap();
+        assert_eq!(spec.program, "/bin/sh");
+        assert_eq!(
+            spec.args,
+            vec![
+                "-c",
+                "ulimit -n 1024 && exec \"$0\" \"$@\"",
+                "/bin/bash",
+                "-l",
+                "-i",
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_memory_limit_uses_a_systemd_scope() {
+        let spec = CommandSpec {
+            program: "/bin/bash".to_string(),
+            args: vec!["-i".to_string()],
+            env: Vec::new(),
+        };
+        let limits = ResourceLimits {
+            memory_mb: Some(2048),
+            cpu_seconds: Some(60),
+            open_files: None,
+        };
+
+        let limited = limit_command(
+            spec.clone(),
+            &limits,
+            Some(PathBuf::from("/usr/bin/systemd-run")),
+        )
+        .unwrap();
+        assert_eq!(limited.program, "/usr/bin/systemd-run");
+        assert_eq!(
+            limited.args,
+            vec![
+                "--user",
+                "--scope",
+                "--quiet",
+                "-p",
+                "MemoryMax=2048M",
+                "--",
+                "/bin/sh",
+                "-c",
+                "ulimit -t 60 && exec \"$0\" \"$@\"",
+                "/bin/bash",
+                "-i",
+            ]
+        );
+
+        // Without systemd the launch fails rather than running uncapped or
+        // faking the cap with `ulimit -v`.
+        let err = limit_command(spec.clone(), &limits, None).unwrap_err();
+        assert!(err.contains("systemd-run"), "{}", err);
+        let cpu_only = ResourceLimits {
+            memory_mb: None,
+            ..limits
+        };
+        assert_eq!(limit_command(spec, &cpu_only, None).unwrap().program, "/bin/sh");
+    }
+
+    #[test]
+    fn test_claude_dangerous_mode_requires_sandbox() {
+        let project = tempfile::TempDir::new().unwrap();
+        let mut record = shell_record();
+        record.tool = SessionTool::Claude;
+        record.command = "claude".to_string();
+        record.project_path = project.path().display().to_string();
+        let mut config = UserConfig::default();
+        config.claude.dangerous_mode = true;
+
+        let spec = build_command(&record, &config).unwrap();
+        assert!(!spec.args.contains(&"--dangerously-skip-permissions".to_string()));
+
+        record.sandbox = Some(SessionSandbox {
+            enabled: true,
+            mode: crate::session::model::SandboxMode::Wrapper,
+            wrapper: vec!["sandbox-run".to_string()],
+            writable_paths: Vec::new(),
+            network: false,
+        });
+        let spec = build_command(&record, &config).unwrap();
+        assert_eq!(spec.program, "sandbox-run");
+        assert_eq!(spec.args[0], "claude");
+        assert!(spec.args.contains(&"--dangerously-skip-permissions".to_string()));
+    }
+
+    #[test]
+    fn test_worktree_sessions_report_the_main_checkout() {
+        let repo = tempfile::tempdir().unwrap();
+        let worktree = tempfile::tempdir().unwrap();
+        let git_dir = repo.path().join(".git").join("worktrees").join("step");
+        std::fs::write(
+            worktree.path().join(".git"),
+            format!("gitdir: {}\n", git_dir.display()),
+        )
+        .unwrap();
+        assert_eq!(main_checkout(worktree.path()), Some(repo.path().to_path_buf()));
+        assert_eq!(main_checkout(repo.path()), None);
+
+        let mut record = shell_record();
+        record.project_path = worktree.path().display().to_string();
+        let spec = build_command(&record, &UserConfig::default()).unwrap();
+        assert!(spec.env.contains(&(
+            PROJECT_PATH_ENV.to_string(),
+            repo.path().display().to_string()
+        )));
+    }
+}
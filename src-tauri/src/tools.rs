@@ -1,6 +1,6 @@
 //! Tools and shells configuration management
 
-use crate::mcp::config::{ShellSettings, ToolDef};
+use crate::mcp::config::{ResourceLimits, SandboxSettings, ShellSettings, ToolDef};
 use crate::mcp::McpManager;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -49,12 +49,94 @@ pub struct ToolDefDto {
     pub order: i32,
     #[serde(default = "default_enabled")]
     pub enabled: bool,
+    #[serde(default)]
+    pub limits: ResourceLimitsDto,
+    #[serde(default)]
+    pub sandbox: SandboxSettingsDto,
 }
 
 fn default_enabled() -> bool {
     true
 }
 
+/// ResourceLimits DTO for frontend
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceLimitsDto {
+    #[serde(default)]
+    pub memory_mb: Option<u64>,
+    #[serde(default)]
+    pub cpu_seconds: Option<u64>,
+    #[serde(default)]
+    pub open_files: Option<u64>,
+}
+
+impl From<ResourceLimits> for ResourceLimitsDto {
+    fn from(limits: ResourceLimits) -> Self {
+        Self {
+            memory_mb: limits.memory_mb,
+            cpu_seconds: limits.cpu_seconds,
+            open_files: limits.open_files,
+        }
+    }
+}
+
+impl From<ResourceLimitsDto> for ResourceLimits {
+    fn from(limits: ResourceLimitsDto) -> Self {
+        Self {
+            memory_mb: limits.memory_mb,
+            cpu_seconds: limits.cpu_seconds,
+            open_files: limits.open_files,
+        }
+    }
+}
+
+/// SandboxSettings DTO for frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SandboxSettingsDto {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub mode: String,
+    #[serde(default)]
+    pub wrapper: Vec<String>,
+    #[serde(default)]
+    pub writable_paths: Vec<String>,
+    #[serde(default = "default_enabled")]
+    pub network: bool,
+}
+
+impl Default for SandboxSettingsDto {
+    fn default() -> Self {
+        SandboxSettings::default().into()
+    }
+}
+
+impl From<SandboxSettings> for SandboxSettingsDto {
+    fn from(settings: SandboxSettings) -> Self {
+        Self {
+            enabled: settings.enabled,
+            mode: settings.mode,
+            wrapper: settings.wrapper,
+            writable_paths: settings.writable_paths,
+            network: settings.network,
+        }
+    }
+}
+
+impl From<SandboxSettingsDto> for SandboxSettings {
+    fn from(settings: SandboxSettingsDto) -> Self {
+        Self {
+            enabled: settings.enabled,
+            mode: settings.mode,
+            wrapper: settings.wrapper,
+            writable_paths: settings.writable_paths,
+            network: settings.network,
+        }
+    }
+}
+
 /// ShellSettings DTO for frontend
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -126,6 +208,8 @@ pub async fn tools_get_settings(state: State<'_, McpManager>) -> Result<ToolsSet
                     is_shell: def.is_shell,
                     order: def.order,
                     enabled: def.enabled,
+                    limits: def.limits.into(),
+                    sandbox: def.sandbox.into(),
                 },
             )
         })
@@ -164,6 +248,8 @@ pub async fn tools_set_settings(
                     is_shell: def.is_shell,
                     order: def.order,
                     enabled: def.enabled,
+                    limits: def.limits.into(),
+                    sandbox: def.sandbox.into(),
                 },
             )
         })
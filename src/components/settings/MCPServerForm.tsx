@@ -12,6 +12,8 @@ type MCPServerFormProps = {
   url: string;
   transport: string;
   envText: string;
+  allowTools: string;
+  denyTools: string;
   isPooled: boolean;
   isPoolAllEnabled: boolean;
   onNameChange: (value: string) => void;
@@ -21,6 +23,8 @@ type MCPServerFormProps = {
   onUrlChange: (value: string) => void;
   onTransportChange: (value: string) => void;
   onEnvTextChange: (value: string) => void;
+  onAllowToolsChange: (value: string) => void;
+  onDenyToolsChange: (value: string) => void;
   onIsPooledChange: (value: boolean) => void;
   onSave: () => void;
   onBack: () => void;
@@ -37,6 +41,8 @@ export function MCPServerForm({
   url,
   transport,
   envText,
+  allowTools,
+  denyTools,
   isPooled,
   isPoolAllEnabled,
   onNameChange,
@@ -46,6 +52,8 @@ export function MCPServerForm({
   onUrlChange,
   onTransportChange,
   onEnvTextChange,
+  onAllowToolsChange,
+  onDenyToolsChange,
   onIsPooledChange,
   onSave,
   onBack,
@@ -144,6 +152,27 @@ export function MCPServerForm({
           />
         </label>
 
+        <div className="grid grid-cols-2 gap-4">
+          <label className="dialog-label">
+            Allowed tools (empty = all)
+            <input
+              type="text"
+              value={allowTools}
+              onChange={(e) => onAllowToolsChange(e.target.value)}
+              placeholder="query, list_*"
+            />
+          </label>
+          <label className="dialog-label">
+            Blocked tools
+            <input
+              type="text"
+              value={denyTools}
+              onChange={(e) => onDenyToolsChange(e.target.value)}
+              placeholder="delete_*, drop_table"
+            />
+          </label>
+        </div>
+
         <div className="flex items-center justify-between p-3 rounded-lg border bg-muted/50">
           <div className="flex-1">
             <div className="font-medium">MCP Pool</div>
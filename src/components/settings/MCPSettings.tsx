@@ -63,6 +63,8 @@ export function MCPSettings({
   const [formUrl, setFormUrl] = useState('');
   const [formTransport, setFormTransport] = useState('');
   const [formEnvText, setFormEnvText] = useState('');
+  const [formAllowTools, setFormAllowTools] = useState('');
+  const [formDenyTools, setFormDenyTools] = useState('');
   const [formIsPooled, setFormIsPooled] = useState(false);
   const [validationError, setValidationError] = useState('');
 
@@ -88,6 +90,8 @@ export function MCPSettings({
     setFormUrl('');
     setFormTransport('');
     setFormEnvText('');
+    setFormAllowTools('');
+    setFormDenyTools('');
     setFormIsPooled(false);
     setDialogMode('add');
     setEditingIndex(null);
@@ -105,6 +109,8 @@ export function MCPSettings({
     setFormUrl(item.url);
     setFormTransport(item.transport);
     setFormEnvText(envText[item.id] || '');
+    setFormAllowTools(joinList(item.policy?.allow || []));
+    setFormDenyTools(joinList(item.policy?.deny || []));
     setFormIsPooled(pool.poolMcps.includes(item.name));
     setDialogMode('edit');
     setEditingIndex(index);
@@ -133,6 +139,7 @@ export function MCPSettings({
     }
 
     let updatedPoolMcps = [...pool.poolMcps];
+    const policy = { allow: parseList(formAllowTools), deny: parseList(formDenyTools) };
 
     if (dialogMode === 'add') {
       const newId = makeId();
@@ -146,6 +153,7 @@ export function MCPSettings({
         url: formUrl.trim(),
         transport: formTransport.trim(),
         env: textToEnv(formEnvText),
+        policy,
       };
       onMcpsChange([...mcps, newMcp]);
       onEnvTextChange(newId, formEnvText);
@@ -182,6 +190,7 @@ export function MCPSettings({
               url: formUrl.trim(),
               transport: formTransport.trim(),
               env: textToEnv(formEnvText),
+              policy,
             }
           : m
       );
@@ -278,6 +287,8 @@ export function MCPSettings({
             url={formUrl}
             transport={formTransport}
             envText={formEnvText}
+            allowTools={formAllowTools}
+            denyTools={formDenyTools}
             onNameChange={setFormName}
             onCommandChange={setFormCommand}
             onArgsChange={setFormArgs}
@@ -285,6 +296,8 @@ export function MCPSettings({
             onUrlChange={setFormUrl}
             onTransportChange={setFormTransport}
             onEnvTextChange={setFormEnvText}
+            onAllowToolsChange={setFormAllowTools}
+            onDenyToolsChange={setFormDenyTools}
             isPooled={formIsPooled}
             isPoolAllEnabled={pool.poolAll}
             onIsPooledChange={setFormIsPooled}
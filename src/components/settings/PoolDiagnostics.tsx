@@ -1,6 +1,8 @@
+import { useState } from 'react';
 import { Card, CardHeader, CardTitle, CardDescription, CardContent } from '@/components/ui/card';
 import { Button } from '@/components/ui/button';
 import { StatusDot } from './StatusDot';
+import { useMcpServerLogs } from '@/hooks/useMcpServerLogs';
 import type { PoolStatusResponse, McpServerStatus } from '@/components/sidebar/settingsTypes';
 
 type PoolDiagnosticsProps = {
@@ -27,19 +29,49 @@ function formatUptime(seconds: number | null): string {
   return `${secs}s`;
 }
 
+function formatHealth(server: McpServerStatus): string {
+  if (server.health === 'Unknown') return '-';
+  if (server.health === 'Unhealthy') return 'Unhealthy';
+  return server.lastPingMs === null ? 'Healthy' : `${server.lastPingMs} ms`;
+}
+
+function ServerLogs({ name, onClose }: { name: string; onClose: () => void }) {
+  const { lines, error } = useMcpServerLogs(name);
+
+  return (
+    <div className="mt-4 space-y-2">
+      <div className="flex items-center justify-between">
+        <span className="text-sm font-medium">{name} stderr</span>
+        <Button variant="ghost" size="sm" className="h-7 px-2 text-xs" onClick={onClose}>
+          Close
+        </Button>
+      </div>
+      {error && <div className="text-destructive text-sm">{error}</div>}
+      <pre className="max-h-64 overflow-auto rounded bg-muted p-2 font-mono text-xs whitespace-pre-wrap">
+        {lines.length === 0
+          ? 'No output yet'
+          : lines.map((line) => line.line).join('\n')}
+      </pre>
+    </div>
+  );
+}
+
 function ServerRow({
   server,
   onRestart,
   onStop,
   onStart,
+  onShowLogs,
 }: {
   server: McpServerStatus;
   onRestart: () => void;
   onStop: () => void;
   onStart: () => void;
+  onShowLogs: () => void;
 }) {
   const isRunning = server.status === 'Running';
   const isStarting = server.status === 'Starting';
+  const isIdle = server.status === 'Idle';
   const canControl = server.owned;
 
   return (
@@ -53,8 +85,18 @@ function ServerRow({
           )}
         </div>
       </td>
-      <td className="py-2 px-3 text-sm text-muted-foreground">
+      <td className="py-2 px-3 text-sm text-muted-foreground" title={server.lastError ?? undefined}>
         {server.status}
+        {server.restartCount > 0 && (
+          <span className="ml-1 text-xs">({server.restartCount} restarts)</span>
+        )}
+      </td>
+      <td
+        className={`py-2 px-3 text-sm ${
+          server.health === 'Unhealthy' ? 'text-destructive' : 'text-muted-foreground'
+        }`}
+      >
+        {formatHealth(server)}
       </td>
       <td className="py-2 px-3 text-muted-foreground font-mono text-xs max-w-[200px] truncate">
         {server.socketPath}
@@ -68,7 +110,15 @@ function ServerRow({
       <td className="py-2 px-3">
         {canControl && (
           <div className="flex items-center gap-1">
-            {isRunning || isStarting ? (
+            <Button
+              variant="ghost"
+              size="sm"
+              className="h-7 px-2 text-xs"
+              onClick={onShowLogs}
+            >
+              Logs
+            </Button>
+            {isRunning || isStarting || isIdle ? (
               <>
                 <Button
                   variant="ghost"
@@ -115,6 +165,8 @@ export function PoolDiagnostics({
   onStopServer,
   onStartServer,
 }: PoolDiagnosticsProps) {
+  const [logsFor, setLogsFor] = useState<string | null>(null);
+
   if (!status?.enabled) {
     return (
       <Card>
@@ -156,6 +208,7 @@ export function PoolDiagnostics({
                 <tr className="border-b border-border">
                   <th className="py-2 px-3 text-left font-medium">Server</th>
                   <th className="py-2 px-3 text-left font-medium">Status</th>
+                  <th className="py-2 px-3 text-left font-medium">Health</th>
                   <th className="py-2 px-3 text-left font-medium">Socket</th>
                   <th className="py-2 px-3 text-left font-medium">Uptime</th>
                   <th className="py-2 px-3 text-center font-medium">Conn.</th>
@@ -170,12 +223,14 @@ export function PoolDiagnostics({
                     onRestart={() => onRestartServer(server.name)}
                     onStop={() => onStopServer(server.name)}
                     onStart={() => onStartServer(server.name)}
+                    onShowLogs={() => setLogsFor(server.name)}
                   />
                 ))}
               </tbody>
             </table>
           </div>
         )}
+        {logsFor && <ServerLogs name={logsFor} onClose={() => setLogsFor(null)} />}
       </CardContent>
     </Card>
   );
@@ -20,6 +20,7 @@ const parseList = (value: string) =>
 
 export function PoolSettings({ pool, onPoolChange }: PoolSettingsProps) {
   const excludeMcpsText = useMemo(() => joinList(pool.excludeMcps || []), [pool.excludeMcps]);
+  const recordRedactText = useMemo(() => joinList(pool.recordRedact || []), [pool.recordRedact]);
 
   return (
     <Card>
@@ -89,6 +90,47 @@ export function PoolSettings({ pool, onPoolChange }: PoolSettingsProps) {
               onCheckedChange={(checked) => onPoolChange({ fallbackToStdio: checked === true })}
             />
           </div>
+          <div className="flex items-center space-x-2">
+            <Label htmlFor="pool-autorestart" className="text-sm font-normal">
+              Restart crashed servers
+            </Label>
+            <Checkbox
+              id="pool-autorestart"
+              checked={pool.autoRestart}
+              onCheckedChange={(checked) => onPoolChange({ autoRestart: checked === true })}
+            />
+          </div>
+          <div className="flex items-center space-x-2">
+            <Label htmlFor="pool-healthchecks" className="text-sm font-normal">
+              Health checks
+            </Label>
+            <Checkbox
+              id="pool-healthchecks"
+              checked={pool.healthChecks}
+              onCheckedChange={(checked) => onPoolChange({ healthChecks: checked === true })}
+            />
+          </div>
+          <div className="flex items-center space-x-2">
+            <Label htmlFor="pool-restartunhealthy" className="text-sm font-normal">
+              Restart unresponsive servers
+            </Label>
+            <Checkbox
+              id="pool-restartunhealthy"
+              checked={pool.restartUnhealthy}
+              disabled={!pool.healthChecks}
+              onCheckedChange={(checked) => onPoolChange({ restartUnhealthy: checked === true })}
+            />
+          </div>
+          <div className="flex items-center space-x-2">
+            <Label htmlFor="pool-record" className="text-sm font-normal">
+              Record traffic
+            </Label>
+            <Checkbox
+              id="pool-record"
+              checked={pool.recordTraffic}
+              onCheckedChange={(checked) => onPoolChange({ recordTraffic: checked === true })}
+            />
+          </div>
         </div>
         <div className="space-y-2">
           <Label htmlFor="exclude-mcps">Exclude MCPs (used with "Pool all MCPs")</Label>
@@ -99,6 +141,28 @@ export function PoolSettings({ pool, onPoolChange }: PoolSettingsProps) {
             placeholder="chrome-devtools"
           />
         </div>
+        {pool.recordTraffic && (
+          <div className="grid grid-cols-3 gap-4">
+            <div className="col-span-2 space-y-2">
+              <Label htmlFor="record-redact">Never record these fields</Label>
+              <Input
+                id="record-redact"
+                value={recordRedactText}
+                onChange={(e) => onPoolChange({ recordRedact: parseList(e.target.value) })}
+                placeholder="password, token"
+              />
+            </div>
+            <div className="space-y-2">
+              <Label htmlFor="record-max">Records kept</Label>
+              <Input
+                id="record-max"
+                type="number"
+                value={pool.recordMaxEntries}
+                onChange={(e) => onPoolChange({ recordMaxEntries: Number(e.target.value || 0) })}
+              />
+            </div>
+          </div>
+        )}
         <div className="grid grid-cols-3 gap-4 items-end">
           <div className="space-y-2">
             <Label htmlFor="port-start">Port start</Label>
@@ -118,6 +182,25 @@ export function PoolSettings({ pool, onPoolChange }: PoolSettingsProps) {
               onChange={(e) => onPoolChange({ portEnd: Number(e.target.value || 0) })}
             />
           </div>
+          <div className="space-y-2">
+            <Label htmlFor="max-restarts">Max restarts in a row</Label>
+            <Input
+              id="max-restarts"
+              type="number"
+              value={pool.maxRestarts}
+              onChange={(e) => onPoolChange({ maxRestarts: Number(e.target.value || 0) })}
+            />
+          </div>
+          <div className="space-y-2">
+            <Label htmlFor="idle-timeout">Stop idle servers after (min, 0 = never)</Label>
+            <Input
+              id="idle-timeout"
+              type="number"
+              min={0}
+              value={pool.idleTimeoutMinutes}
+              onChange={(e) => onPoolChange({ idleTimeoutMinutes: Number(e.target.value || 0) })}
+            />
+          </div>
           <div className="flex items-center space-x-2 pb-2">
             <Label htmlFor="pool-status" className="text-sm font-normal">
               Show pool status
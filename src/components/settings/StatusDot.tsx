@@ -11,6 +11,7 @@ const statusColors: Record<ServerStatus, string> = {
   Starting: 'bg-yellow-500 animate-pulse',
   Stopped: 'bg-gray-400',
   Failed: 'bg-red-500',
+  Idle: 'bg-blue-400',
 };
 
 const statusLabels: Record<ServerStatus, string> = {
@@ -18,6 +19,7 @@ const statusLabels: Record<ServerStatus, string> = {
   Starting: 'Starting...',
   Stopped: 'Stopped',
   Failed: 'Failed',
+  Idle: 'Idle (starts on connect)',
 };
 
 export function StatusDot({ status, size = 'sm', showLabel = false }: StatusDotProps) {
@@ -30,12 +30,20 @@ const emptyPool: MCPPoolSettings = {
   portStart: 8001,
   portEnd: 8050,
   startOnDemand: false,
+  idleTimeoutMinutes: 0,
   shutdownOnExit: true,
   poolMcps: [],
   fallbackToStdio: true,
   showPoolStatus: true,
   poolAll: false,
   excludeMcps: [],
+  autoRestart: true,
+  maxRestarts: 5,
+  healthChecks: true,
+  restartUnhealthy: false,
+  recordTraffic: false,
+  recordMaxEntries: 2000,
+  recordRedact: ['password', 'token', 'secret', 'apiKey', 'api_key', 'authorization'],
 };
 
 const envToText = (env: Record<string, string>) =>
@@ -126,6 +134,8 @@ export function MCPDialog({ onClose }: MCPDialogProps) {
           description: item.description.trim(),
           url: item.url.trim(),
           transport: item.transport.trim(),
+          policy: item.policy,
+          project_policies: item.project_policies,
         };
       });
       await invoke('mcp_set_settings', {
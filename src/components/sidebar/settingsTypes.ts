@@ -6,6 +6,13 @@ export type MCPDef = {
   description: string;
   url: string;
   transport: string;
+  policy?: ToolPolicy;
+  project_policies?: Record<string, ToolPolicy>;
+};
+
+export type ToolPolicy = {
+  allow: string[];
+  deny: string[];
 };
 
 export type MCPPoolSettings = {
@@ -14,15 +21,25 @@ export type MCPPoolSettings = {
   portStart: number;
   portEnd: number;
   startOnDemand: boolean;
+  idleTimeoutMinutes: number;
   shutdownOnExit: boolean;
   poolMcps: string[];
   fallbackToStdio: boolean;
   showPoolStatus: boolean;
   poolAll: boolean;
   excludeMcps: string[];
+  autoRestart: boolean;
+  maxRestarts: number;
+  healthChecks: boolean;
+  restartUnhealthy: boolean;
+  recordTraffic: boolean;
+  recordMaxEntries: number;
+  recordRedact: string[];
 };
 
-export type ServerStatus = 'Stopped' | 'Starting' | 'Running' | 'Failed';
+export type ServerStatus = 'Stopped' | 'Starting' | 'Running' | 'Failed' | 'Idle';
+
+export type HealthStatus = 'Unknown' | 'Healthy' | 'Unhealthy';
 
 export type McpServerStatus = {
   name: string;
@@ -31,6 +48,39 @@ export type McpServerStatus = {
   uptimeSeconds: number | null;
   connectionCount: number;
   owned: boolean;
+  restartCount: number;
+  lastError: string | null;
+  health: HealthStatus;
+  lastPingMs: number | null;
+};
+
+export type McpServerLogLine = {
+  seq: number;
+  name: string;
+  timestamp: string;
+  line: string;
+};
+
+export type TrafficQuery = {
+  server?: string;
+  sessionId?: string;
+  method?: string;
+  limit?: number;
+};
+
+export type TrafficRecord = {
+  id: number;
+  server: string;
+  clientId: string;
+  sessionId: string | null;
+  method: string;
+  startedAt: string;
+  durationMs: number | null;
+  params: unknown;
+  result: unknown;
+  error: unknown;
+  requestBytes: number;
+  responseBytes: number;
 };
 
 export type PoolStatusResponse = {
@@ -0,0 +1,54 @@
+import { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import type { McpServerLogLine } from '@/components/sidebar/settingsTypes';
+
+const MAX_LINES = 500;
+
+export function useMcpServerLogs(name: string | null, tail = 200) {
+  const [lines, setLines] = useState<McpServerLogLine[]>([]);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    setLines([]);
+    setError(null);
+    if (!name) return;
+
+    let cancelled = false;
+    let unlisten: (() => void) | null = null;
+
+    const load = async () => {
+      // Subscribe before fetching the backlog so no line falls in between.
+      const stop = await listen<McpServerLogLine>('mcp-server-log', (event) => {
+        if (event.payload.name !== name) return;
+        setLines((current) => [...current, event.payload].slice(-MAX_LINES));
+      });
+      if (cancelled) {
+        stop();
+        return;
+      }
+      unlisten = stop;
+
+      const backlog = await invoke<McpServerLogLine[]>('mcp_server_logs', { name, tail });
+      if (cancelled) return;
+      // Lines that arrived live while the backlog loaded may be in both.
+      const seen = new Set(backlog.map((line) => line.seq));
+      setLines((live) =>
+        [...backlog, ...live.filter((line) => !seen.has(line.seq))].slice(-MAX_LINES)
+      );
+    };
+
+    load().catch((err) => {
+      if (!cancelled) {
+        setError(err instanceof Error ? err.message : String(err));
+      }
+    });
+
+    return () => {
+      cancelled = true;
+      unlisten?.();
+    };
+  }, [name, tail]);
+
+  return { lines, error };
+}